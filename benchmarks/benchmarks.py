@@ -21,6 +21,44 @@ class TimeSuite:
         parse(file)
 
 
+class WideLiteralSuite:
+    # the half size is reported too, parse time should scale linearly
+    params = [0.5, 1.0]
+    param_names = ["size"]
+
+    def setup(self, size):
+        self.list_code = "x = [" + ", ".join(f"[{i}]" for i in range(int(50_000 * size))) + "]\n"
+        self.dict_code = "d = {" + ", ".join(f"'k{i}': {i}" for i in range(int(10_000 * size))) + "}\n"
+
+    def time_list(self, size):
+        parse(self.list_code)
+
+    def time_dict(self, size):
+        parse(self.dict_code)
+
+
+class ConstantTableSuite:
+    # a generated lookup table, repeating small numbers and short strings
+    def setup(self):
+        rows = (f"    {i}: ({i % 7}, {i % 3}.5, 0x{i:x}, 'k{i % 5}', '\\t', None),\n" for i in range(4000))
+        self.code = "TABLE = {\n" + "".join(rows) + "}\n"
+
+    def time_table(self):
+        parse(self.code)
+
+    def peakmem_table(self):
+        parse(self.code)
+
+
+class ChainedAssignmentSuite:
+    # a minified one-liner, each target being read once before the last turns out to be the value
+    def setup(self):
+        self.code = " = ".join(f"a{i}.b[f(x{i}, (y, z))]" for i in range(500)) + " = v\n"
+
+    def time_chain(self):
+        parse(self.code)
+
+
 class PeakMemSuite:
     def peakmem_parse_small(self):
         parse("![ls -alh]")
@@ -33,3 +71,18 @@ def timeraw_import_parser():
     return """
     from peg_parser.parser import XonshParser
     """
+
+
+def timeraw_import_package():
+    return """
+    import peg_parser
+    """
+
+
+def timeraw_first_parse():
+    # the startup of a shell, parsing a single small rc file in a fresh interpreter
+    rc_file = Path(__file__).parent.parent / "tests" / "data" / "xonshrc.xsh"
+    return f"""
+    import peg_parser
+    peg_parser.parse(open({str(rc_file)!r}).read())
+    """
@@ -0,0 +1,532 @@
+from __future__ import annotations
+
+import ast
+import copy as copy_module
+import functools
+import hashlib
+import io
+import threading
+import warnings as warnings_module
+from collections import OrderedDict
+from typing import TYPE_CHECKING, Any, NamedTuple, cast
+
+if TYPE_CHECKING:
+    from collections.abc import Callable, Iterable
+
+    from .buffer import TokenBuffer
+    from .statements import StatementRange
+    from .subheader import ParseWarning
+    from .tokenize import TabPolicy
+
+# ast.parse mode -> start rule of the grammar
+MODES = {"exec": "file", "eval": "eval", "single": "interactive", "func_type": "func_type"}
+# the classes loaded on first access, as importing the generated parser takes most of the import time
+_LAZY_ATTRS = {
+    "XonshParser": "parser",
+    "Tokenizer": "tokenizer",
+    "ParseWarning": "subheader",
+    "TokenBuffer": "buffer",
+    "format_error": "subheader",
+    "StatementRange": "statements",
+    "TabPolicy": "tokenize",
+}
+
+
+def __getattr__(name: str) -> Any:
+    if name not in _LAZY_ATTRS:
+        raise AttributeError(f"module {__name__!r} has no attribute {name!r}")
+    import importlib
+
+    value = getattr(importlib.import_module(f".{_LAZY_ATTRS[name]}", __name__), name)
+    globals()[name] = value
+    return value
+
+
+class CacheInfo(NamedTuple):
+    hits: int
+    misses: int
+    maxsize: int
+    currsize: int
+
+
+class Parser:
+    """A parse configuration that is checked once and reused for many sources, like the lines of a REPL.
+
+    Each `parse` call gets a parser of its own, so an instance can be shared between threads.
+
+    With *warnings*, `parse` returns the tree along with a list of `ParseWarning` for legal but
+    questionable code: ``is`` comparisons to literals, assertions of tuples and invalid escape sequences,
+    plus ``==`` comparisons to None, True and False when *pedantic*. With *emit_warnings* they are also
+    issued with the `warnings` module, and raise a SyntaxError when turned into errors, as in `compile`.
+
+    Tabs in indentation are read by *tab_policy*, see `peg_parser.tokenize.generate_tokens`. The
+    default, `TabPolicy.STRICT`, raises a TabError where CPython does, while `TabPolicy.EXPAND` accepts
+    the mixed indentation accepted by older xonsh.
+
+    *is_stub* tells that the sources are ``.pyi`` stub files. Stubs are never run, so as for type
+    checkers they may use any syntax this parser knows, whatever the *feature_version*.
+
+    For untrusted input, *max_source_bytes* and *max_tokens* make `parse` fail with a `LimitExceeded`
+    as soon as a source is found over them, see `peg_parser.tokenize.generate_tokens`.
+    """
+
+    def __init__(
+        self,
+        mode: str = "exec",
+        *,
+        filename: str = "<unknown>",
+        type_comments: bool = False,
+        feature_version: int | tuple[int, int] | None = None,
+        optimize: int = -1,
+        xonsh_operators: bool = True,
+        warnings: bool = False,
+        emit_warnings: bool = False,
+        pedantic: bool = False,
+        tab_policy: TabPolicy | None = None,
+        is_stub: bool = False,
+        max_source_bytes: int | None = None,
+        max_tokens: int | None = None,
+    ) -> None:
+        if mode not in MODES:
+            raise ValueError("compile() mode must be 'exec', 'eval', 'single' or 'func_type'")
+        if isinstance(feature_version, tuple):
+            major, minor = feature_version
+            if major != 3:
+                raise ValueError(f"Unsupported major version: {major}")
+            feature_version = minor
+        if optimize > 0 and not hasattr(ast, "PyCF_OPTIMIZED_AST"):
+            raise NotImplementedError("optimized ASTs need Python 3.13+")
+        self.mode = mode
+        self.filename = filename
+        self.type_comments = type_comments
+        self.py_version = None if feature_version is None or is_stub else (3, feature_version)
+        self.optimize = optimize
+        self.xonsh_operators = xonsh_operators
+        self.warnings = warnings
+        self.emit_warnings = emit_warnings
+        self.pedantic = pedantic
+        self.tab_policy = tab_policy
+        self.is_stub = is_stub
+        self.max_source_bytes = max_source_bytes
+        self.max_tokens = max_tokens
+
+    def parse(
+        self,
+        source: str | bytes,
+        filename: str | None = None,
+        *,
+        initial_lineno: int = 1,
+        initial_col_offset: int = 0,
+    ) -> ast.AST | tuple[ast.AST, list[ParseWarning]]:
+        """Parse a source with this configuration.
+
+        For code taken from a larger file, like an f-string field or a docstring, the locations of
+        nodes, warnings and errors start at *initial_lineno*, and the columns of the first line are
+        shifted by *initial_col_offset*.
+
+        The tree and warnings keep no reference to the source, which can be freed once this returns.
+        """
+        import importlib.util
+
+        from .parser import XonshParser
+        from .tokenize import TabPolicy, Trivia, accepts_pep701, generate_tokens
+        from .tokenizer import Tokenizer
+
+        if isinstance(source, bytes):
+            source = importlib.util.decode_source(source)
+        filename = self.filename if filename is None else filename
+        tokens = generate_tokens(
+            source,
+            max_source_bytes=self.max_source_bytes,
+            max_tokens=self.max_tokens,
+            skip_trivia=lambda: tokenizer.skips_trivia(),
+            initial_lineno=initial_lineno,
+            initial_col_offset=initial_col_offset,
+            pep701=accepts_pep701(self.py_version),
+            trivia=Trivia.COALESCED,
+            tab_policy=self.tab_policy or TabPolicy.STRICT,
+            type_comments=self.type_comments,
+        )
+        tokenizer = Tokenizer(tokens)
+        parser = XonshParser(
+            tokenizer, filename=filename, py_version=self.py_version, xonsh_operators=self.xonsh_operators
+        )
+        tree = parser.parse(MODES[self.mode])
+        found: list[ParseWarning] = []
+        if self.warnings or self.emit_warnings:
+            parser.check_warnings(tree, self.pedantic)
+            found = sorted(parser.warnings, key=lambda entry: (entry.lineno, entry.col_offset))
+        if self.emit_warnings:
+            _emit_warnings(found, source, filename, initial_lineno)
+        if self.optimize > 0:
+            tree = compile(tree, filename, self.mode, flags=ast.PyCF_OPTIMIZED_AST, optimize=self.optimize)
+        return (tree, found) if self.warnings else tree
+
+
+def _emit_warnings(found: list[ParseWarning], source: str, filename: str, initial_lineno: int) -> None:
+    for entry in found:
+        try:
+            warnings_module.warn_explicit(entry.message, entry.category, filename, entry.lineno)
+        except entry.category:
+            line = source.splitlines()[entry.lineno - initial_lineno]
+            raise SyntaxError(entry.message, (filename, entry.lineno, entry.col_offset + 1, line)) from None
+
+
+def parse(
+    source: str | bytes,
+    filename: str = "<unknown>",
+    mode: str = "exec",
+    *,
+    type_comments: bool = False,
+    feature_version: int | tuple[int, int] | None = None,
+    optimize: int = -1,
+    xonsh_operators: bool = True,
+    warnings: bool = False,
+    emit_warnings: bool = False,
+    pedantic: bool = False,
+    tab_policy: TabPolicy | None = None,
+    is_stub: bool = False,
+    max_source_bytes: int | None = None,
+    max_tokens: int | None = None,
+    initial_lineno: int = 1,
+    initial_col_offset: int = 0,
+) -> ast.AST | tuple[ast.AST, list[ParseWarning]]:
+    """Replacement for ``ast.parse`` that also accepts xonsh syntax.
+
+    See `Parser` for the *warnings*, *emit_warnings*, *pedantic*, *tab_policy*, *is_stub*,
+    *max_source_bytes* and *max_tokens* options, and `Parser.parse` for *initial_lineno* and
+    *initial_col_offset*.
+
+    Unlike ``ast.parse``, the columns of nodes count characters rather than UTF-8 bytes, so they differ
+    after non-ASCII characters, as for `source_segment`.
+
+    The source may also hold lone surrogates, as from `os.fsdecode`. They are kept in string literals
+    and comments, and raise a SyntaxError elsewhere, as other invalid characters do.
+    """
+    parser = _parser(
+        mode,
+        type_comments,
+        feature_version,
+        optimize,
+        xonsh_operators,
+        warnings,
+        emit_warnings,
+        pedantic,
+        tab_policy,
+        is_stub,
+        max_source_bytes,
+        max_tokens,
+    )
+    return parser.parse(
+        source, filename, initial_lineno=initial_lineno, initial_col_offset=initial_col_offset
+    )
+
+
+@functools.lru_cache(maxsize=16)
+def _parser(
+    mode: str,
+    type_comments: bool,
+    feature_version: int | tuple[int, int] | None,
+    optimize: int,
+    xonsh_operators: bool,
+    warnings: bool,
+    emit_warnings: bool,
+    pedantic: bool,
+    tab_policy: TabPolicy | None,
+    is_stub: bool,
+    max_source_bytes: int | None,
+    max_tokens: int | None,
+) -> Parser:
+    return Parser(
+        mode,
+        type_comments=type_comments,
+        feature_version=feature_version,
+        optimize=optimize,
+        xonsh_operators=xonsh_operators,
+        warnings=warnings,
+        emit_warnings=emit_warnings,
+        pedantic=pedantic,
+        tab_policy=tab_policy,
+        is_stub=is_stub,
+        max_source_bytes=max_source_bytes,
+        max_tokens=max_tokens,
+    )
+
+
+# node kinds reported by `parse_events` unless told otherwise
+EVENT_KINDS = (
+    ast.FunctionDef,
+    ast.AsyncFunctionDef,
+    ast.ClassDef,
+    ast.Call,
+    ast.ExceptHandler,
+    ast.Assign,
+    ast.Import,
+    ast.ImportFrom,
+)
+
+
+def parse_events(
+    source: str,
+    callback: Callable[[str, dict[str, int], ast.AST], object],
+    kinds: Iterable[type[ast.AST]] = EVENT_KINDS,
+    filename: str = "<unknown>",
+    *,
+    max_source_bytes: int | None = None,
+    max_tokens: int | None = None,
+) -> None:
+    """Parse a module, calling ``callback(kind, location, node)`` for the nodes of the given kinds.
+
+    Events come in source order as each top level statement is parsed, so statements before a syntax
+    error are still reported. An exception raised by the callback stops parsing and is propagated.
+    The limits are those of `Parser`, and events before a limit is reached are reported too.
+    """
+    from .parser import XonshParser
+    from .tokenize import Trivia, accepts_pep701, generate_tokens
+    from .tokenizer import Tokenizer
+
+    tokens = generate_tokens(
+        source,
+        max_source_bytes=max_source_bytes,
+        max_tokens=max_tokens,
+        pep701=accepts_pep701(),
+        trivia=Trivia.COALESCED,
+    )
+    parser = XonshParser(Tokenizer(tokens), filename=filename)
+    parser.event_callback = callback
+    parser.event_kinds = tuple(kinds)
+    parser.parse("file")
+
+
+def paren_map(source: str, filename: str = "<unknown>") -> list[dict[str, int]]:
+    """The spans of the parentheses only grouping an expression, a target or a pattern, in source order.
+
+    These are the parentheses a formatter may keep or drop without changing the tree, like those of
+    ``return (x)``, ``with (cm):``, ``@(decorator)``, ``if (y := f(x))`` in a comprehension or
+    ``(a) = 1``. Each span is a location covering both parentheses, as found while parsing, and nested
+    groups have a span each. Those of tuples, generator expressions, calls and ``with (a as b):`` are
+    not grouping ones.
+    """
+    from .parser import XonshParser
+    from .tokenize import Trivia, accepts_pep701, generate_tokens
+    from .tokenizer import Tokenizer
+
+    tokens = generate_tokens(io.StringIO(source).readline, pep701=accepts_pep701(), trivia=Trivia.COALESCED)
+    parser = XonshParser(Tokenizer(tokens), filename=filename)
+    parser.groups = set()
+    parser.parse("file")
+    keys = ("lineno", "col_offset", "end_lineno", "end_col_offset")
+    return [dict(zip(keys, span)) for span in sorted(parser.groups)]
+
+
+def parse_single_with_echo(
+    source: str, filename: str = "<unknown>"
+) -> tuple[ast.Interactive, bool, dict[str, int] | None]:
+    """Parse the statements of a REPL input, telling whether the value of the last one is to be printed.
+
+    Unlike ``parse(source, mode="single")``, a paste of several statements is accepted. The result is
+    ``(tree, is_expression_last, last_expr_span)``, the span being the location of the expression
+    statement ending the input, or None.
+    """
+    module = cast(ast.Module, parse(source, filename))
+    tree = ast.Interactive(body=module.body)
+    if not module.body or not isinstance(last := module.body[-1], ast.Expr):
+        return tree, False, None
+    return tree, True, {attr: getattr(last, attr) for attr in last._attributes}
+
+
+def split_statements(source: str) -> list[StatementRange]:
+    """The ranges of the top level statements of a pasted source, found from its tokens without parsing it.
+
+    The last range is not `StatementRange.complete` when the source ends within a statement.
+    """
+    from .statements import split_statements
+
+    return split_statements(source)
+
+
+def block_complete(source: str) -> bool | None:
+    """Whether an interactive input closes all its blocks, checked from its tokens on every keystroke.
+
+    See `peg_parser.statements.block_complete` for when it is None or False.
+    """
+    from .statements import block_complete
+
+    return block_complete(source)
+
+
+def scan_module_interface(source: str) -> dict[str, Any]:
+    """The imports, literal ``__all__`` and top level definitions of a module, without parsing its blocks.
+
+    See `peg_parser.interface.scan_module_interface` for the keys of the result.
+    """
+    from .interface import scan_module_interface
+
+    return scan_module_interface(source)
+
+
+def scan_functions(source: str) -> list[dict[str, Any]]:
+    """The parameters, decorators and enclosing definitions of the functions of a module, for completers.
+
+    See `peg_parser.interface.scan_functions` for the keys of the records.
+    """
+    from .interface import scan_functions
+
+    return scan_functions(source)
+
+
+def context_at(source: str, offset: int) -> dict[str, Any]:
+    """The enclosing calls, attribute access, statement and string at a cursor *offset* of a source.
+
+    See `peg_parser.completion.context_at` for the keys of the result.
+    """
+    from .completion import context_at
+
+    return context_at(source, offset)
+
+
+def detect_features(source: str, filename: str = "<unknown>") -> dict[str, list[dict[str, int]]]:
+    """The syntax features of a module that need a recent Python, or xonsh, with the locations of their uses.
+
+    See `peg_parser.features` for the names of the features and the Python versions they need.
+    """
+    from .features import detect_features
+
+    return detect_features(source, filename)
+
+
+def dump_tokens(source: str, *, exact: bool = False) -> str:
+    """The tokens of a source in the text format of ``python -m tokenize``, to be compared with CPython's.
+
+    The same is printed for a file by ``python -m peg_parser.tokenize <file>``. With *exact*, operators
+    are named by their exact type, like ``LPAR``.
+    """
+    from .tokenize import dump_tokens
+
+    return dump_tokens(source, exact=exact)
+
+
+def tokenize_for_parse(source: str) -> TokenBuffer:
+    """The tokens of a source, to be parsed by `parse_tokens_buffer`.
+
+    Parsing an edited source again, like a line wrapped in ``![...]`` when it failed to parse as
+    Python, can use ``buffer.replace_range(start, end, text)``. It tokenizes only the statements
+    touched by the edit and shares the other tokens with *buffer*.
+    """
+    from .buffer import tokenize_for_parse
+
+    return tokenize_for_parse(source)
+
+
+def parse_tokens_buffer(buffer: TokenBuffer, mode: str = "exec", filename: str = "<unknown>") -> ast.AST:
+    """Parse the tokens of a `TokenBuffer`, as `parse` does with the source they were read from."""
+    from .buffer import parse_tokens_buffer
+
+    return parse_tokens_buffer(buffer, mode, filename)
+
+
+def grammar_rules() -> list[dict[str, Any]]:
+    """The rules of the grammar, with their definitions and the tokens they can start with.
+
+    Keywords are quoted as in the grammar, ``'if'``, soft keywords as ``"match"``, and other tokens are
+    named by their type. The rules only used to report syntax errors are left out.
+    """
+    from .parser import XonshParser
+
+    return [
+        {"name": name, "grammar": grammar, "first": list(first)}
+        for name, (grammar, first) in XonshParser.RULES.items()
+    ]
+
+
+_cache: OrderedDict[tuple[bytes, str, int | None, int | None], ast.AST] = OrderedDict()
+_cache_lock = threading.Lock()
+_cache_stats = {"hits": 0, "misses": 0, "maxsize": 128}
+
+
+def parse_cached(
+    source: str,
+    filename: str = "<unknown>",
+    mode: str = "exec",
+    *,
+    copy: bool = True,
+    max_source_bytes: int | None = None,
+    max_tokens: int | None = None,
+) -> ast.AST:
+    """`parse` with an in-process LRU cache keyed by the hash of the source and the limits.
+
+    Each hit returns a fresh copy of the cached tree. With ``copy=False`` the cached tree itself is
+    returned, and callers must not mutate it. Sources failing to parse are not cached, so a source over
+    the limits always fails.
+    """
+    key = (hashlib.sha1(source.encode("utf-8", "surrogatepass")).digest(), mode, max_source_bytes, max_tokens)
+    with _cache_lock:
+        tree = _cache.get(key)
+        if tree is not None:
+            _cache.move_to_end(key)
+            _cache_stats["hits"] += 1
+        else:
+            _cache_stats["misses"] += 1
+    if tree is None:
+        tree = cast(
+            "ast.AST",
+            parse(source, filename, mode, max_source_bytes=max_source_bytes, max_tokens=max_tokens),
+        )
+        with _cache_lock:
+            if _cache_stats["maxsize"] > 0:
+                _cache[key] = tree
+                while len(_cache) > _cache_stats["maxsize"]:
+                    _cache.popitem(last=False)
+    return copy_module.deepcopy(tree) if copy else tree
+
+
+def set_cache_size(maxsize: int) -> None:
+    """Set how many trees `parse_cached` keeps, evicting the least recently used ones. Zero disables it."""
+    if maxsize < 0:
+        raise ValueError("cache size must not be negative")
+    with _cache_lock:
+        _cache_stats["maxsize"] = maxsize
+        while len(_cache) > maxsize:
+            _cache.popitem(last=False)
+
+
+def cache_info() -> CacheInfo:
+    """Hit and miss statistics of `parse_cached`, like `functools.lru_cache`."""
+    with _cache_lock:
+        return CacheInfo(_cache_stats["hits"], _cache_stats["misses"], _cache_stats["maxsize"], len(_cache))
+
+
+def cache_clear() -> None:
+    """Empty the `parse_cached` cache and reset its statistics."""
+    with _cache_lock:
+        _cache.clear()
+        _cache_stats["hits"] = _cache_stats["misses"] = 0
+
+
+def _line_offsets(source: str) -> list[int]:
+    # lines are split as the tokenizer reads them, on "\n" only
+    offsets = [0]
+    for line in io.StringIO(source):
+        offsets.append(offsets[-1] + len(line))
+    return offsets
+
+
+def source_segment(source: str, node: ast.AST, *, padded: bool = False) -> str | None:
+    """Get the source text of a node returned by `parse`, or None if it has no location.
+
+    Unlike `ast.get_source_segment`, columns count characters rather than UTF-8 bytes, as in the nodes
+    of this parser. With *padded*, the first line of a multi-line node is indented to its column.
+    """
+    try:
+        lineno, end_lineno = node.lineno, node.end_lineno  # type: ignore[attr-defined]
+        col, end_col = node.col_offset, node.end_col_offset  # type: ignore[attr-defined]
+    except AttributeError:
+        return None
+    if end_lineno is None or end_col is None:
+        return None
+    offsets = _line_offsets(source)
+    start = offsets[lineno - 1] + col
+    text = source[start : offsets[end_lineno - 1] + end_col]
+    if padded and end_lineno > lineno:
+        text = "".join(c if c in "\f\t" else " " for c in source[offsets[lineno - 1] : start]) + text
+    return text
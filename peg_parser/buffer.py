@@ -0,0 +1,156 @@
+"""Token buffers, to parse a source again after small edits without tokenizing all of it.
+
+Like xonsh's execer, which wraps a line failing to parse as Python in ``![...]`` and parses again, the
+buffer of the edited source is made by `TokenBuffer.replace_range`. It tokenizes from the start of the
+statement holding the edit until the tokens line up with those of the previous source again, and
+shares the other tokens.
+"""
+
+from __future__ import annotations
+
+import ast
+import io
+from typing import TYPE_CHECKING
+
+from .tokenize import Token, TokenInfo, _tokenize, accepts_pep701, tabsize
+
+if TYPE_CHECKING:
+    from collections.abc import Iterator
+
+
+class TokenBuffer:
+    """The tokens of a source, made by `tokenize_for_parse` and parsed by `parse_tokens_buffer`.
+
+    ``tokenized_lines`` counts the lines read by the tokenizer to make this buffer. Unlike a parsed
+    tree, a buffer keeps its ``source``, which `replace_range` edits.
+    """
+
+    def __init__(
+        self, source: str, tokens: list[TokenInfo], error: Exception | None, tokenized_lines: int
+    ) -> None:
+        self.source = source
+        self.tokens = tokens
+        # raised after the tokens, as the tokenizer did when reading the source
+        self.error = error
+        self.tokenized_lines = tokenized_lines
+        self._starts: dict[int, tuple[int, tuple[int, ...], tuple[int, ...]]] | None = None
+
+    def __iter__(self) -> Iterator[TokenInfo]:
+        yield from self.tokens
+        if self.error is not None:
+            raise self.error
+
+    def statement_starts(self) -> dict[int, tuple[int, tuple[int, ...], tuple[int, ...]]]:
+        """Map the lines starting a statement to the index of their first token and the indentation levels.
+
+        The levels are measured as by the tokenizer, then counting each tab as one column to check the
+        consistency of tabs. The tokenizer can start again on these lines, with no bracket or string
+        left open.
+        """
+        if self._starts is None:
+            indents, alt_indents = [0], [0]
+            self._starts = {1: (0, (0,), (0,))}
+            for index, tok in enumerate(self.tokens):
+                if tok.type == Token.INDENT:
+                    indents.append(indent_column(tok.string))
+                    alt_indents.append(indent_column(tok.string, 1))
+                elif tok.type == Token.DEDENT:
+                    indents.pop()
+                    alt_indents.pop()
+                elif tok.type == Token.NEWLINE and tok.string:
+                    self._starts[tok.start[0] + 1] = (index + 1, tuple(indents), tuple(alt_indents))
+        return self._starts
+
+    def replace_range(self, start: int, end: int, text: str) -> TokenBuffer:
+        """The buffer of the source with the characters from offset *start* to *end* replaced by *text*."""
+        source = self.source[:start] + text + self.source[end:]
+        starts = self.statement_starts()
+        first_line = max(line for line in starts if line <= self.source.count("\n", 0, start) + 1)
+        index, indents, alt_indents = starts[first_line]
+        old_end_line = self.source.count("\n", 0, end) + 1
+        new_end_line = source.count("\n", 0, start + len(text)) + 1
+        shift = new_end_line - old_end_line
+
+        offset = line_offset(self.source, first_line)
+        readline = io.StringIO(source[offset:]).readline
+        lines_read = 0
+
+        def counting_readline() -> str:
+            nonlocal lines_read
+            lines_read += 1
+            return readline()
+
+        tokens = self.tokens[:index]
+        levels = list(indents)
+        try:
+            for tok in _tokenize(
+                counting_readline,
+                lineno=first_line,
+                indents=indents,
+                alt_indents=alt_indents,
+                pep701=accepts_pep701(),
+            ):
+                tokens.append(tok)
+                if tok.type == Token.INDENT:
+                    levels.append(indent_column(tok.string))
+                elif tok.type == Token.DEDENT:
+                    levels.pop()
+                elif tok.type == Token.NEWLINE and tok.string and tok.start[0] >= new_end_line:
+                    # the rest is tokenized as before once a statement starts in the same state
+                    old_line = tok.start[0] + 1 - shift
+                    if self.error is None and starts.get(old_line, (0, (), ()))[1] == tuple(levels):
+                        rest = self.tokens[starts[old_line][0] :]
+                        tokens += [shift_token(tok, shift) for tok in rest] if shift else rest
+                        break
+        except Exception as e:
+            return TokenBuffer(source, tokens, e, lines_read)
+        return TokenBuffer(source, tokens, None, lines_read)
+
+
+def indent_column(whitespace: str, tab_size: int = tabsize) -> int:
+    """The indentation level of a line, measured as by the tokenizer."""
+    column = 0
+    for char in whitespace:
+        if char == "\t":
+            column = (column // tab_size + 1) * tab_size
+        else:
+            column = 0 if char == "\f" else column + 1
+    return column
+
+
+def line_offset(source: str, lineno: int) -> int:
+    """The offset of the start of a line, split on ``\\n`` as the tokenizer reads them."""
+    offset = 0
+    for _ in range(lineno - 1):
+        offset = source.index("\n", offset) + 1
+    return offset
+
+
+def shift_token(tok: TokenInfo, lines: int) -> TokenInfo:
+    return tok._replace(start=(tok.start[0] + lines, tok.start[1]), end=(tok.end[0] + lines, tok.end[1]))
+
+
+def tokenize_for_parse(source: str) -> TokenBuffer:
+    tokens: list[TokenInfo] = []
+    readline = io.StringIO(source).readline
+    lines_read = 0
+
+    def counting_readline() -> str:
+        nonlocal lines_read
+        lines_read += 1
+        return readline()
+
+    try:
+        tokens.extend(_tokenize(counting_readline, pep701=accepts_pep701()))
+    except Exception as e:
+        return TokenBuffer(source, tokens, e, lines_read)
+    return TokenBuffer(source, tokens, None, lines_read)
+
+
+def parse_tokens_buffer(buffer: TokenBuffer, mode: str = "exec", filename: str = "<unknown>") -> ast.AST:
+    from . import MODES
+    from .parser import XonshParser
+    from .tokenizer import Tokenizer
+
+    parser = XonshParser(Tokenizer(iter(buffer)), filename=filename)
+    return parser.parse(MODES[mode])
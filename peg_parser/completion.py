@@ -0,0 +1,227 @@
+"""The syntactic context at a cursor, for completers working on incomplete sources."""
+
+from __future__ import annotations
+
+import dataclasses
+import keyword
+import re
+from typing import Any
+
+from .tokenize import Token, TokenError, TokenInfo, generate_tokens
+
+SKIPPED = (Token.WS, Token.COMMENT, Token.NL, Token.INDENT, Token.DEDENT, Token.ENDMARKER)
+CLOSERS = {")", "]", "}"}
+STRING_START = re.compile(r"[a-zA-Z]{0,2}['\"]")
+ATOM_KEYWORDS = {"None", "True", "False"}
+STATEMENT_KEYWORDS = {
+    "assert",
+    "break",
+    "class",
+    "continue",
+    "def",
+    "del",
+    "elif",
+    "else",
+    "except",
+    "finally",
+    "for",
+    "from",
+    "global",
+    "if",
+    "import",
+    "nonlocal",
+    "pass",
+    "raise",
+    "return",
+    "try",
+    "while",
+    "with",
+}
+COMPOUND_KEYWORDS = {"class", "def", "elif", "else", "except", "finally", "for", "if", "try", "while", "with"}
+
+
+@dataclasses.dataclass
+class Frame:
+    """An open bracket, f-string or f-string replacement field."""
+
+    opener: int  # index of the opening token
+    kind: str  # "bracket", "fstring" or "field"
+    func: str | None = None  # source text of the callee when the bracket is a call
+    arg_index: int = 0
+    in_spec: bool = False
+
+
+def ends_primary(tok: TokenInfo) -> bool:
+    if tok.type == Token.NAME:
+        return tok.string in ATOM_KEYWORDS or not keyword.iskeyword(tok.string)
+    return tok.type in (Token.NUMBER, Token.STRING, Token.FSTRING_END) or tok.string in CLOSERS
+
+
+class ContextScanner:
+    def __init__(self, source: str) -> None:
+        self.source = source
+        # lines are split as the tokenizer reads them, on "\n" only
+        self.line_starts = [0] + [index + 1 for index, char in enumerate(source) if char == "\n"]
+        self.tokens: list[TokenInfo] = []
+        self.openers: dict[int, int] = {}  # closing token index -> opening token index
+        self.frames: list[Frame] = []
+        self.statement: str | None = None
+        self.statement_start = True
+        self.lambdas = 0
+        self.string_at: tuple[int, int] | None = None  # start of an unterminated string
+
+    def offset(self, pos: tuple[int, int]) -> int:
+        return self.line_starts[pos[0] - 1] + pos[1]
+
+    def text(self, start: int, end: int) -> str:
+        return self.source[self.offset(self.tokens[start].start) : self.offset(self.tokens[end].end)]
+
+    def scan(self) -> None:
+        tokens = []
+        try:
+            for tok in generate_tokens(self.source):
+                # the NEWLINE added at the end of the source is not a statement end at the cursor
+                if tok.type not in SKIPPED and not (tok.type == Token.NEWLINE and not tok.string):
+                    tokens.append(tok)
+        except TokenError as e:
+            if "string" in e.args[0]:
+                self.string_at = e.args[1]
+        except SyntaxError:
+            pass
+        if tokens and tokens[-1].type == Token.ERRORTOKEN and STRING_START.match(tokens[-1].string):
+            self.string_at = tokens[-1].start  # a single quoted string is cut at the end of its line
+        # the tokens read before an unterminated string are still valid
+        tail = []
+        while self.string_at and tokens and tokens[-1].start >= self.string_at:
+            tail.insert(0, tokens.pop())
+        self.feed_all(tokens)
+        top = self.frames[-1] if self.frames else None
+        if self.string_at and (
+            (top and (top.kind == "fstring" or top.in_spec))
+            or not STRING_START.match(self.source, self.offset(self.string_at))
+        ):
+            # the unterminated string is the enclosing f-string, only its literal part is garbled
+            self.string_at = None
+            self.feed_all([tok for tok in tail if tok.type != Token.ERRORTOKEN])
+
+    def feed_all(self, tokens: list[TokenInfo]) -> None:
+        for tok in tokens:
+            self.tokens.append(tok)
+            self.feed(len(self.tokens) - 1)
+
+    def feed(self, index: int) -> None:
+        tok = self.tokens[index]
+        top = self.frames[-1] if self.frames else None
+        if tok.type == Token.NEWLINE:
+            self.statement, self.statement_start, self.lambdas = None, True, 0
+            return
+        if self.statement_start:
+            self.statement_start = tok.string == "async"
+            if tok.string in STATEMENT_KEYWORDS:
+                self.statement = tok.string
+        if tok.type == Token.FSTRING_START:
+            self.frames.append(Frame(index, "fstring"))
+        elif tok.type == Token.FSTRING_END:
+            self.pop(index)
+        elif tok.type != Token.OP:
+            if tok.type == Token.NAME and tok.string == "lambda" and not self.frames:
+                self.lambdas += 1
+        elif top and top.kind == "fstring" and tok.string == "{":
+            self.frames.append(Frame(index, "field"))
+        elif tok.string[-1] in "([{":
+            func = None
+            if tok.string == "(" and index and ends_primary(self.tokens[index - 1]):
+                start = self.primary_start(index - 1)
+                if start is not None and not (start and self.tokens[start - 1].string == "def"):
+                    func = self.text(start, index - 1)
+            self.frames.append(Frame(index, "bracket", func))
+        elif tok.string in CLOSERS:
+            self.pop(index)
+        elif tok.string == "," and top:
+            top.arg_index += 1
+        elif tok.string == ":" and top and top.kind == "field":
+            top.in_spec = True
+        elif tok.string in (";", ":") and not self.frames:
+            if tok.string == ":" and self.lambdas:
+                self.lambdas -= 1
+            elif tok.string == ";" or self.statement in COMPOUND_KEYWORDS:
+                self.statement, self.statement_start = None, True
+
+    def pop(self, index: int) -> None:
+        if self.frames:
+            self.openers[index] = self.frames.pop().opener
+
+    def primary_start(self, index: int) -> int | None:
+        """Index of the first token of the primary expression ending at *index*, like ``a.b(c)[d]``."""
+        while True:
+            tok = self.tokens[index]
+            if index in self.openers:
+                index = self.openers[index]
+                if self.tokens[index].string in ("(", "[") and index and ends_primary(self.tokens[index - 1]):
+                    index -= 1
+                    continue
+                return index
+            if not ends_primary(tok):
+                return None
+            if tok.type == Token.NAME and index > 1 and self.tokens[index - 1].string == ".":
+                if not ends_primary(self.tokens[index - 2]):
+                    return None
+                index -= 2
+                continue
+            if index and self.tokens[index - 1].string == "$":
+                return index - 1  # an environment variable
+            return index
+
+    def attribute(self) -> dict[str, Any] | None:
+        if self.string_at or not self.tokens:
+            return None
+        index = len(self.tokens) - 1
+        if self.tokens[index].type == Token.NAME and self.tokens[index].end == self.end_pos():
+            index -= 1  # a partially typed attribute name
+        if index < 1 or self.tokens[index].string != "." or not ends_primary(self.tokens[index - 1]):
+            return None
+        start = self.primary_start(index - 1)
+        if start is None:
+            return None
+        begin, end = self.offset(self.tokens[start].start), self.offset(self.tokens[index - 1].end)
+        return {"base": self.source[begin:end], "start": begin, "end": end}
+
+    def end_pos(self) -> tuple[int, int]:
+        return len(self.line_starts), len(self.source) - self.line_starts[-1]
+
+    def string(self) -> dict[str, Any] | None:
+        if self.string_at:
+            return {"kind": "string", "start": self.offset(self.string_at), "in_field": False}
+        for frame in reversed(self.frames):
+            if frame.kind == "fstring":
+                start = self.offset(self.tokens[frame.opener].start)
+                return {"kind": "fstring", "start": start, "in_field": frame is not self.frames[-1]}
+        return None
+
+
+def context_at(source: str, offset: int) -> dict[str, Any]:
+    """Describe the syntactic context at *offset*, the cursor, of a possibly incomplete source.
+
+    Only the text before the cursor is read. The result has the keys
+
+    - ``calls``: the enclosing calls, outermost first, as dicts with the ``func`` source text
+      and the ``arg_index`` of the argument at the cursor
+    - ``attribute``: when the cursor is after ``base.``, a dict with the ``base`` source text and its
+      ``start`` and ``end`` offsets, else None
+    - ``statement``: the keyword starting the enclosing simple statement or clause, like ``import``
+    - ``string``: when the cursor is in a string, a dict with its ``kind`` ("string" or "fstring"),
+      ``start`` offset and whether it is ``in_field``, a replacement field of an f-string
+    """
+    scanner = ContextScanner(source[:offset])
+    scanner.scan()
+    calls = [
+        {"func": frame.func, "arg_index": frame.arg_index}
+        for frame in scanner.frames
+        if frame.func is not None
+    ]
+    return {
+        "calls": calls,
+        "attribute": scanner.attribute(),
+        "statement": scanner.statement,
+        "string": scanner.string(),
+    }
@@ -0,0 +1,103 @@
+"""The versioned and xonsh specific syntax used by a source, for checks of the minimal Python version."""
+
+from __future__ import annotations
+
+import ast
+import io
+import string
+from typing import TYPE_CHECKING
+
+from .tokenize import Token, TokenInfo, Trivia, closes, generate_tokens
+
+if TYPE_CHECKING:
+    from collections.abc import Iterator
+
+# Python syntax features -> the first Python version accepting them
+FEATURE_VERSIONS = {
+    "walrus": (3, 8),
+    "positional_only_parameters": (3, 8),
+    "parenthesized_context_managers": (3, 10),
+    "match_statement": (3, 10),
+    "except_star": (3, 11),
+    "type_statement": (3, 12),
+    "type_parameters": (3, 12),
+    "fstring_nesting": (3, 12),
+}
+# the xonsh features, reported by the parser as they are translated to plain Python
+XONSH_FEATURES = ("subprocess", "search_path", "macro")
+
+
+def location(node: ast.AST | TokenInfo) -> dict[str, int]:
+    if isinstance(node, TokenInfo):
+        return {**node.loc_start(), **node.loc_end()}
+    return {attr: getattr(node, attr) for attr in ("lineno", "col_offset", "end_lineno", "end_col_offset")}
+
+
+def tree_features(tree: ast.AST) -> Iterator[tuple[str, ast.AST]]:
+    """The Python features visible in the nodes of a tree."""
+    for node in ast.walk(tree):
+        if isinstance(node, ast.NamedExpr):
+            yield "walrus", node
+        elif isinstance(node, ast.arguments) and node.posonlyargs:
+            yield "positional_only_parameters", node.posonlyargs[0]
+        elif isinstance(node, ast.Match):
+            yield "match_statement", node
+        elif type(node).__name__ == "TryStar":
+            yield "except_star", node
+        elif type(node).__name__ == "TypeAlias":
+            yield "type_statement", node
+        if getattr(node, "type_params", None):
+            yield "type_parameters", node.type_params[0]  # type: ignore[attr-defined]
+
+
+def fstring_features(source: str) -> Iterator[TokenInfo]:
+    """The tokens of f-string replacement fields that need Python 3.12 (PEP 701).
+
+    These are strings reusing the quotes of an enclosing f-string, backslashes in the expression part,
+    comments, and line breaks within single quoted f-strings.
+    """
+    fstrings: list[list] = []  # the quote of each f-string being read and the braces opened within it
+    for tok in generate_tokens(io.StringIO(source).readline):
+        in_field = bool(fstrings) and fstrings[-1][1] > 0
+        if tok.type in (Token.FSTRING_START, Token.STRING):
+            quote = tok.string[len(tok.string) - len(tok.string.lstrip(string.ascii_letters)) :][:3]
+            quote = quote if quote in ('"""', "'''") else quote[0]
+            if in_field and (
+                any(closes(outer, quote) for outer, _ in fstrings)
+                or (tok.type == Token.STRING and "\\" in tok.string)
+            ):
+                yield tok
+            if tok.type == Token.FSTRING_START:
+                fstrings.append([quote, 0])
+        elif tok.type == Token.FSTRING_END:
+            fstrings.pop()
+        elif in_field and (tok.type == Token.COMMENT or (tok.type == Token.NL and len(fstrings[-1][0]) == 1)):
+            yield tok
+        elif fstrings and tok.type == Token.OP and tok.string[-1] in "{}":
+            fstrings[-1][1] += 1 if tok.string[-1] == "{" else -1
+
+
+def detect_features(source: str, filename: str = "<unknown>") -> dict[str, list[dict[str, int]]]:
+    """Find the syntax features of a module that need a recent Python or xonsh, with their locations.
+
+    The keys of the result are the names of `FEATURE_VERSIONS` and `XONSH_FEATURES` appearing in the
+    source, in that order, mapped to the locations of each use in source order.
+    """
+    from .parser import XonshParser
+    from .tokenizer import Tokenizer
+
+    tokens = generate_tokens(io.StringIO(source).readline, trivia=Trivia.COALESCED)
+    parser = XonshParser(Tokenizer(tokens), filename=filename)
+    parser.features = {}
+    tree = parser.parse("file")
+    found: dict[str, list[dict[str, int]]] = {}
+    for name, node in tree_features(tree):
+        found.setdefault(name, []).append(location(node))
+    for tok in fstring_features(source):
+        found.setdefault("fstring_nesting", []).append(location(tok))
+    found.update(parser.features)
+    return {
+        name: sorted(found[name], key=lambda loc: (loc["lineno"], loc["col_offset"]))
+        for name in (*FEATURE_VERSIONS, *XONSH_FEATURES)
+        if name in found
+    }
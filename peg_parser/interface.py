@@ -0,0 +1,250 @@
+"""The interface of a module, for xonsh's lazy imports and completers.
+
+`scan_module_interface` reads it without parsing all of the module. Only the top level statements and
+those of the blocks of top level ``if`` and ``try`` statements are read, and only the imports and the
+statements binding ``__all__`` are parsed. The bodies of functions, classes and other blocks are
+skipped on their INDENT token, up to the matching DEDENT, only looking for ``__all__`` in the blocks
+run on import.
+"""
+
+from __future__ import annotations
+
+import ast
+from typing import TYPE_CHECKING, Any
+
+from . import Parser, _line_offsets, parse_events
+from .statements import CONTINUATIONS
+from .tokenize import Token, generate_tokens
+
+if TYPE_CHECKING:
+    from collections.abc import Iterator
+
+    from .tokenize import TokenInfo
+
+# the compound statements whose blocks are read, their imports and definitions being conditional
+CONDITIONAL_STATEMENTS = {"if", "try"}
+# the names of the first parameter of methods
+METHOD_RECEIVERS = ("self", "cls")
+
+
+def scan_module_interface(source: str) -> dict[str, Any]:
+    """The imports, ``__all__`` and definitions of a module.
+
+    The keys of the result are:
+
+    - ``imports``: dicts of the ``module``, imported ``name`` (None for ``import module``), ``alias``,
+      ``lineno`` and whether the import is ``conditional``, as within ``if TYPE_CHECKING:`` or ``try:``.
+      Relative modules start with their dots.
+    - ``__all__``: the list of names when ``__all__`` is only assigned, or extended with ``+=``, with
+      literal lists or tuples of strings at the top level, else None.
+    - ``definitions``: dicts of the ``name``, ``kind`` (``"function"`` or ``"class"``), ``lineno``
+      and whether the function or class is ``conditional``.
+    """
+    found: dict[str, Any] = {"imports": [], "__all__": None, "definitions": []}
+    offsets = _line_offsets(source)
+    tokens = generate_tokens(source, skip_trivia=True)
+    line: list[TokenInfo] = []
+    level = 0
+    compound = ""  # the first keyword of the last top level statement
+    header = ""  # the first keyword of the last statement, after ``async``
+    for tok in tokens:
+        if tok.type == Token.INDENT:
+            if level == 0 and compound in CONDITIONAL_STATEMENTS:
+                level += 1
+            elif skip_block(tokens) and header not in ("def", "class"):
+                found["__all__"] = None  # may be changed in a loop or a nested block
+        elif tok.type == Token.DEDENT:
+            level -= 1
+        elif tok.type == Token.NEWLINE and line:
+            header = line[1].string if line[0].string == "async" and len(line) > 1 else line[0].string
+            if level == 0 and line[0].string not in CONTINUATIONS:
+                compound = line[0].string
+            if level == 0 and compound in CONDITIONAL_STATEMENTS:
+                if body := clause_body(line):
+                    scan_statement(source, offsets, body, True, found)
+            else:
+                scan_statement(source, offsets, line, level > 0, found)
+            line = []
+        elif tok.type == Token.ENDMARKER:
+            break
+        else:
+            line.append(tok)
+    return found
+
+
+def skip_block(tokens: Iterator[TokenInfo]) -> bool:
+    """Read the tokens of a block up to the DEDENT closing it, telling whether ``__all__`` is named."""
+    depth = 1
+    names_all = False
+    for tok in tokens:
+        if tok.type == Token.INDENT:
+            depth += 1
+        elif tok.type == Token.DEDENT:
+            depth -= 1
+            if depth == 0:
+                break
+        elif tok.string == "__all__":
+            names_all = True
+    return names_all
+
+
+def clause_body(line: list[TokenInfo]) -> list[TokenInfo]:
+    """The tokens of the statements following the colon of a clause header on the same line."""
+    depth = 0
+    for index, tok in enumerate(line):
+        if tok.type == Token.OP and tok.string in "([{":
+            depth += 1
+        elif tok.type == Token.OP and tok.string in ")]}":
+            depth -= 1
+        elif depth == 0 and tok.string == ":":
+            return line[index + 1 :]
+    return []
+
+
+def scan_statement(
+    source: str, offsets: list[int], line: list[TokenInfo], conditional: bool, found: dict[str, Any]
+) -> None:
+    """Add the imports, definitions and values of ``__all__`` of a logical line to *found*.
+
+    *offsets* are those of the lines of the source, see `peg_parser.source_segment`.
+    """
+    words = [tok.string for tok in line[:3]]
+    if words[0] == "async":
+        words = words[1:]
+        line = line[1:]
+    if words[0] in ("def", "class") and len(line) > 1:
+        kind = "function" if words[0] == "def" else "class"
+        found["definitions"].append(
+            {"name": line[1].string, "kind": kind, "lineno": line[0].start[0], "conditional": conditional}
+        )
+    elif words[0] in ("import", "from") or any(tok.string == "__all__" for tok in line):
+        (lineno, col), (end_lineno, end_col) = line[0].start, line[-1].end
+        segment = source[offsets[lineno - 1] + col : offsets[end_lineno - 1] + end_col]
+        tree = Parser().parse(segment, initial_lineno=lineno, initial_col_offset=col)
+        for stmt in tree.body:  # type: ignore[union-attr]
+            scan_parsed_statement(stmt, conditional, found)
+
+
+def scan_parsed_statement(stmt: ast.stmt, conditional: bool, found: dict[str, Any]) -> None:
+    if isinstance(stmt, ast.Import | ast.ImportFrom):
+        for alias in stmt.names:
+            if isinstance(stmt, ast.Import):
+                module, name = alias.name, None
+            else:
+                module, name = "." * stmt.level + (stmt.module or ""), alias.name
+            found["imports"].append(
+                {
+                    "module": module,
+                    "name": name,
+                    "alias": alias.asname,
+                    "lineno": stmt.lineno,
+                    "conditional": conditional,
+                }
+            )
+        return
+    names = None if conditional else literal_names(stmt, found["__all__"])
+    if names is not None:
+        found["__all__"] = names
+    elif binds_all(stmt):
+        found["__all__"] = None
+
+
+def literal_names(stmt: ast.stmt, names: list[str] | None) -> list[str] | None:
+    """The value of ``__all__`` after a statement assigning or extending it with strings, else None."""
+    if isinstance(stmt, ast.Assign) and len(stmt.targets) == 1 and is_all(stmt.targets[0]):
+        value, names = stmt.value, []
+    elif isinstance(stmt, ast.AugAssign) and isinstance(stmt.op, ast.Add) and is_all(stmt.target):
+        value = stmt.value
+    else:
+        return None
+    if names is None or not isinstance(value, ast.List | ast.Tuple):
+        return None
+    if not all(isinstance(elt, ast.Constant) and isinstance(elt.value, str) for elt in value.elts):
+        return None
+    return [*names, *(elt.value for elt in value.elts)]  # type: ignore[attr-defined]
+
+
+def binds_all(stmt: ast.stmt) -> bool:
+    """Whether a statement may change ``__all__``, by binding it, its items or calling one of its methods."""
+    for node in ast.walk(stmt):
+        if isinstance(node, ast.Name | ast.Subscript) and not isinstance(node.ctx, ast.Load):
+            if is_all(node if isinstance(node, ast.Name) else node.value):
+                return True
+        if isinstance(node, ast.Call) and isinstance(node.func, ast.Attribute) and is_all(node.func.value):
+            return True
+    return False
+
+
+def is_all(node: ast.AST) -> bool:
+    return isinstance(node, ast.Name) and node.id == "__all__"
+
+
+def scan_functions(source: str) -> list[dict[str, Any]]:
+    """The signatures of the functions of a module, from the events of `peg_parser.parse_events`.
+
+    Each function, nested ones included, gives a dict of its ``name``, ``qualname`` as in
+    ``__qualname__``, the qualified name of the ``enclosing`` function or class (or None), ``lineno``,
+    the names of its ``parameters`` in order, the names of those with ``defaults``, whether it
+    ``is_method`` (a function of a class body taking ``self`` or ``cls`` first), the dotted names of
+    its ``decorators``, like ``staticmethod`` or ``name.setter``, and whether it ``is_async``.
+    Lambdas are left out.
+    """
+    functions: list[dict[str, Any]] = []
+    scopes: list[tuple[ast.AST, str]] = []  # the definitions enclosing the last one, with their qualnames
+
+    def on_event(kind: str, location: dict[str, int], node: ast.AST) -> None:
+        while scopes and not encloses(scopes[-1][0], node):
+            scopes.pop()
+        parent, enclosing = scopes[-1] if scopes else (None, None)
+        name = node.name  # type: ignore[attr-defined]
+        if enclosing is None:
+            qualname = name
+        elif isinstance(parent, ast.ClassDef):
+            qualname = f"{enclosing}.{name}"
+        else:
+            qualname = f"{enclosing}.<locals>.{name}"
+        scopes.append((node, qualname))
+        if isinstance(node, ast.FunctionDef | ast.AsyncFunctionDef):
+            functions.append(function_record(node, qualname, enclosing, isinstance(parent, ast.ClassDef)))
+
+    parse_events(source, on_event, kinds=(ast.FunctionDef, ast.AsyncFunctionDef, ast.ClassDef))
+    return functions
+
+
+def encloses(outer: ast.AST, node: ast.AST) -> bool:
+    start = (node.lineno, node.col_offset)  # type: ignore[attr-defined]
+    end = (outer.end_lineno, outer.end_col_offset)  # type: ignore[attr-defined]
+    return (outer.lineno, outer.col_offset) <= start < end  # type: ignore[attr-defined]
+
+
+def function_record(
+    node: ast.FunctionDef | ast.AsyncFunctionDef, qualname: str, enclosing: str | None, in_class: bool
+) -> dict[str, Any]:
+    args = node.args
+    positional = [arg.arg for arg in (*args.posonlyargs, *args.args)]
+    defaults = positional[len(positional) - len(args.defaults) :] if args.defaults else []
+    defaults += [arg.arg for arg, default in zip(args.kwonlyargs, args.kw_defaults) if default is not None]
+    parameters = [*positional, *(arg.arg for arg in (args.vararg, *args.kwonlyargs, args.kwarg) if arg)]
+    return {
+        "name": node.name,
+        "qualname": qualname,
+        "enclosing": enclosing,
+        "lineno": node.lineno,
+        "parameters": parameters,
+        "defaults": defaults,
+        "is_method": in_class and bool(positional) and positional[0] in METHOD_RECEIVERS,
+        "decorators": [name for name in map(dotted_name, node.decorator_list) if name],
+        "is_async": isinstance(node, ast.AsyncFunctionDef),
+    }
+
+
+def dotted_name(node: ast.expr) -> str | None:
+    """The dotted name of a decorator, without the arguments of a call, or None for other expressions."""
+    if isinstance(node, ast.Call):
+        return dotted_name(node.func)
+    if isinstance(node, ast.Name):
+        return node.id
+    if isinstance(node, ast.Attribute) and not isinstance(node.value, ast.Call):
+        value = dotted_name(node.value)
+        return f"{value}.{node.attr}" if value else None
+    return None
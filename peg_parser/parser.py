@@ -5,16 +5,25 @@ import itertools
 import sys
 from typing import Any
 
-from peg_parser.subheader import Del, Load, Parser, Store, Target, logger, memoize, memoize_left_rec
+from peg_parser.subheader import (
+    Del,
+    Load,
+    Parser,
+    Store,
+    Target,
+    logger,
+    memoize,
+    memoize_left_rec,
+)
 
 
 # Keywords and soft keywords are listed at the end of the parser definition.
 class XonshParser(Parser):
     def file(self) -> ast.Module | None:
-        # file: statements? $
+        # file: module_statements? $
         mark = self._mark()
-        if (a := self.statements(),) and (self.token("ENDMARKER")):
-            return ast.Module(body=a or [], type_ignores=[])
+        if (a := self.module_statements(),) and (self.token("ENDMARKER")):
+            return ast.Module(body=a or [], type_ignores=self.type_ignores())
         self._reset(mark)
         return None
 
@@ -38,6 +47,22 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
+    def func_type(self) -> ast.FunctionType | None:
+        # func_type: '(' type_expressions? ')' '->' expression NEWLINE* $
+        mark = self._mark()
+        if (
+            (self.expect("("))
+            and (a := self.type_expressions(),)
+            and (self.expect(")"))
+            and (self.expect("->"))
+            and (b := self.expression())
+            and (self.repeated(self.token, "NEWLINE"),)
+            and (self.token("ENDMARKER"))
+        ):
+            return ast.FunctionType(argtypes=a or [], returns=b)
+        self._reset(mark)
+        return None
+
     def fstring(self) -> ast.JoinedStr | None:
         # fstring: FSTRING_START fstring_mid* FSTRING_END
         mark = self._mark()
@@ -59,6 +84,22 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
+    def module_statements(self) -> list | None:
+        # module_statements: module_statement+
+        mark = self._mark()
+        if a := self.repeated(self.module_statement):
+            return list(itertools.chain.from_iterable(a))
+        self._reset(mark)
+        return None
+
+    def module_statement(self) -> list | None:
+        # module_statement: statement
+        mark = self._mark()
+        if a := self.statement():
+            return self.emit_events(a)
+        self._reset(mark)
+        return None
+
     def statement(self) -> list | None:
         # statement: compound_stmt | simple_stmts
         mark = self._mark()
@@ -112,84 +153,133 @@ class XonshParser(Parser):
         # simple_stmt: assignment | &"type" type_alias | star_expressions | &'return' return_stmt | &('import' | 'from') import_stmt | &'raise' raise_stmt | 'pass' | &'del' del_stmt | &'yield' yield_stmt | &'assert' assert_stmt | 'break' | 'continue' | &'global' global_stmt | &'nonlocal' nonlocal_stmt
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if assignment := self.assignment():
-            return assignment
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "type")) and (type_alias := self.type_alias()):
-            return type_alias
-        self._reset(mark)
-        if e := self.star_expressions():
-            return ast.Expr(value=e, **self.span(_lnum, _col))
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "return")) and (return_stmt := self.return_stmt()):
-            return return_stmt
-        self._reset(mark)
-        if (self.positive_lookahead(self._tmp_1)) and (import_stmt := self.import_stmt()):
-            return import_stmt
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "raise")) and (raise_stmt := self.raise_stmt()):
-            return raise_stmt
-        self._reset(mark)
-        if self.expect("pass"):
-            return ast.Pass(**self.span(_lnum, _col))
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "del")) and (del_stmt := self.del_stmt()):
-            return del_stmt
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "yield")) and (yield_stmt := self.yield_stmt()):
-            return yield_stmt
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "assert")) and (assert_stmt := self.assert_stmt()):
-            return assert_stmt
-        self._reset(mark)
-        if self.expect("break"):
-            return ast.Break(**self.span(_lnum, _col))
-        self._reset(mark)
-        if self.expect("continue"):
-            return ast.Continue(**self.span(_lnum, _col))
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "global")) and (global_stmt := self.global_stmt()):
-            return global_stmt
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "nonlocal")) and (nonlocal_stmt := self.nonlocal_stmt()):
-            return nonlocal_stmt
-        self._reset(mark)
+        match self._tokenizer.peek().string:
+            case "type":
+                if assignment := self.assignment():
+                    return assignment
+                self._reset(mark)
+                if type_alias := self.type_alias():
+                    return type_alias
+                self._reset(mark)
+                if e := self.star_expressions():
+                    return ast.Expr(value=e, **self.span(_lnum, _col))
+                self._reset(mark)
+            case "return":
+                if return_stmt := self.return_stmt():
+                    return return_stmt
+                self._reset(mark)
+            case "from" | "import":
+                if import_stmt := self.import_stmt():
+                    return import_stmt
+                self._reset(mark)
+            case "raise":
+                if raise_stmt := self.raise_stmt():
+                    return raise_stmt
+                self._reset(mark)
+            case "pass":
+                if self.expect("pass"):
+                    return ast.Pass(**self.span(_lnum, _col))
+                self._reset(mark)
+            case "del":
+                if del_stmt := self.del_stmt():
+                    return del_stmt
+                self._reset(mark)
+            case "yield":
+                if assignment := self.assignment():
+                    return assignment
+                self._reset(mark)
+                if yield_stmt := self.yield_stmt():
+                    return yield_stmt
+                self._reset(mark)
+            case "assert":
+                if assert_stmt := self.assert_stmt():
+                    return assert_stmt
+                self._reset(mark)
+            case "break":
+                if self.expect("break"):
+                    return ast.Break(**self.span(_lnum, _col))
+                self._reset(mark)
+            case "continue":
+                if self.expect("continue"):
+                    return ast.Continue(**self.span(_lnum, _col))
+                self._reset(mark)
+            case "global":
+                if global_stmt := self.global_stmt():
+                    return global_stmt
+                self._reset(mark)
+            case "nonlocal":
+                if nonlocal_stmt := self.nonlocal_stmt():
+                    return nonlocal_stmt
+                self._reset(mark)
+            case _:
+                if assignment := self.assignment():
+                    return assignment
+                self._reset(mark)
+                if e := self.star_expressions():
+                    return ast.Expr(value=e, **self.span(_lnum, _col))
+                self._reset(mark)
         return None
 
     def compound_stmt(self) -> Any | None:
         # compound_stmt: &('def' | '@' | 'async') function_def | &'if' if_stmt | &('class' | '@') class_def | &('with' | 'async') with_stmt | &('for' | 'async') for_stmt | &'try' try_stmt | &'while' while_stmt | match_stmt
         mark = self._mark()
-        if (self.positive_lookahead(self._tmp_2)) and (function_def := self.function_def()):
-            return function_def
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "if")) and (if_stmt := self.if_stmt()):
-            return if_stmt
-        self._reset(mark)
-        if (self.positive_lookahead(self._tmp_3)) and (class_def := self.class_def()):
-            return class_def
-        self._reset(mark)
-        if (self.positive_lookahead(self._tmp_4)) and (with_stmt := self.with_stmt()):
-            return with_stmt
-        self._reset(mark)
-        if (self.positive_lookahead(self._tmp_5)) and (for_stmt := self.for_stmt()):
-            return for_stmt
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "try")) and (try_stmt := self.try_stmt()):
-            return try_stmt
-        self._reset(mark)
-        if (self.positive_lookahead(self.expect, "while")) and (while_stmt := self.while_stmt()):
-            return while_stmt
-        self._reset(mark)
-        if match_stmt := self.match_stmt():
-            return match_stmt
-        self._reset(mark)
+        match self._tokenizer.peek().string:
+            case "@":
+                if function_def := self.function_def():
+                    return function_def
+                self._reset(mark)
+                if class_def := self.class_def():
+                    return class_def
+                self._reset(mark)
+            case "async":
+                if function_def := self.function_def():
+                    return function_def
+                self._reset(mark)
+                if with_stmt := self.with_stmt():
+                    return with_stmt
+                self._reset(mark)
+                if for_stmt := self.for_stmt():
+                    return for_stmt
+                self._reset(mark)
+            case "def":
+                if function_def := self.function_def():
+                    return function_def
+                self._reset(mark)
+            case "if":
+                if if_stmt := self.if_stmt():
+                    return if_stmt
+                self._reset(mark)
+            case "class":
+                if class_def := self.class_def():
+                    return class_def
+                self._reset(mark)
+            case "with":
+                if with_stmt := self.with_stmt():
+                    return with_stmt
+                self._reset(mark)
+            case "for":
+                if for_stmt := self.for_stmt():
+                    return for_stmt
+                self._reset(mark)
+            case "try":
+                if try_stmt := self.try_stmt():
+                    return try_stmt
+                self._reset(mark)
+            case "while":
+                if while_stmt := self.while_stmt():
+                    return while_stmt
+                self._reset(mark)
+            case _:
+                if match_stmt := self.match_stmt():
+                    return match_stmt
+                self._reset(mark)
         return None
 
     def assignment(self) -> Any | None:
         # assignment: NAME ':' expression ['=' annotated_rhs] | ('(' single_target ')' | single_subscript_attribute_target) ':' expression ['=' annotated_rhs] | ((star_targets '='))+ annotated_rhs !'=' TYPE_COMMENT? | single_target augassign ~ annotated_rhs | invalid_assignment
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (a := self.name()) and (self.expect(":")) and (b := self.expression()) and (c := self._tmp_6(),):
+        if (a := self.name()) and (self.expect(":")) and (b := self.expression()) and (c := self._tmp_1(),):
             return ast.AnnAssign(
                 target=ast.Name(
                     id=a.string,
@@ -205,16 +295,21 @@ class XonshParser(Parser):
                 **self.span(_lnum, _col),
             )
         self._reset(mark)
-        if (a := self._tmp_7()) and (self.expect(":")) and (b := self.expression()) and (c := self._tmp_6(),):
+        if (a := self._tmp_2()) and (self.expect(":")) and (b := self.expression()) and (c := self._tmp_1(),):
             return ast.AnnAssign(target=a, annotation=b, value=c, simple=0, **self.span(_lnum, _col))
         self._reset(mark)
         if (
-            (a := self.repeated(self._tmp_9))
+            (a := self.repeated(self._tmp_4))
             and (b := self.annotated_rhs())
             and (self.negative_lookahead(self.expect, "="))
             and (tc := self.token("TYPE_COMMENT"),)
         ):
-            return ast.Assign(targets=a, value=b, type_comment=tc, **self.span(_lnum, _col))
+            return ast.Assign(
+                targets=[self.check_star_targets(t) for t in a],
+                value=b,
+                type_comment=tc.string if tc else None,
+                **self.span(_lnum, _col),
+            )
         self._reset(mark)
         cut = False
         if (
@@ -296,7 +391,7 @@ class XonshParser(Parser):
         # raise_stmt: 'raise' expression ['from' expression] | 'raise'
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (self.expect("raise")) and (a := self.expression()) and (b := self._tmp_10(),):
+        if (self.expect("raise")) and (a := self.expression()) and (b := self._tmp_5(),):
             return ast.Raise(exc=a, cause=b, **self.span(_lnum, _col))
         self._reset(mark)
         if self.expect("raise"):
@@ -326,7 +421,7 @@ class XonshParser(Parser):
         # del_stmt: 'del' del_targets &(';' | NEWLINE) | invalid_del_stmt
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (self.expect("del")) and (a := self.del_targets()) and (self.positive_lookahead(self._tmp_11)):
+        if (self.expect("del")) and (a := self.del_targets()) and (self.positive_lookahead(self._tmp_6)):
             return ast.Delete(targets=a, **self.span(_lnum, _col))
         self._reset(mark)
         if self.call_invalid_rules and (self.invalid_del_stmt()):
@@ -347,7 +442,7 @@ class XonshParser(Parser):
         # assert_stmt: 'assert' expression [',' expression]
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (self.expect("assert")) and (a := self.expression()) and (b := self._tmp_12(),):
+        if (self.expect("assert")) and (a := self.expression()) and (b := self._tmp_7(),):
             return ast.Assert(test=a, msg=b, **self.span(_lnum, _col))
         self._reset(mark)
         return None
@@ -375,7 +470,7 @@ class XonshParser(Parser):
         _lnum, _col = self._tokenizer.peek().start
         if (
             (self.expect("from"))
-            and (a := self.repeated(self._tmp_13),)
+            and (a := self.repeated(self._tmp_8),)
             and (b := self.dotted_name())
             and (self.expect("import"))
             and (c := self.import_from_targets())
@@ -386,7 +481,7 @@ class XonshParser(Parser):
         self._reset(mark)
         if (
             (self.expect("from"))
-            and (a := self.repeated(self._tmp_13))
+            and (a := self.repeated(self._tmp_8))
             and (self.expect("import"))
             and (b := self.import_from_targets())
         ):
@@ -431,7 +526,7 @@ class XonshParser(Parser):
         # import_from_as_name: NAME ['as' NAME]
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (a := self.name()) and (b := self._tmp_15(),):
+        if (a := self.name()) and (b := self._tmp_10(),):
             return ast.alias(name=a.string, asname=b, **self.span(_lnum, _col))
         self._reset(mark)
         return None
@@ -448,7 +543,7 @@ class XonshParser(Parser):
         # dotted_as_name: dotted_name ['as' NAME]
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (a := self.dotted_name()) and (b := self._tmp_15(),):
+        if (a := self.dotted_name()) and (b := self._tmp_10(),):
             return ast.alias(name=a, asname=b, **self.span(_lnum, _col))
         self._reset(mark)
         return None
@@ -494,45 +589,10 @@ class XonshParser(Parser):
         return None
 
     def decorator(self) -> Any | None:
-        # decorator: ('@' dec_maybe_call NEWLINE) | ('@' named_expression NEWLINE)
+        # decorator: '@' named_expression NEWLINE
         mark = self._mark()
-        if a := self._tmp_17():
-            return a
-        self._reset(mark)
-        if a := self._tmp_18():
-            return a
-        self._reset(mark)
-        return None
-
-    def dec_maybe_call(self) -> Any | None:
-        # dec_maybe_call: dec_primary '(' arguments? ')' | dec_primary
-        mark = self._mark()
-        _lnum, _col = self._tokenizer.peek().start
-        if (
-            (dn := self.dec_primary())
-            and (self.expect("("))
-            and (z := self.arguments(),)
-            and (self.expect(")"))
-        ):
-            return ast.Call(
-                func=dn, args=z[0] if z else [], keywords=z[1] if z else [], **self.span(_lnum, _col)
-            )
-        self._reset(mark)
-        if dec_primary := self.dec_primary():
-            return dec_primary
-        self._reset(mark)
-        return None
-
-    @memoize_left_rec
-    def dec_primary(self) -> Any | None:
-        # dec_primary: dec_primary '.' NAME | NAME
-        mark = self._mark()
-        _lnum, _col = self._tokenizer.peek().start
-        if (a := self.dec_primary()) and (self.expect(".")) and (b := self.name()):
-            return ast.Attribute(value=a, attr=b.string, ctx=Load, **self.span(_lnum, _col))
-        self._reset(mark)
-        if a := self.name():
-            return ast.Name(id=a.string, ctx=Load, **self.span(_lnum, _col))
+        if (self.expect("@")) and (f := self.named_expression()) and (self.token("NEWLINE")):
+            return f
         self._reset(mark)
         return None
 
@@ -558,13 +618,13 @@ class XonshParser(Parser):
             (self.expect("class"))
             and (a := self.name())
             and (t := self.type_params(),)
-            and (b := self._tmp_19(),)
+            and (b := self._tmp_12(),)
             and (self.expect_forced(self.expect(":"), "':'"))
             and (c := self.block())
         ):
             return (
                 ast.ClassDef(
-                    a.string,
+                    name=a.string,
                     bases=b[0] if b else [],
                     keywords=b[1] if b else [],
                     body=c,
@@ -574,7 +634,7 @@ class XonshParser(Parser):
                 )
                 if sys.version_info >= (3, 12)
                 else ast.ClassDef(
-                    a.string,
+                    name=a.string,
                     bases=b[0] if b else [],
                     keywords=b[1] if b else [],
                     body=c,
@@ -610,7 +670,7 @@ class XonshParser(Parser):
             and (self.expect_forced(self.expect("("), "'('"))
             and (params := self.params(),)
             and (self.expect(")"))
-            and (a := self._tmp_20(),)
+            and (a := self._tmp_13(),)
             and (self.expect_forced(self.expect(":"), "':'"))
             and (tc := self.func_type_comment(),)
             and (b := self.block())
@@ -644,7 +704,7 @@ class XonshParser(Parser):
             and (self.expect_forced(self.expect("("), "'('"))
             and (params := self.params(),)
             and (self.expect(")"))
-            and (a := self._tmp_20(),)
+            and (a := self._tmp_13(),)
             and (self.expect_forced(self.expect(":"), "':'"))
             and (tc := self.func_type_comment(),)
             and (b := self.block())
@@ -797,30 +857,30 @@ class XonshParser(Parser):
     def param_no_default(self) -> ast.arg | None:
         # param_no_default: param ',' TYPE_COMMENT? | param TYPE_COMMENT? &')'
         mark = self._mark()
-        if (a := self.param()) and (self.expect(",")) and (self.token("TYPE_COMMENT"),):
-            return a
+        if (a := self.param()) and (self.expect(",")) and (tc := self.token("TYPE_COMMENT"),):
+            return self.add_type_comment(a, tc)
         self._reset(mark)
         if (
             (a := self.param())
-            and (self.token("TYPE_COMMENT"),)
+            and (tc := self.token("TYPE_COMMENT"),)
             and (self.positive_lookahead(self.expect, ")"))
         ):
-            return a
+            return self.add_type_comment(a, tc)
         self._reset(mark)
         return None
 
     def param_no_default_star_annotation(self) -> ast.arg | None:
         # param_no_default_star_annotation: param_star_annotation ',' TYPE_COMMENT? | param_star_annotation TYPE_COMMENT? &')'
         mark = self._mark()
-        if (a := self.param_star_annotation()) and (self.expect(",")) and (self.token("TYPE_COMMENT"),):
-            return a
+        if (a := self.param_star_annotation()) and (self.expect(",")) and (tc := self.token("TYPE_COMMENT"),):
+            return self.add_type_comment(a, tc)
         self._reset(mark)
         if (
             (a := self.param_star_annotation())
-            and (self.token("TYPE_COMMENT"),)
+            and (tc := self.token("TYPE_COMMENT"),)
             and (self.positive_lookahead(self.expect, ")"))
         ):
-            return a
+            return self.add_type_comment(a, tc)
         self._reset(mark)
         return None
 
@@ -831,17 +891,17 @@ class XonshParser(Parser):
             (a := self.param())
             and (c := self.default())
             and (self.expect(","))
-            and (self.token("TYPE_COMMENT"),)
+            and (tc := self.token("TYPE_COMMENT"),)
         ):
-            return (a, c)
+            return (self.add_type_comment(a, tc), c)
         self._reset(mark)
         if (
             (a := self.param())
             and (c := self.default())
-            and (self.token("TYPE_COMMENT"),)
+            and (tc := self.token("TYPE_COMMENT"),)
             and (self.positive_lookahead(self.expect, ")"))
         ):
-            return (a, c)
+            return (self.add_type_comment(a, tc), c)
         self._reset(mark)
         return None
 
@@ -852,17 +912,17 @@ class XonshParser(Parser):
             (a := self.param())
             and (c := self.default(),)
             and (self.expect(","))
-            and (self.token("TYPE_COMMENT"),)
+            and (tc := self.token("TYPE_COMMENT"),)
         ):
-            return (a, c)
+            return (self.add_type_comment(a, tc), c)
         self._reset(mark)
         if (
             (a := self.param())
             and (c := self.default(),)
-            and (self.token("TYPE_COMMENT"),)
+            and (tc := self.token("TYPE_COMMENT"),)
             and (self.positive_lookahead(self.expect, ")"))
         ):
-            return (a, c)
+            return (self.add_type_comment(a, tc), c)
         self._reset(mark)
         return None
 
@@ -880,7 +940,9 @@ class XonshParser(Parser):
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
         if (a := self.name()) and (b := self.star_annotation()):
-            return ast.arg(arg=a.string, annotations=b, **self.span(_lnum, _col))
+            return self.check_unpacking_version(
+                [b], "Starred annotations are", ast.arg(arg=a.string, annotation=b, **self.span(_lnum, _col))
+            )
         self._reset(mark)
         return None
 
@@ -1014,7 +1076,12 @@ class XonshParser(Parser):
             and (el := self.else_block(),)
         ):
             return ast.For(
-                target=t, iter=ex, body=b, orelse=el or [], type_comment=tc, **self.span(_lnum, _col)
+                target=self.check_star_targets(t),
+                iter=ex,
+                body=b,
+                orelse=el or [],
+                type_comment=tc.string if tc else None,
+                **self.span(_lnum, _col),
             )
         self._reset(mark)
         if cut:
@@ -1033,7 +1100,12 @@ class XonshParser(Parser):
             and (el := self.else_block(),)
         ):
             return ast.AsyncFor(
-                target=t, iter=ex, body=b, orelse=el or [], type_comment=tc, **self.span(_lnum, _col)
+                target=self.check_star_targets(t),
+                iter=ex,
+                body=b,
+                orelse=el or [],
+                type_comment=tc.string if tc else None,
+                **self.span(_lnum, _col),
             )
         self._reset(mark)
         if cut:
@@ -1062,14 +1134,16 @@ class XonshParser(Parser):
             return None
         if (
             (self.expect("with"))
-            and (self.expect("("))
+            and (l := self.expect("("))
             and (a := self.gathered(self.with_item, self.expect, ","))
-            and (self.expect(","),)
-            and (self.expect(")"))
+            and (c := self.expect(","),)
+            and (r := self.expect(")"))
             and (self.expect(":"))
             and (b := self.block())
         ):
-            return ast.With(items=a, body=b, **self.span(_lnum, _col))
+            return self.parenthesized_with(
+                ast.With(items=a, body=b, **self.span(_lnum, _col)), None if c else (l, r)
+            )
         self._reset(mark)
         if (
             (self.expect("with"))
@@ -1078,19 +1152,21 @@ class XonshParser(Parser):
             and (tc := self.token("TYPE_COMMENT"),)
             and (b := self.block())
         ):
-            return ast.With(items=a, body=b, type_comment=tc, **self.span(_lnum, _col))
+            return ast.With(items=a, body=b, type_comment=tc.string if tc else None, **self.span(_lnum, _col))
         self._reset(mark)
         if (
             (self.expect("async"))
             and (self.expect("with"))
-            and (self.expect("("))
+            and (l := self.expect("("))
             and (a := self.gathered(self.with_item, self.expect, ","))
-            and (self.expect(","),)
-            and (self.expect(")"))
+            and (c := self.expect(","),)
+            and (r := self.expect(")"))
             and (self.expect(":"))
             and (b := self.block())
         ):
-            return ast.AsyncWith(items=a, body=b, **self.span(_lnum, _col))
+            return self.parenthesized_with(
+                ast.AsyncWith(items=a, body=b, **self.span(_lnum, _col)), None if c else (l, r)
+            )
         self._reset(mark)
         if (
             (self.expect("async"))
@@ -1100,7 +1176,9 @@ class XonshParser(Parser):
             and (tc := self.token("TYPE_COMMENT"),)
             and (b := self.block())
         ):
-            return ast.AsyncWith(items=a, body=b, type_comment=tc, **self.span(_lnum, _col))
+            return ast.AsyncWith(
+                items=a, body=b, type_comment=tc.string if tc else None, **self.span(_lnum, _col)
+            )
         self._reset(mark)
         if self.call_invalid_rules and (self.invalid_with_stmt()):
             return None
@@ -1114,9 +1192,9 @@ class XonshParser(Parser):
             (e := self.expression())
             and (self.expect("as"))
             and (t := self.star_target())
-            and (self.positive_lookahead(self._tmp_22))
+            and (self.positive_lookahead(self._tmp_15))
         ):
-            return ast.withitem(context_expr=e, optional_vars=t)
+            return ast.withitem(context_expr=e, optional_vars=self.check_star_targets(t))
         self._reset(mark)
         if self.call_invalid_rules and (self.invalid_with_item()):
             return None
@@ -1185,12 +1263,8 @@ class XonshParser(Parser):
             and (el := self.else_block(),)
             and (f := self.finally_block(),)
         ):
-            return self.check_version(
-                (3, 11),
-                "Exception groups are",
-                ast.TryStar(body=b, handlers=ex, orelse=el or [], finalbody=f or [], **self.span(_lnum, _col))
-                if sys.version_info >= (3, 11)
-                else None,
+            return self.versioned_node("TryStar", "Exception groups are")(
+                body=b, handlers=ex, orelse=el or [], finalbody=f or [], **self.span(_lnum, _col)
             )
         self._reset(mark)
         return None
@@ -1205,7 +1279,7 @@ class XonshParser(Parser):
         if (
             (self.expect("except"))
             and (e := self.expression())
-            and (t := self._tmp_15(),)
+            and (t := self._tmp_10(),)
             and (self.expect(":"))
             and (b := self.block())
         ):
@@ -1230,7 +1304,7 @@ class XonshParser(Parser):
             (self.expect("except"))
             and (self.expect("*"))
             and (e := self.expression())
-            and (t := self._tmp_15(),)
+            and (t := self._tmp_10(),)
             and (self.expect(":"))
             and (b := self.block())
         ):
@@ -1376,7 +1450,7 @@ class XonshParser(Parser):
         # literal_pattern: signed_number !('+' | '-') | complex_number | strings | 'None' | 'True' | 'False'
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (value := self.signed_number()) and (self.negative_lookahead(self._tmp_25)):
+        if (value := self.signed_number()) and (self.negative_lookahead(self._tmp_18)):
             return ast.MatchValue(value=value, **self.span(_lnum, _col))
         self._reset(mark)
         if value := self.complex_number():
@@ -1400,7 +1474,7 @@ class XonshParser(Parser):
         # literal_expr: signed_number !('+' | '-') | complex_number | strings | 'None' | 'True' | 'False'
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (signed_number := self.signed_number()) and (self.negative_lookahead(self._tmp_25)):
+        if (signed_number := self.signed_number()) and (self.negative_lookahead(self._tmp_18)):
             return signed_number
         self._reset(mark)
         if complex_number := self.complex_number():
@@ -1437,13 +1511,13 @@ class XonshParser(Parser):
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
         if a := self.token("NUMBER"):
-            return ast.Constant(value=ast.literal_eval(a.string), **self.span(_lnum, _col))
+            return ast.Constant(value=self.number(a), **self.span(_lnum, _col))
         self._reset(mark)
         if (self.expect("-")) and (a := self.token("NUMBER")):
             return ast.UnaryOp(
                 op=ast.USub(),
                 operand=ast.Constant(
-                    value=ast.literal_eval(a.string),
+                    value=self.number(a),
                     lineno=a.start[0],
                     col_offset=a.start[1],
                     end_lineno=a.end[0],
@@ -1499,7 +1573,7 @@ class XonshParser(Parser):
         if (
             (self.negative_lookahead(self.expect, "_"))
             and (name := self.name())
-            and (self.negative_lookahead(self._tmp_27))
+            and (self.negative_lookahead(self._tmp_20))
         ):
             return name.string
         self._reset(mark)
@@ -1510,7 +1584,7 @@ class XonshParser(Parser):
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
         if self.expect("_"):
-            return ast.MatchAs(pattern=None, target=None, **self.span(_lnum, _col))
+            return ast.MatchAs(pattern=None, name=None, **self.span(_lnum, _col))
         self._reset(mark)
         return None
 
@@ -1518,7 +1592,7 @@ class XonshParser(Parser):
         # value_pattern: attr !('.' | '(' | '=')
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (attr := self.attr()) and (self.negative_lookahead(self._tmp_27)):
+        if (attr := self.attr()) and (self.negative_lookahead(self._tmp_20)):
             return ast.MatchValue(value=attr, **self.span(_lnum, _col))
         self._reset(mark)
         return None
@@ -1549,8 +1623,9 @@ class XonshParser(Parser):
     def group_pattern(self) -> Any | None:
         # group_pattern: '(' pattern ')'
         mark = self._mark()
+        _lnum, _col = self._tokenizer.peek().start
         if (self.expect("(")) and (pattern := self.pattern()) and (self.expect(")")):
-            return pattern
+            return self.grouped(pattern, **self.span(_lnum, _col))
         self._reset(mark)
         return None
 
@@ -1601,7 +1676,7 @@ class XonshParser(Parser):
             return ast.MatchStar(name=target, **self.span(_lnum, _col))
         self._reset(mark)
         if (self.expect("*")) and (self.wildcard_pattern()):
-            return ast.MatchStar(target=None, **self.span(_lnum, _col))
+            return ast.MatchStar(name=None, **self.span(_lnum, _col))
         self._reset(mark)
         return None
 
@@ -1661,7 +1736,7 @@ class XonshParser(Parser):
     def key_value_pattern(self) -> Any | None:
         # key_value_pattern: (literal_expr | attr) ':' pattern
         mark = self._mark()
-        if (key := self._tmp_29()) and (self.expect(":")) and (pattern := self.pattern()):
+        if (key := self._tmp_22()) and (self.expect(":")) and (pattern := self.pattern()):
             return (key, pattern)
         self._reset(mark)
         return None
@@ -1766,24 +1841,18 @@ class XonshParser(Parser):
             and (self.expect("="))
             and (b := self.expression())
         ):
-            return self.check_version(
-                (3, 12),
-                "Type statement is",
-                ast.TypeAlias(
-                    name=ast.Name(
-                        id=n.string,
-                        ctx=Store,
-                        lineno=n.start[0],
-                        col_offset=n.start[1],
-                        end_lineno=n.end[0],
-                        end_col_offset=n.end[1],
-                    ),
-                    type_params=t or [],
-                    value=b,
-                    **self.span(_lnum, _col),
-                )
-                if sys.version_info >= (3, 12)
-                else None,
+            return self.versioned_node("TypeAlias", "Type statement is")(
+                name=ast.Name(
+                    id=n.string,
+                    ctx=Store,
+                    lineno=n.start[0],
+                    col_offset=n.start[1],
+                    end_lineno=n.end[0],
+                    end_col_offset=n.end[1],
+                ),
+                type_params=t or [],
+                value=b,
+                **self.span(_lnum, _col),
             )
         self._reset(mark)
         return None
@@ -1810,10 +1879,8 @@ class XonshParser(Parser):
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
         if (a := self.name()) and (b := self.type_param_bound(),):
-            return (
-                ast.TypeVar(name=a.string, bound=b, **self.span(_lnum, _col))
-                if sys.version_info >= (3, 12)
-                else object()
+            return self.versioned_node("TypeVar", "Type parameter lists are")(
+                name=a.string, bound=b, **self.span(_lnum, _col)
             )
         self._reset(mark)
         if (self.expect("*")) and (self.name()) and (colon := self.expect(":")) and (e := self.expression()):
@@ -1825,10 +1892,8 @@ class XonshParser(Parser):
             )
         self._reset(mark)
         if (self.expect("*")) and (a := self.name()):
-            return (
-                ast.TypeVarTuple(name=a.string, **self.span(_lnum, _col))
-                if sys.version_info >= (3, 12)
-                else object()
+            return self.versioned_node("TypeVarTuple", "Type parameter lists are")(
+                name=a.string, **self.span(_lnum, _col)
             )
         self._reset(mark)
         if (self.expect("**")) and (self.name()) and (colon := self.expect(":")) and (e := self.expression()):
@@ -1840,10 +1905,8 @@ class XonshParser(Parser):
             )
         self._reset(mark)
         if (self.expect("**")) and (a := self.name()):
-            return (
-                ast.ParamSpec(name=a.string, **self.span(_lnum, _col))
-                if sys.version_info >= (3, 12)
-                else object()
+            return self.versioned_node("ParamSpec", "Type parameter lists are")(
+                name=a.string, **self.span(_lnum, _col)
             )
         self._reset(mark)
         return None
@@ -1860,7 +1923,7 @@ class XonshParser(Parser):
         # expressions: expression ((',' expression))+ ','? | expression ',' | expression
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (a := self.expression()) and (b := self.repeated(self._tmp_30)) and (self.expect(","),):
+        if (a := self.expression()) and (b := self.repeated(self._tmp_23)) and (self.expect(","),):
             return ast.Tuple(elts=[a] + b, ctx=Load, **self.span(_lnum, _col))
         self._reset(mark)
         if (a := self.expression()) and (self.expect(",")):
@@ -1915,7 +1978,7 @@ class XonshParser(Parser):
         # star_expressions: star_expression ((',' star_expression))+ ','? | star_expression ',' | star_expression
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (a := self.star_expression()) and (b := self.repeated(self._tmp_31)) and (self.expect(","),):
+        if (a := self.star_expression()) and (b := self.repeated(self._tmp_24)) and (self.expect(","),):
             return ast.Tuple(elts=[a] + b, ctx=Load, **self.span(_lnum, _col))
         self._reset(mark)
         if (a := self.star_expression()) and (self.expect(",")):
@@ -1998,10 +2061,10 @@ class XonshParser(Parser):
 
     @memoize
     def disjunction(self) -> Any | None:
-        # disjunction: conjunction ((('or' | '||') conjunction))+ | conjunction
+        # disjunction: conjunction ((or_op conjunction))+ | conjunction
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (a := self.conjunction()) and (b := self.repeated(self._tmp_32)):
+        if (a := self.conjunction()) and (b := self.repeated(self._tmp_25)):
             return ast.BoolOp(op=ast.Or(), values=[a] + b, **self.span(_lnum, _col))
         self._reset(mark)
         if conjunction := self.conjunction():
@@ -2011,10 +2074,10 @@ class XonshParser(Parser):
 
     @memoize
     def conjunction(self) -> Any | None:
-        # conjunction: inversion ((('and' | '&&') inversion))+ | inversion
+        # conjunction: inversion ((and_op inversion))+ | inversion
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (a := self.inversion()) and (b := self.repeated(self._tmp_33)):
+        if (a := self.inversion()) and (b := self.repeated(self._tmp_26)):
             return ast.BoolOp(op=ast.And(), values=[a] + b, **self.span(_lnum, _col))
         self._reset(mark)
         if inversion := self.inversion():
@@ -2022,11 +2085,36 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
+    def or_op(self) -> Any | None:
+        # or_op: 'or' | '||'
+        mark = self._mark()
+        if literal := self.expect("or"):
+            return literal
+        self._reset(mark)
+        if a := self.expect("||"):
+            return self.xonsh_operator(a, "or")
+        self._reset(mark)
+        return None
+
+    def and_op(self) -> Any | None:
+        # and_op: 'and' | '&&'
+        mark = self._mark()
+        if literal := self.expect("and"):
+            return literal
+        self._reset(mark)
+        if a := self.expect("&&"):
+            return self.xonsh_operator(a, "and")
+        self._reset(mark)
+        return None
+
     @memoize
     def inversion(self) -> Any | None:
-        # inversion: 'not' inversion | comparison
+        # inversion: invalid_inversion | 'not' inversion | comparison
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
+        if self.call_invalid_rules and (self.invalid_inversion()):
+            return None
+        self._reset(mark)
         if (self.expect("not")) and (a := self.inversion()):
             return ast.UnaryOp(op=ast.Not(), operand=a, **self.span(_lnum, _col))
         self._reset(mark)
@@ -2245,9 +2333,12 @@ class XonshParser(Parser):
 
     @memoize
     def factor(self) -> Any | None:
-        # factor: '+' factor | '-' factor | '~' factor | power
+        # factor: invalid_factor | '+' factor | '-' factor | '~' factor | power
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
+        if self.call_invalid_rules and (self.invalid_factor()):
+            return None
+        self._reset(mark)
         if (self.expect("+")) and (a := self.factor()):
             return ast.UnaryOp(op=ast.UAdd(), operand=a, **self.span(_lnum, _col))
         self._reset(mark)
@@ -2280,7 +2371,7 @@ class XonshParser(Parser):
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
         if (self.expect("await")) and (a := self.primary()):
-            return ast.Await(a, **self.span(_lnum, _col))
+            return ast.Await(value=a, **self.span(_lnum, _col))
         self._reset(mark)
         if primary := self.primary():
             return primary
@@ -2318,7 +2409,7 @@ class XonshParser(Parser):
             return ast.Subscript(value=a, slice=b, ctx=Load, **self.span(_lnum, _col))
         self._reset(mark)
         cut = False
-        if (self.positive_lookahead(self._tmp_34)) and (cut := True) and (sub_procs := self.sub_procs()):
+        if (self.positive_lookahead(self._tmp_27)) and (cut := True) and (sub_procs := self.sub_procs()):
             return sub_procs
         self._reset(mark)
         if cut:
@@ -2376,7 +2467,7 @@ class XonshParser(Parser):
     def help_atom(self) -> Any | None:
         # help_atom: atom ('??' | '?')
         mark = self._mark()
-        if (a := self.atom()) and (b := self._tmp_35()):
+        if (a := self.atom()) and (b := self._tmp_28()):
             return (a, b)
         self._reset(mark)
         return None
@@ -2409,7 +2500,7 @@ class XonshParser(Parser):
             return sub_procs
         self._reset(mark)
         cut = False
-        if (self.expect("@(")) and (cut := True) and (a := self._tmp_36()) and (self.expect(")")):
+        if (self.expect("@(")) and (cut := True) and (a := self._tmp_29()) and (self.expect(")")):
             return self.proc_pyexpr(a, **self.span(_lnum, _col))
         self._reset(mark)
         if cut:
@@ -2430,7 +2521,7 @@ class XonshParser(Parser):
             return search_path
         self._reset(mark)
         cut = False
-        if (self.proc_macro_start()) and (cut := True) and (a := self.repeated(self._tmp_37),):
+        if (self.proc_macro_start()) and (cut := True) and (a := self.repeated(self._tmp_30),):
             return self.proc_macro_arg(a, **self.span(_lnum, _col))
         self._reset(mark)
         if cut:
@@ -2484,10 +2575,10 @@ class XonshParser(Parser):
     def cmd_group(self) -> Any | None:
         # cmd_group: ('(' | '!(' | '$(') any_cmd* ')' | ('[' | '![' | '$[') any_cmd* ']'
         mark = self._mark()
-        if (a := self._tmp_38()) and (b := self.repeated(self.any_cmd),) and (c := self.expect(")")):
+        if (a := self._tmp_31()) and (b := self.repeated(self.any_cmd),) and (c := self.expect(")")):
             return "".join(i.string for i in [a, *b, c])
         self._reset(mark)
-        if (a := self._tmp_39()) and (b := self.repeated(self.any_cmd),) and (c := self.expect("]")):
+        if (a := self._tmp_32()) and (b := self.repeated(self.any_cmd),) and (c := self.expect("]")):
             return "".join(i.string for i in [a, *b, c])
         self._reset(mark)
         return None
@@ -2499,8 +2590,10 @@ class XonshParser(Parser):
         if (a := self.slice()) and (self.negative_lookahead(self.expect, ",")):
             return a
         self._reset(mark)
-        if (a := self.gathered(self._tmp_40, self.expect, ",")) and (self.expect(","),):
-            return ast.Tuple(elts=a, ctx=Load, **self.span(_lnum, _col))
+        if (a := self.gathered(self._tmp_33, self.expect, ",")) and (self.expect(","),):
+            return self.check_unpacking_version(
+                a, "Unpacking in subscripts is", ast.Tuple(elts=a, ctx=Load, **self.span(_lnum, _col))
+            )
         self._reset(mark)
         return None
 
@@ -2512,7 +2605,7 @@ class XonshParser(Parser):
             (a := self.expression(),)
             and (self.expect(":"))
             and (b := self.expression(),)
-            and (c := self._tmp_41(),)
+            and (c := self._tmp_34(),)
         ):
             return ast.Slice(lower=a, upper=b, step=c, **self.span(_lnum, _col))
         self._reset(mark)
@@ -2521,10 +2614,14 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
+    @memoize
     def atom(self) -> Any | None:
-        # atom: search_path | NAME | 'True' | 'False' | 'None' | &(STRING | FSTRING_START) strings | NUMBER | &'(' (ptuple | group | genexp) | &'[' (plist | listcomp) | &'{' (dict | set | dictcomp | setcomp) | '...'
+        # atom: invalid_path_literal | search_path | NAME | 'True' | 'False' | 'None' | &(STRING | FSTRING_START) strings | NUMBER | &'(' (ptuple | group | genexp) | &'[' (plist | listcomp) | &'{' (dict | set | dictcomp | setcomp) | '...'
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
+        if self.call_invalid_rules and (self.invalid_path_literal()):
+            return None
+        self._reset(mark)
         if search_path := self.search_path():
             return search_path
         self._reset(mark)
@@ -2540,20 +2637,20 @@ class XonshParser(Parser):
         if self.expect("None"):
             return ast.Constant(value=None, **self.span(_lnum, _col))
         self._reset(mark)
-        if (self.positive_lookahead(self._tmp_42)) and (strings := self.strings()):
+        if (self.positive_lookahead(self._tmp_35)) and (strings := self.strings()):
             return strings
         self._reset(mark)
         if a := self.token("NUMBER"):
-            return ast.Constant(value=ast.literal_eval(a.string), **self.span(_lnum, _col))
+            return ast.Constant(value=self.number(a), **self.span(_lnum, _col))
         self._reset(mark)
-        if (self.positive_lookahead(self.expect, "(")) and (_tmp_43 := self._tmp_43()):
-            return _tmp_43
+        if (self.positive_lookahead(self.expect, "(")) and (_tmp_36 := self._tmp_36()):
+            return _tmp_36
         self._reset(mark)
-        if (self.positive_lookahead(self.expect, "[")) and (_tmp_44 := self._tmp_44()):
-            return _tmp_44
+        if (self.positive_lookahead(self.expect, "[")) and (_tmp_37 := self._tmp_37()):
+            return _tmp_37
         self._reset(mark)
-        if (self.positive_lookahead(self.expect, "{")) and (_tmp_45 := self._tmp_45()):
-            return _tmp_45
+        if (self.positive_lookahead(self.expect, "{")) and (_tmp_38 := self._tmp_38()):
+            return _tmp_38
         self._reset(mark)
         if self.expect("..."):
             return ast.Constant(value=Ellipsis, **self.span(_lnum, _col))
@@ -2572,8 +2669,9 @@ class XonshParser(Parser):
     def group(self) -> Any | None:
         # group: '(' (yield_expr | named_expression) ')' | invalid_group
         mark = self._mark()
-        if (self.expect("(")) and (a := self._tmp_46()) and (self.expect(")")):
-            return a
+        _lnum, _col = self._tokenizer.peek().start
+        if (self.expect("(")) and (a := self._tmp_39()) and (self.expect(")")):
+            return self.grouped(a, **self.span(_lnum, _col))
         self._reset(mark)
         if self.call_invalid_rules and (self.invalid_group()):
             return None
@@ -2762,7 +2860,7 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
-    def fstring_mid(self) -> ast.FormattedValue | ast.Constant | None:
+    def fstring_mid(self) -> ast.FormattedValue | ast.JoinedStr | ast.Constant | None:
         # fstring_mid: fstring_replacement_field | FSTRING_MIDDLE
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
@@ -2774,7 +2872,7 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
-    def fstring_replacement_field(self) -> ast.FormattedValue | None:
+    def fstring_replacement_field(self) -> ast.FormattedValue | ast.JoinedStr | None:
         # fstring_replacement_field: '{' annotated_rhs '='? fstring_conversion? fstring_full_format_spec? '}' | invalid_replacement_field
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
@@ -2786,12 +2884,7 @@ class XonshParser(Parser):
             and (format := self.fstring_full_format_spec(),)
             and (self.expect("}"))
         ):
-            return ast.FormattedValue(
-                value=a,
-                conversion=conver if conver else b"r"[0] if debug_expr else -1,
-                format_spec=format,
-                **self.span(_lnum, _col),
-            )
+            return self.formatted_value(a, debug_expr, conver, format, **self.span(_lnum, _col))
         self._reset(mark)
         if self.call_invalid_rules and (self.invalid_replacement_field()):
             return None
@@ -2811,9 +2904,7 @@ class XonshParser(Parser):
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
         if (self.expect(":")) and (spec := self.repeated(self.fstring_format_spec),):
-            return ast.JoinedStr(
-                values=spec if spec and (len(spec) > 1 or spec[0].value) else [], **self.span(_lnum, _col)
-            )
+            return self.format_spec(spec, **self.span(_lnum, _col))
         self._reset(mark)
         return None
 
@@ -2833,7 +2924,7 @@ class XonshParser(Parser):
     def strings(self) -> Any | None:
         # strings: ((fstring | STRING))+
         mark = self._mark()
-        if a := self.repeated(self._tmp_47):
+        if a := self.repeated(self._tmp_40):
             return self.concatenate_strings(a)
         self._reset(mark)
         return None
@@ -2851,7 +2942,7 @@ class XonshParser(Parser):
         # ptuple: '(' [star_named_expression ',' star_named_expressions?] ')'
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (self.expect("(")) and (a := self._tmp_48(),) and (self.expect(")")):
+        if (self.expect("(")) and (a := self._tmp_41(),) and (self.expect(")")):
             return ast.Tuple(elts=a or [], ctx=Load, **self.span(_lnum, _col))
         self._reset(mark)
         return None
@@ -2930,9 +3021,9 @@ class XonshParser(Parser):
             and (self.expect("in"))
             and (cut := True)
             and (b := self.disjunction())
-            and (c := self.repeated(self._tmp_49),)
+            and (c := self.repeated(self._tmp_42),)
         ):
-            return ast.comprehension(target=a, iter=b, ifs=c, is_async=1)
+            return ast.comprehension(target=self.check_star_targets(a), iter=b, ifs=c, is_async=1)
         self._reset(mark)
         if cut:
             return None
@@ -2943,9 +3034,9 @@ class XonshParser(Parser):
             and (self.expect("in"))
             and (cut := True)
             and (b := self.disjunction())
-            and (c := self.repeated(self._tmp_49),)
+            and (c := self.repeated(self._tmp_42),)
         ):
-            return ast.comprehension(target=a, iter=b, ifs=c, is_async=0)
+            return ast.comprehension(target=self.check_star_targets(a), iter=b, ifs=c, is_async=0)
         self._reset(mark)
         if cut:
             return None
@@ -2964,7 +3055,7 @@ class XonshParser(Parser):
             and (b := self.for_if_clauses())
             and (self.expect("]"))
         ):
-            return ast.ListComp(elt=a, generators=b, **self.span(_lnum, _col))
+            return self.check_comprehension(ast.ListComp(elt=a, generators=b, **self.span(_lnum, _col)))
         self._reset(mark)
         if self.call_invalid_rules and (self.invalid_comprehension()):
             return None
@@ -2981,7 +3072,7 @@ class XonshParser(Parser):
             and (b := self.for_if_clauses())
             and (self.expect("}"))
         ):
-            return ast.SetComp(elt=a, generators=b, **self.span(_lnum, _col))
+            return self.check_comprehension(ast.SetComp(elt=a, generators=b, **self.span(_lnum, _col)))
         self._reset(mark)
         if self.call_invalid_rules and (self.invalid_comprehension()):
             return None
@@ -2994,11 +3085,11 @@ class XonshParser(Parser):
         _lnum, _col = self._tokenizer.peek().start
         if (
             (self.expect("("))
-            and (a := self._tmp_51())
+            and (a := self._tmp_44())
             and (b := self.for_if_clauses())
             and (self.expect(")"))
         ):
-            return ast.GeneratorExp(elt=a, generators=b, **self.span(_lnum, _col))
+            return self.check_comprehension(ast.GeneratorExp(elt=a, generators=b, **self.span(_lnum, _col)))
         self._reset(mark)
         if self.call_invalid_rules and (self.invalid_comprehension()):
             return None
@@ -3009,8 +3100,8 @@ class XonshParser(Parser):
         # bare_genexp: (assignment_expression | expression !':=') for_if_clauses
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (a := self._tmp_51()) and (b := self.for_if_clauses()):
-            return ast.GeneratorExp(elt=a, generators=b, **self.span(_lnum, _col))
+        if (a := self._tmp_44()) and (b := self.for_if_clauses()):
+            return self.check_comprehension(ast.GeneratorExp(elt=a, generators=b, **self.span(_lnum, _col)))
         self._reset(mark)
         return None
 
@@ -3024,7 +3115,9 @@ class XonshParser(Parser):
             and (b := self.for_if_clauses())
             and (self.expect("}"))
         ):
-            return ast.DictComp(key=a[0], value=a[1], generators=b, **self.span(_lnum, _col))
+            return self.check_comprehension(
+                ast.DictComp(key=a[0], value=a[1], generators=b, **self.span(_lnum, _col))
+            )
         self._reset(mark)
         if self.call_invalid_rules and (self.invalid_dict_comprehension()):
             return None
@@ -3046,7 +3139,7 @@ class XonshParser(Parser):
     def args(self) -> tuple[list, list] | None:
         # args: ','.(starred_expression | (assignment_expression | expression !':=') !'=')+ [',' kwargs] | kwargs
         mark = self._mark()
-        if (a := self.gathered(self._tmp_53, self.expect, ",")) and (b := self._tmp_54(),):
+        if (a := self.gathered(self._tmp_46, self.expect, ",")) and (b := self._tmp_47(),):
             return (
                 a + ([e for e in b if isinstance(e, ast.Starred)] if b else []),
                 [e for e in b if not isinstance(e, ast.Starred)] if b else [],
@@ -3127,7 +3220,7 @@ class XonshParser(Parser):
         if (a := self.star_target()) and (self.negative_lookahead(self.expect, ",")):
             return a
         self._reset(mark)
-        if (a := self.star_target()) and (b := self.repeated(self._tmp_55),) and (self.expect(","),):
+        if (a := self.star_target()) and (b := self.repeated(self._tmp_48),) and (self.expect(","),):
             return ast.Tuple(elts=[a] + b, ctx=Store, **self.span(_lnum, _col))
         self._reset(mark)
         return None
@@ -3143,7 +3236,7 @@ class XonshParser(Parser):
     def star_targets_tuple_seq(self) -> list | None:
         # star_targets_tuple_seq: star_target ((',' star_target))+ ','? | star_target ','
         mark = self._mark()
-        if (a := self.star_target()) and (b := self.repeated(self._tmp_55)) and (self.expect(","),):
+        if (a := self.star_target()) and (b := self.repeated(self._tmp_48)) and (self.expect(","),):
             return [a] + b
         self._reset(mark)
         if (a := self.star_target()) and (self.expect(",")):
@@ -3156,7 +3249,7 @@ class XonshParser(Parser):
         # star_target: '*' (!'*' star_target) | target_with_star_atom
         mark = self._mark()
         _lnum, _col = self._tokenizer.peek().start
-        if (self.expect("*")) and (a := self._tmp_57()):
+        if (self.expect("*")) and (a := self._tmp_50()):
             return ast.Starred(value=self.set_expr_context(a, Store), ctx=Store, **self.span(_lnum, _col))
         self._reset(mark)
         if target_with_star_atom := self.target_with_star_atom():
@@ -3205,13 +3298,13 @@ class XonshParser(Parser):
             return ast.Name(id=a.string, ctx=Store, **self.span(_lnum, _col))
         self._reset(mark)
         if (self.expect("(")) and (a := self.target_with_star_atom()) and (self.expect(")")):
-            return self.set_expr_context(a, Store)
+            return self.grouped(self.set_expr_context(a, Store), **self.span(_lnum, _col))
         self._reset(mark)
         if (self.expect("(")) and (a := self.star_targets_tuple_seq(),) and (self.expect(")")):
-            return ast.Tuple(elts=a, ctx=Store, **self.span(_lnum, _col))
+            return ast.Tuple(elts=a or [], ctx=Store, **self.span(_lnum, _col))
         self._reset(mark)
         if (self.expect("[")) and (a := self.star_targets_list_seq(),) and (self.expect("]")):
-            return ast.List(elts=a, ctx=Store, **self.span(_lnum, _col))
+            return ast.List(elts=a or [], ctx=Store, **self.span(_lnum, _col))
         self._reset(mark)
         return None
 
@@ -3226,7 +3319,7 @@ class XonshParser(Parser):
             return ast.Name(id=a.string, ctx=Store, **self.span(_lnum, _col))
         self._reset(mark)
         if (self.expect("(")) and (a := self.single_target()) and (self.expect(")")):
-            return a
+            return self.grouped(a, **self.span(_lnum, _col))
         self._reset(mark)
         return None
 
@@ -3345,13 +3438,63 @@ class XonshParser(Parser):
             return ast.Name(id=a.string, ctx=Del, **self.span(_lnum, _col))
         self._reset(mark)
         if (self.expect("(")) and (a := self.del_target()) and (self.expect(")")):
-            return self.set_expr_context(a, Del)
+            return self.grouped(self.set_expr_context(a, Del), **self.span(_lnum, _col))
         self._reset(mark)
         if (self.expect("(")) and (a := self.del_targets(),) and (self.expect(")")):
-            return ast.Tuple(elts=a, ctx=Del, **self.span(_lnum, _col))
+            return ast.Tuple(elts=a or [], ctx=Del, **self.span(_lnum, _col))
         self._reset(mark)
         if (self.expect("[")) and (a := self.del_targets(),) and (self.expect("]")):
-            return ast.List(elts=a, ctx=Del, **self.span(_lnum, _col))
+            return ast.List(elts=a or [], ctx=Del, **self.span(_lnum, _col))
+        self._reset(mark)
+        return None
+
+    def type_expressions(self) -> list | None:
+        # type_expressions: ','.expression+ ',' '*' expression ',' '**' expression | ','.expression+ ',' '*' expression | ','.expression+ ',' '**' expression | '*' expression ',' '**' expression | '*' expression | '**' expression | ','.expression+
+        mark = self._mark()
+        if (
+            (a := self.gathered(self.expression, self.expect, ","))
+            and (self.expect(","))
+            and (self.expect("*"))
+            and (b := self.expression())
+            and (self.expect(","))
+            and (self.expect("**"))
+            and (c := self.expression())
+        ):
+            return [*a, b, c]
+        self._reset(mark)
+        if (
+            (a := self.gathered(self.expression, self.expect, ","))
+            and (self.expect(","))
+            and (self.expect("*"))
+            and (b := self.expression())
+        ):
+            return [*a, b]
+        self._reset(mark)
+        if (
+            (a := self.gathered(self.expression, self.expect, ","))
+            and (self.expect(","))
+            and (self.expect("**"))
+            and (b := self.expression())
+        ):
+            return [*a, b]
+        self._reset(mark)
+        if (
+            (self.expect("*"))
+            and (a := self.expression())
+            and (self.expect(","))
+            and (self.expect("**"))
+            and (b := self.expression())
+        ):
+            return [a, b]
+        self._reset(mark)
+        if (self.expect("*")) and (a := self.expression()):
+            return [a]
+        self._reset(mark)
+        if (self.expect("**")) and (a := self.expression()):
+            return [a]
+        self._reset(mark)
+        if a := self.gathered(self.expression, self.expect, ","):
+            return a
         self._reset(mark)
         return None
 
@@ -3361,32 +3504,31 @@ class XonshParser(Parser):
         if (
             (self.token("NEWLINE"))
             and (t := self.token("TYPE_COMMENT"))
-            and (self.positive_lookahead(self._tmp_58))
+            and (self.positive_lookahead(self._tmp_51))
         ):
             return t.string
         self._reset(mark)
         if self.call_invalid_rules and (self.invalid_double_type_comments()):
             return None
         self._reset(mark)
-        if _type_comment := self.token("TYPE_COMMENT"):
-            return _type_comment
+        if t := self.token("TYPE_COMMENT"):
+            return t.string
         self._reset(mark)
         return None
 
     def invalid_arguments(self) -> None:
-        # invalid_arguments: args ',' '*' | expression for_if_clauses ',' [args | expression for_if_clauses] | NAME '=' expression for_if_clauses | [(args ',')] NAME '=' &(',' | ')') | args for_if_clauses | args ',' expression for_if_clauses | args ',' args
+        # invalid_arguments: ((','.(starred_expression | (assignment_expression | expression !':=') !'=')+ ',' kwargs) | kwargs) ',' '*' | expression for_if_clauses ',' [args | expression for_if_clauses] | NAME '=' expression for_if_clauses | [(args ',')] NAME '=' &(',' | ')') | args for_if_clauses | args ',' expression for_if_clauses | args ',' args
         mark = self._mark()
-        if (a := self.args()) and (self.expect(",")) and (self.expect("*")):
+        if (self._tmp_52()) and (self.expect(",")) and (a := self.expect("*")):
             return self.raise_syntax_error_known_location(
-                "iterable argument unpacking follows keyword argument unpacking",
-                a[1][-1] if a[1] else a[0][-1],
+                "iterable argument unpacking follows keyword argument unpacking", a
             )
         self._reset(mark)
         if (
             (a := self.expression())
             and (b := self.for_if_clauses())
             and (self.expect(","))
-            and (self._tmp_59(),)
+            and (self._tmp_53(),)
         ):
             return self.raise_syntax_error_known_range(
                 "Generator expression must be parenthesized", a, b[-1].ifs[-1] if b[-1].ifs else b[-1].iter
@@ -3398,10 +3540,10 @@ class XonshParser(Parser):
             )
         self._reset(mark)
         if (
-            (self._tmp_60(),)
+            (self._tmp_54(),)
             and (a := self.name())
             and (b := self.expect("="))
-            and (self.positive_lookahead(self._tmp_61))
+            and (self.positive_lookahead(self._tmp_55))
         ):
             return self.raise_syntax_error_known_range("expected argument value expression", a, b)
         self._reset(mark)
@@ -3433,7 +3575,7 @@ class XonshParser(Parser):
     def invalid_kwarg(self) -> None:
         # invalid_kwarg: ('True' | 'False' | 'None') '=' | NAME '=' expression for_if_clauses | !(NAME '=') expression '=' | '**' expression '=' expression
         mark = self._mark()
-        if (a := self._tmp_62()) and (b := self.expect("=")):
+        if (a := self._tmp_56()) and (b := self.expect("=")):
             return self.raise_syntax_error_known_range(f"cannot assign to {a.string}", a, b)
         self._reset(mark)
         if (a := self.name()) and (b := self.expect("=")) and (self.expression()) and (self.for_if_clauses()):
@@ -3441,7 +3583,7 @@ class XonshParser(Parser):
                 "invalid syntax. Maybe you meant '==' or ':=' instead of '='?", a, b
             )
         self._reset(mark)
-        if (self.negative_lookahead(self._tmp_63)) and (a := self.expression()) and (b := self.expect("=")):
+        if (self.negative_lookahead(self._tmp_57)) and (a := self.expression()) and (b := self.expect("=")):
             return self.raise_syntax_error_known_range(
                 'expression cannot contain assignment, perhaps you meant "=="?', a, b
             )
@@ -3502,16 +3644,30 @@ class XonshParser(Parser):
         return None
 
     def invalid_expression(self) -> None:
-        # invalid_expression: !(NAME STRING | SOFT_KEYWORD) disjunction expression_without_invalid | disjunction 'if' disjunction !('else' | ':') | 'lambda' lambda_params? ':' &(FSTRING_MIDDLE | fstring_replacement_field)
+        # invalid_expression: ".".atom+ '??' disjunction | invalid_attribute_number | !(NAME STRING | SOFT_KEYWORD) disjunction expression_without_invalid | disjunction 'if' disjunction !('else' | ':') | 'lambda' lambda_params? ':' &(FSTRING_MIDDLE | fstring_replacement_field)
         mark = self._mark()
         if (
-            (self.negative_lookahead(self._tmp_64))
+            (a := self.gathered(self.atom, self.expect, "."))
+            and (self.expect("??"))
+            and (b := self.disjunction())
+        ):
+            return self.raise_syntax_error_known_range(
+                "the null-coalescing operator '??' is not supported yet", a[0], b
+            )
+        self._reset(mark)
+        if self.call_invalid_rules and (self.invalid_attribute_number()):
+            return None
+        self._reset(mark)
+        if (
+            (self.negative_lookahead(self._tmp_58))
             and (a := self.disjunction())
             and (b := self.expression_without_invalid())
         ):
             return (
                 self.raise_syntax_error_known_range("invalid syntax. Perhaps you forgot a comma?", a, b)
-                if not isinstance(a, ast.Name) or a.id not in ("print", "exec")
+                if (
+                    self._tokenizer.bracket_depth(-1) and (not isinstance(a, ast.Name) or a.id not in ("print", "exec"))
+                )
                 else None
             )
         self._reset(mark)
@@ -3519,7 +3675,7 @@ class XonshParser(Parser):
             (a := self.disjunction())
             and (self.expect("if"))
             and (b := self.disjunction())
-            and (self.negative_lookahead(self._tmp_65))
+            and (self.negative_lookahead(self._tmp_59))
         ):
             return self.raise_syntax_error_known_range("expected 'else' after 'if' expression", a, b)
         self._reset(mark)
@@ -3527,7 +3683,7 @@ class XonshParser(Parser):
             (a := self.expect("lambda"))
             and (self.lambda_params(),)
             and (b := self.expect(":"))
-            and (self.positive_lookahead(self._tmp_66))
+            and (self.positive_lookahead(self._tmp_60))
         ):
             return self.raise_syntax_error_known_range(
                 "f-string: lambda expressions are not allowed without parentheses", a, b
@@ -3535,6 +3691,42 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
+    def invalid_inversion(self) -> None:
+        # invalid_inversion: 'not' !inversion
+        mark = self._mark()
+        if (self.expect("not")) and (self.negative_lookahead(self.inversion)):
+            return self.raise_syntax_error_known_location(
+                "expected expression after 'not'", self._tokenizer.peek()
+            )
+        self._reset(mark)
+        return None
+
+    def invalid_factor(self) -> None:
+        # invalid_factor: ('+' | '-' | '~') !factor
+        mark = self._mark()
+        if (a := self._tmp_61()) and (self.negative_lookahead(self.factor)):
+            return self.raise_syntax_error_known_location(
+                f"expected expression after '{a.string}'", self._tokenizer.peek()
+            )
+        self._reset(mark)
+        return None
+
+    def invalid_attribute_number(self) -> None:
+        # invalid_attribute_number: primary NUMBER
+        mark = self._mark()
+        if (self.primary()) and (b := self.token("NUMBER")):
+            return self.check_attribute_number(b)
+        self._reset(mark)
+        return None
+
+    def invalid_path_literal(self) -> None:
+        # invalid_path_literal: NAME STRING
+        mark = self._mark()
+        if (a := self.name()) and (b := self.token("STRING")):
+            return self.check_bytes_path_literal(a, b)
+        self._reset(mark)
+        return None
+
     def invalid_named_expression(self) -> None:
         # invalid_named_expression: expression ':=' expression | NAME '=' bitwise_or !('=' | ':=') | !(plist | ptuple | genexp | 'True' | 'None' | 'False') bitwise_or '=' bitwise_or !('=' | ':=')
         mark = self._mark()
@@ -3547,29 +3739,21 @@ class XonshParser(Parser):
             (a := self.name())
             and (self.expect("="))
             and (b := self.bitwise_or())
-            and (self.negative_lookahead(self._tmp_67))
+            and (self.negative_lookahead(self._tmp_62))
         ):
-            return (
-                None
-                if self.in_recursive_rule
-                else self.raise_syntax_error_known_range(
-                    "invalid syntax. Maybe you meant '==' or ':=' instead of '='?", a, b
-                )
+            return self.raise_syntax_error_known_range(
+                "invalid syntax. Maybe you meant '==' or ':=' instead of '='?", a, b
             )
         self._reset(mark)
         if (
-            (self.negative_lookahead(self._tmp_68))
+            (self.negative_lookahead(self._tmp_63))
             and (a := self.bitwise_or())
             and (self.expect("="))
             and (self.bitwise_or())
-            and (self.negative_lookahead(self._tmp_67))
+            and (self.negative_lookahead(self._tmp_62))
         ):
-            return (
-                None
-                if self.in_recursive_rule
-                else self.raise_syntax_error_known_location(
-                    f"cannot assign to {self.get_expr_name(a)} here. Maybe you meant '==' instead of '='?", a
-                )
+            return self.raise_syntax_error_known_location(
+                f"cannot assign to {self.get_expr_name(a)} here. Maybe you meant '==' instead of '='?", a
             )
         self._reset(mark)
         return None
@@ -3601,10 +3785,10 @@ class XonshParser(Parser):
         if (a := self.expression()) and (self.expect(":")) and (self.expression()):
             return self.raise_syntax_error_known_location("illegal target for annotation", a)
         self._reset(mark)
-        if (self.repeated(self._tmp_70),) and (a := self.star_expressions()) and (self.expect("=")):
+        if (self.repeated(self._tmp_65),) and (a := self.star_expressions()) and (self.expect("=")):
             return self.raise_syntax_error_invalid_target(Target.STAR_TARGETS, a)
         self._reset(mark)
-        if (self.repeated(self._tmp_70),) and (a := self.yield_expr()) and (self.expect("=")):
+        if (self.repeated(self._tmp_65),) and (a := self.yield_expr()) and (self.expect("=")):
             return self.raise_syntax_error_known_location("assignment to yield expression not possible", a)
         self._reset(mark)
         if (a := self.star_expressions()) and (self.augassign()) and (self.annotated_rhs()):
@@ -3652,13 +3836,13 @@ class XonshParser(Parser):
     def invalid_comprehension(self) -> None:
         # invalid_comprehension: ('[' | '(' | '{') starred_expression for_if_clauses | ('[' | '{') star_named_expression ',' star_named_expressions for_if_clauses | ('[' | '{') star_named_expression ',' for_if_clauses
         mark = self._mark()
-        if (self._tmp_72()) and (a := self.starred_expression()) and (self.for_if_clauses()):
+        if (self._tmp_67()) and (a := self.starred_expression()) and (self.for_if_clauses()):
             return self.raise_syntax_error_known_location(
                 "iterable unpacking cannot be used in comprehension", a
             )
         self._reset(mark)
         if (
-            (self._tmp_73())
+            (self._tmp_68())
             and (a := self.star_named_expression())
             and (self.expect(","))
             and (b := self.star_named_expressions())
@@ -3669,7 +3853,7 @@ class XonshParser(Parser):
             )
         self._reset(mark)
         if (
-            (self._tmp_73())
+            (self._tmp_68())
             and (a := self.star_named_expression())
             and (b := self.expect(","))
             and (self.for_if_clauses())
@@ -3702,7 +3886,7 @@ class XonshParser(Parser):
         if (a := self.expect("/")) and (self.expect(",")):
             return self.raise_syntax_error_known_location("at least one argument must precede /", a)
         self._reset(mark)
-        if (self._tmp_75()) and (self.repeated(self.param_maybe_default),) and (a := self.expect("/")):
+        if (self._tmp_70()) and (self.repeated(self.param_maybe_default),) and (a := self.expect("/")):
             return self.raise_syntax_error_known_location("/ may appear only once", a)
         self._reset(mark)
         if (
@@ -3726,10 +3910,10 @@ class XonshParser(Parser):
             return self.raise_syntax_error_known_range("Function parameters cannot be parenthesized", a, b)
         self._reset(mark)
         if (
-            (self._tmp_75(),)
+            (self._tmp_70(),)
             and (self.repeated(self.param_maybe_default),)
             and (self.expect("*"))
-            and (self._tmp_77())
+            and (self._tmp_72())
             and (self.repeated(self.param_maybe_default),)
             and (a := self.expect("/"))
         ):
@@ -3743,7 +3927,7 @@ class XonshParser(Parser):
     def invalid_default(self) -> Any | None:
         # invalid_default: '=' &(')' | ',')
         mark = self._mark()
-        if (a := self.expect("=")) and (self.positive_lookahead(self._tmp_78)):
+        if (a := self.expect("=")) and (self.positive_lookahead(self._tmp_73)):
             return self.raise_syntax_error_known_location("expected default value expression", a)
         self._reset(mark)
         return None
@@ -3751,7 +3935,7 @@ class XonshParser(Parser):
     def invalid_star_etc(self) -> Any | None:
         # invalid_star_etc: '*' (')' | ',' (')' | '**')) | '*' ',' TYPE_COMMENT | '*' param '=' | '*' (param_no_default | ',') param_maybe_default* '*' (param_no_default | ',')
         mark = self._mark()
-        if (a := self.expect("*")) and (self._tmp_79()):
+        if (a := self.expect("*")) and (self._tmp_74()):
             return self.raise_syntax_error_known_location("named arguments must follow bare *", a)
         self._reset(mark)
         if (self.expect("*")) and (self.expect(",")) and (self.token("TYPE_COMMENT")):
@@ -3764,10 +3948,10 @@ class XonshParser(Parser):
         self._reset(mark)
         if (
             (self.expect("*"))
-            and (self._tmp_80())
+            and (self._tmp_75())
             and (self.repeated(self.param_maybe_default),)
             and (a := self.expect("*"))
-            and (self._tmp_80())
+            and (self._tmp_75())
         ):
             return self.raise_syntax_error_known_location("* argument may appear only once", a)
         self._reset(mark)
@@ -3782,7 +3966,7 @@ class XonshParser(Parser):
         if (self.expect("**")) and (self.param()) and (self.expect(",")) and (a := self.param()):
             return self.raise_syntax_error_known_location("arguments cannot follow var-keyword argument", a)
         self._reset(mark)
-        if (self.expect("**")) and (self.param()) and (self.expect(",")) and (a := self._tmp_82()):
+        if (self.expect("**")) and (self.param()) and (self.expect(",")) and (a := self._tmp_77()):
             return self.raise_syntax_error_known_location("arguments cannot follow var-keyword argument", a)
         self._reset(mark)
         return None
@@ -3804,7 +3988,7 @@ class XonshParser(Parser):
         if (a := self.expect("/")) and (self.expect(",")):
             return self.raise_syntax_error_known_location("at least one argument must precede /", a)
         self._reset(mark)
-        if (self._tmp_83()) and (self.repeated(self.lambda_param_maybe_default),) and (a := self.expect("/")):
+        if (self._tmp_78()) and (self.repeated(self.lambda_param_maybe_default),) and (a := self.expect("/")):
             return self.raise_syntax_error_known_location("/ may appear only once", a)
         self._reset(mark)
         if (
@@ -3830,10 +4014,10 @@ class XonshParser(Parser):
             )
         self._reset(mark)
         if (
-            (self._tmp_83(),)
+            (self._tmp_78(),)
             and (self.repeated(self.lambda_param_maybe_default),)
             and (self.expect("*"))
-            and (self._tmp_85())
+            and (self._tmp_80())
             and (self.repeated(self.lambda_param_maybe_default),)
             and (a := self.expect("/"))
         ):
@@ -3862,7 +4046,7 @@ class XonshParser(Parser):
     def invalid_lambda_star_etc(self) -> None:
         # invalid_lambda_star_etc: '*' (':' | ',' (':' | '**')) | '*' lambda_param '=' | '*' (lambda_param_no_default | ',') lambda_param_maybe_default* '*' (lambda_param_no_default | ',')
         mark = self._mark()
-        if (self.expect("*")) and (self._tmp_86()):
+        if (self.expect("*")) and (self._tmp_81()):
             return self.raise_syntax_error("named arguments must follow bare *")
         self._reset(mark)
         if (self.expect("*")) and (self.lambda_param()) and (a := self.expect("=")):
@@ -3872,10 +4056,10 @@ class XonshParser(Parser):
         self._reset(mark)
         if (
             (self.expect("*"))
-            and (self._tmp_87())
+            and (self._tmp_82())
             and (self.repeated(self.lambda_param_maybe_default),)
             and (a := self.expect("*"))
-            and (self._tmp_87())
+            and (self._tmp_82())
         ):
             return self.raise_syntax_error_known_location("* argument may appear only once", a)
         self._reset(mark)
@@ -3895,7 +4079,7 @@ class XonshParser(Parser):
         ):
             return self.raise_syntax_error_known_location("arguments cannot follow var-keyword argument", a)
         self._reset(mark)
-        if (self.expect("**")) and (self.lambda_param()) and (self.expect(",")) and (a := self._tmp_82()):
+        if (self.expect("**")) and (self.lambda_param()) and (self.expect(",")) and (a := self._tmp_77()):
             return self.raise_syntax_error_known_location("arguments cannot follow var-keyword argument", a)
         self._reset(mark)
         return None
@@ -3921,7 +4105,7 @@ class XonshParser(Parser):
             (self.expression())
             and (self.expect("as"))
             and (a := self.expression())
-            and (self.positive_lookahead(self._tmp_22))
+            and (self.positive_lookahead(self._tmp_15))
         ):
             return self.raise_syntax_error_invalid_target(Target.STAR_TARGETS, a)
         self._reset(mark)
@@ -3975,7 +4159,7 @@ class XonshParser(Parser):
         if (
             (self.expect("async"),)
             and (self.expect("with"))
-            and (self.gathered(self._tmp_91, self.expect, ","))
+            and (self.gathered(self._tmp_86, self.expect, ","))
             and (self.expect_forced(self.expect(":"), "':'"))
         ):
             return None
@@ -3984,7 +4168,7 @@ class XonshParser(Parser):
             (self.expect("async"),)
             and (self.expect("with"))
             and (self.expect("("))
-            and (self.gathered(self._tmp_92, self.expect, ","))
+            and (self.gathered(self._tmp_87, self.expect, ","))
             and (self.expect(","),)
             and (self.expect(")"))
             and (self.expect_forced(self.expect(":"), "':'"))
@@ -3999,7 +4183,7 @@ class XonshParser(Parser):
         if (
             (self.expect("async"),)
             and (a := self.expect("with"))
-            and (self.gathered(self._tmp_91, self.expect, ","))
+            and (self.gathered(self._tmp_86, self.expect, ","))
             and (self.expect(":"))
             and (self.token("NEWLINE"))
             and (self.negative_lookahead(self.token, "INDENT"))
@@ -4012,7 +4196,7 @@ class XonshParser(Parser):
             (self.expect("async"),)
             and (a := self.expect("with"))
             and (self.expect("("))
-            and (self.gathered(self._tmp_92, self.expect, ","))
+            and (self.gathered(self._tmp_87, self.expect, ","))
             and (self.expect(","),)
             and (self.expect(")"))
             and (self.expect(":"))
@@ -4042,7 +4226,7 @@ class XonshParser(Parser):
             (self.expect("try"))
             and (self.expect(":"))
             and (self.block())
-            and (self.negative_lookahead(self._tmp_95))
+            and (self.negative_lookahead(self._tmp_90))
         ):
             return self.raise_syntax_error("expected 'except' or 'finally' block")
         self._reset(mark)
@@ -4054,7 +4238,7 @@ class XonshParser(Parser):
             and (a := self.expect("except"))
             and (b := self.expect("*"))
             and (self.expression())
-            and (self._tmp_96(),)
+            and (self._tmp_91(),)
             and (self.expect(":"))
         ):
             return self.raise_syntax_error_known_range(
@@ -4067,7 +4251,7 @@ class XonshParser(Parser):
             and (self.repeated(self.block),)
             and (self.repeated(self.except_star_block))
             and (a := self.expect("except"))
-            and (self._tmp_97(),)
+            and (self._tmp_92(),)
             and (self.expect(":"))
         ):
             return self.raise_syntax_error_known_location(
@@ -4085,7 +4269,7 @@ class XonshParser(Parser):
             and (a := self.expression())
             and (self.expect(","))
             and (self.expressions())
-            and (self._tmp_96(),)
+            and (self._tmp_91(),)
             and (self.expect(":"))
         ):
             return self.raise_syntax_error_starting_from("multiple exception types must be parenthesized", a)
@@ -4094,7 +4278,7 @@ class XonshParser(Parser):
             (self.expect("except"))
             and (self.expect("*"),)
             and (self.expression())
-            and (self._tmp_96(),)
+            and (self._tmp_91(),)
             and (self.token("NEWLINE"))
         ):
             return self.raise_syntax_error("expected ':'")
@@ -4102,7 +4286,7 @@ class XonshParser(Parser):
         if (self.expect("except")) and (self.expect("*"),) and (self.token("NEWLINE")):
             return self.raise_syntax_error("expected ':'")
         self._reset(mark)
-        if (self.expect("except")) and (self.expect("*")) and (self._tmp_100()):
+        if (self.expect("except")) and (self.expect("*")) and (self._tmp_95()):
             return self.raise_syntax_error("expected one or more exception types")
         self._reset(mark)
         return None
@@ -4128,7 +4312,7 @@ class XonshParser(Parser):
         if (
             (a := self.expect("except"))
             and (self.expression())
-            and (self._tmp_96(),)
+            and (self._tmp_91(),)
             and (self.expect(":"))
             and (self.token("NEWLINE"))
             and (self.negative_lookahead(self.token, "INDENT"))
@@ -4156,7 +4340,7 @@ class XonshParser(Parser):
             (a := self.expect("except"))
             and (self.expect("*"))
             and (self.expression())
-            and (self._tmp_96(),)
+            and (self._tmp_91(),)
             and (self.expect(":"))
             and (self.token("NEWLINE"))
             and (self.negative_lookahead(self.token, "INDENT"))
@@ -4187,14 +4371,9 @@ class XonshParser(Parser):
         return None
 
     def invalid_case_block(self) -> None:
-        # invalid_case_block: "case" patterns guard? !':' | "case" patterns guard? ':' NEWLINE !INDENT
+        # invalid_case_block: "case" patterns guard? NEWLINE | "case" patterns guard? ':' NEWLINE !INDENT
         mark = self._mark()
-        if (
-            (self.expect("case"))
-            and (self.patterns())
-            and (self.guard(),)
-            and (self.negative_lookahead(self.expect, ":"))
-        ):
+        if (self.expect("case")) and (self.patterns()) and (self.guard(),) and (self.token("NEWLINE")):
             return self.raise_syntax_error("expected ':'")
         self._reset(mark)
         if (
@@ -4246,7 +4425,7 @@ class XonshParser(Parser):
         # invalid_class_argument_pattern: [positional_patterns ','] keyword_patterns ',' positional_patterns
         mark = self._mark()
         if (
-            (self._tmp_103(),)
+            (self._tmp_98(),)
             and (self.keyword_patterns())
             and (self.expect(","))
             and (a := self.positional_patterns())
@@ -4367,7 +4546,7 @@ class XonshParser(Parser):
             and (self.expect("("))
             and (self.params(),)
             and (self.expect(")"))
-            and (self._tmp_104(),)
+            and (self._tmp_99(),)
             and (self.expect(":"))
             and (self.token("NEWLINE"))
             and (self.negative_lookahead(self.token, "INDENT"))
@@ -4385,7 +4564,7 @@ class XonshParser(Parser):
             (self.expect("class"))
             and (self.name())
             and (self.type_params(),)
-            and (self._tmp_105(),)
+            and (self._tmp_100(),)
             and (self.token("NEWLINE"))
         ):
             return self.raise_syntax_error("expected ':'")
@@ -4394,7 +4573,7 @@ class XonshParser(Parser):
             (a := self.expect("class"))
             and (self.name())
             and (self.type_params(),)
-            and (self._tmp_105(),)
+            and (self._tmp_100(),)
             and (self.expect(":"))
             and (self.token("NEWLINE"))
             and (self.negative_lookahead(self.token, "INDENT"))
@@ -4421,7 +4600,7 @@ class XonshParser(Parser):
                 "cannot use a starred expression in a dictionary value", a
             )
         self._reset(mark)
-        if (self.expression()) and (a := self.expect(":")) and (self.positive_lookahead(self._tmp_107)):
+        if (self.expression()) and (a := self.expect(":")) and (self.positive_lookahead(self._tmp_102)):
             return self.raise_syntax_error_known_location(
                 "expression expected after dictionary key and ':'", a
             )
@@ -4443,7 +4622,7 @@ class XonshParser(Parser):
                 "cannot use a starred expression in a dictionary value", a
             )
         self._reset(mark)
-        if (self.expression()) and (a := self.expect(":")) and (self.positive_lookahead(self._tmp_107)):
+        if (self.expression()) and (a := self.expect(":")) and (self.positive_lookahead(self._tmp_102)):
             return self.raise_syntax_error_known_location(
                 "expression expected after dictionary key and ':'", a
             )
@@ -4486,14 +4665,14 @@ class XonshParser(Parser):
         if (self.expect("{")) and (self.negative_lookahead(self.annotated_rhs)):
             return self.raise_syntax_error_on_next_token("f-string: expecting a valid expression after '{'")
         self._reset(mark)
-        if (self.expect("{")) and (self.annotated_rhs()) and (self.negative_lookahead(self._tmp_109)):
+        if (self.expect("{")) and (self.annotated_rhs()) and (self.negative_lookahead(self._tmp_104)):
             return self.raise_syntax_error_on_next_token("f-string: expecting '=', or '!', or ':', or '}'")
         self._reset(mark)
         if (
             (self.expect("{"))
             and (self.annotated_rhs())
             and (self.expect("="))
-            and (self.negative_lookahead(self._tmp_110))
+            and (self.negative_lookahead(self._tmp_105))
         ):
             return self.raise_syntax_error_on_next_token("f-string: expecting '!', or ':', or '}'")
         self._reset(mark)
@@ -4510,8 +4689,8 @@ class XonshParser(Parser):
             (self.expect("{"))
             and (self.annotated_rhs())
             and (self.expect("="),)
-            and (self._tmp_111(),)
-            and (self.negative_lookahead(self._tmp_112))
+            and (self._tmp_106(),)
+            and (self.negative_lookahead(self._tmp_107))
         ):
             return self.raise_syntax_error_on_next_token("f-string: expecting ':' or '}'")
         self._reset(mark)
@@ -4519,7 +4698,7 @@ class XonshParser(Parser):
             (self.expect("{"))
             and (self.annotated_rhs())
             and (self.expect("="),)
-            and (self._tmp_111(),)
+            and (self._tmp_106(),)
             and (self.expect(":"))
             and (self.repeated(self.fstring_format_spec),)
             and (self.negative_lookahead(self.expect, "}"))
@@ -4530,7 +4709,7 @@ class XonshParser(Parser):
             (self.expect("{"))
             and (self.annotated_rhs())
             and (self.expect("="),)
-            and (self._tmp_111(),)
+            and (self._tmp_106(),)
             and (self.negative_lookahead(self.expect, "}"))
         ):
             return self.raise_syntax_error_on_next_token("f-string: expecting '}'")
@@ -4540,7 +4719,7 @@ class XonshParser(Parser):
     def invalid_conversion_character(self) -> Any | None:
         # invalid_conversion_character: '!' &(':' | '}') | '!' !NAME
         mark = self._mark()
-        if (self.expect("!")) and (self.positive_lookahead(self._tmp_112)):
+        if (self.expect("!")) and (self.positive_lookahead(self._tmp_107)):
             return self.raise_syntax_error_on_next_token("f-string: missing conversion character")
         self._reset(mark)
         if (self.expect("!")) and (self.negative_lookahead(self.name)):
@@ -4549,202 +4728,151 @@ class XonshParser(Parser):
         return None
 
     def _tmp_1(self) -> Any | None:
-        # _tmp_1: 'import' | 'from'
-        return self.seq_alts(
-            (self.expect, "import"),
-            (self.expect, "from"),
-        )
-
-    def _tmp_2(self) -> Any | None:
-        # _tmp_2: 'def' | '@' | 'async'
-        return self.seq_alts(
-            (self.expect, "def"),
-            (self.expect, "@"),
-            (self.expect, "async"),
-        )
-
-    def _tmp_3(self) -> Any | None:
-        # _tmp_3: 'class' | '@'
-        return self.seq_alts(
-            (self.expect, "class"),
-            (self.expect, "@"),
-        )
-
-    def _tmp_4(self) -> Any | None:
-        # _tmp_4: 'with' | 'async'
-        return self.seq_alts(
-            (self.expect, "with"),
-            (self.expect, "async"),
-        )
-
-    def _tmp_5(self) -> Any | None:
-        # _tmp_5: 'for' | 'async'
-        return self.seq_alts(
-            (self.expect, "for"),
-            (self.expect, "async"),
-        )
-
-    def _tmp_6(self) -> Any | None:
-        # _tmp_6: '=' annotated_rhs
+        # _tmp_1: '=' annotated_rhs
         mark = self._mark()
         if (self.expect("=")) and (d := self.annotated_rhs()):
             return d
         self._reset(mark)
         return None
 
-    def _tmp_7(self) -> Any | None:
-        # _tmp_7: '(' single_target ')' | single_subscript_attribute_target
+    def _tmp_2(self) -> Any | None:
+        # _tmp_2: '(' single_target ')' | single_subscript_attribute_target
         mark = self._mark()
+        _lnum, _col = self._tokenizer.peek().start
         if (self.expect("(")) and (b := self.single_target()) and (self.expect(")")):
-            return b
+            return self.grouped(b, **self.span(_lnum, _col))
         self._reset(mark)
         if single_subscript_attribute_target := self.single_subscript_attribute_target():
             return single_subscript_attribute_target
         self._reset(mark)
         return None
 
-    def _tmp_9(self) -> Any | None:
-        # _tmp_9: star_targets '='
+    def _tmp_4(self) -> Any | None:
+        # _tmp_4: star_targets '='
         mark = self._mark()
         if (z := self.star_targets()) and (self.expect("=")):
             return z
         self._reset(mark)
         return None
 
-    def _tmp_10(self) -> Any | None:
-        # _tmp_10: 'from' expression
+    def _tmp_5(self) -> Any | None:
+        # _tmp_5: 'from' expression
         mark = self._mark()
         if (self.expect("from")) and (z := self.expression()):
             return z
         self._reset(mark)
         return None
 
-    def _tmp_11(self) -> Any | None:
-        # _tmp_11: ';' | NEWLINE
+    def _tmp_6(self) -> Any | None:
+        # _tmp_6: ';' | NEWLINE
         return self.seq_alts(
             (self.expect, ";"),
             (self.token, "NEWLINE"),
         )
 
-    def _tmp_12(self) -> Any | None:
-        # _tmp_12: ',' expression
+    def _tmp_7(self) -> Any | None:
+        # _tmp_7: ',' expression
         mark = self._mark()
         if (self.expect(",")) and (z := self.expression()):
             return z
         self._reset(mark)
         return None
 
-    def _tmp_13(self) -> Any | None:
-        # _tmp_13: '.' | '...'
+    def _tmp_8(self) -> Any | None:
+        # _tmp_8: '.' | '...'
         return self.seq_alts(
             (self.expect, "."),
             (self.expect, "..."),
         )
 
-    def _tmp_15(self) -> Any | None:
-        # _tmp_15: 'as' NAME
+    def _tmp_10(self) -> Any | None:
+        # _tmp_10: 'as' NAME
         mark = self._mark()
         if (self.expect("as")) and (z := self.name()):
             return z.string
         self._reset(mark)
         return None
 
-    def _tmp_17(self) -> Any | None:
-        # _tmp_17: '@' dec_maybe_call NEWLINE
-        mark = self._mark()
-        if (self.expect("@")) and (f := self.dec_maybe_call()) and (self.token("NEWLINE")):
-            return f
-        self._reset(mark)
-        return None
-
-    def _tmp_18(self) -> Any | None:
-        # _tmp_18: '@' named_expression NEWLINE
-        mark = self._mark()
-        if (self.expect("@")) and (f := self.named_expression()) and (self.token("NEWLINE")):
-            return f
-        self._reset(mark)
-        return None
-
-    def _tmp_19(self) -> Any | None:
-        # _tmp_19: '(' arguments? ')'
+    def _tmp_12(self) -> Any | None:
+        # _tmp_12: '(' arguments? ')'
         mark = self._mark()
         if (self.expect("(")) and (z := self.arguments(),) and (self.expect(")")):
             return z
         self._reset(mark)
         return None
 
-    def _tmp_20(self) -> Any | None:
-        # _tmp_20: '->' expression
+    def _tmp_13(self) -> Any | None:
+        # _tmp_13: '->' expression
         mark = self._mark()
         if (self.expect("->")) and (z := self.expression()):
             return z
         self._reset(mark)
         return None
 
-    def _tmp_22(self) -> Any | None:
-        # _tmp_22: ',' | ')' | ':'
+    def _tmp_15(self) -> Any | None:
+        # _tmp_15: ',' | ')' | ':'
         return self.seq_alts(
             (self.expect, ","),
             (self.expect, ")"),
             (self.expect, ":"),
         )
 
-    def _tmp_25(self) -> Any | None:
-        # _tmp_25: '+' | '-'
+    def _tmp_18(self) -> Any | None:
+        # _tmp_18: '+' | '-'
         return self.seq_alts(
             (self.expect, "+"),
             (self.expect, "-"),
         )
 
-    def _tmp_27(self) -> Any | None:
-        # _tmp_27: '.' | '(' | '='
+    def _tmp_20(self) -> Any | None:
+        # _tmp_20: '.' | '(' | '='
         return self.seq_alts(
             (self.expect, "."),
             (self.expect, "("),
             (self.expect, "="),
         )
 
-    def _tmp_29(self) -> Any | None:
-        # _tmp_29: literal_expr | attr
+    def _tmp_22(self) -> Any | None:
+        # _tmp_22: literal_expr | attr
         return self.seq_alts(
             self.literal_expr,
             self.attr,
         )
 
-    def _tmp_30(self) -> Any | None:
-        # _tmp_30: ',' expression
+    def _tmp_23(self) -> Any | None:
+        # _tmp_23: ',' expression
         mark = self._mark()
         if (self.expect(",")) and (c := self.expression()):
             return c
         self._reset(mark)
         return None
 
-    def _tmp_31(self) -> Any | None:
-        # _tmp_31: ',' star_expression
+    def _tmp_24(self) -> Any | None:
+        # _tmp_24: ',' star_expression
         mark = self._mark()
         if (self.expect(",")) and (c := self.star_expression()):
             return c
         self._reset(mark)
         return None
 
-    def _tmp_32(self) -> Any | None:
-        # _tmp_32: ('or' | '||') conjunction
+    def _tmp_25(self) -> Any | None:
+        # _tmp_25: or_op conjunction
         mark = self._mark()
-        if (self._tmp_116()) and (c := self.conjunction()):
+        if (self.or_op()) and (c := self.conjunction()):
             return c
         self._reset(mark)
         return None
 
-    def _tmp_33(self) -> Any | None:
-        # _tmp_33: ('and' | '&&') inversion
+    def _tmp_26(self) -> Any | None:
+        # _tmp_26: and_op inversion
         mark = self._mark()
-        if (self._tmp_117()) and (c := self.inversion()):
+        if (self.and_op()) and (c := self.inversion()):
             return c
         self._reset(mark)
         return None
 
-    def _tmp_34(self) -> Any | None:
-        # _tmp_34: '$(' | '$[' | '![' | '!('
+    def _tmp_27(self) -> Any | None:
+        # _tmp_27: '$(' | '$[' | '![' | '!('
         return self.seq_alts(
             (self.expect, "$("),
             (self.expect, "$["),
@@ -4752,82 +4880,82 @@ class XonshParser(Parser):
             (self.expect, "!("),
         )
 
-    def _tmp_35(self) -> Any | None:
-        # _tmp_35: '??' | '?'
+    def _tmp_28(self) -> Any | None:
+        # _tmp_28: '??' | '?'
         return self.seq_alts(
             (self.expect, "??"),
             (self.expect, "?"),
         )
 
-    def _tmp_36(self) -> Any | None:
-        # _tmp_36: bare_genexp | expressions
+    def _tmp_29(self) -> Any | None:
+        # _tmp_29: bare_genexp | expressions
         return self.seq_alts(
             self.bare_genexp,
             self.expressions,
         )
 
-    def _tmp_37(self) -> Any | None:
-        # _tmp_37: cmd_group | any_cmd
+    def _tmp_30(self) -> Any | None:
+        # _tmp_30: cmd_group | any_cmd
         return self.seq_alts(
             self.cmd_group,
             self.any_cmd,
         )
 
-    def _tmp_38(self) -> Any | None:
-        # _tmp_38: '(' | '!(' | '$('
+    def _tmp_31(self) -> Any | None:
+        # _tmp_31: '(' | '!(' | '$('
         return self.seq_alts(
             (self.expect, "("),
             (self.expect, "!("),
             (self.expect, "$("),
         )
 
-    def _tmp_39(self) -> Any | None:
-        # _tmp_39: '[' | '![' | '$['
+    def _tmp_32(self) -> Any | None:
+        # _tmp_32: '[' | '![' | '$['
         return self.seq_alts(
             (self.expect, "["),
             (self.expect, "!["),
             (self.expect, "$["),
         )
 
-    def _tmp_40(self) -> Any | None:
-        # _tmp_40: slice | starred_expression
+    def _tmp_33(self) -> Any | None:
+        # _tmp_33: slice | starred_expression
         return self.seq_alts(
             self.slice,
             self.starred_expression,
         )
 
-    def _tmp_41(self) -> Any | None:
-        # _tmp_41: ':' expression?
+    def _tmp_34(self) -> Any | None:
+        # _tmp_34: ':' expression?
         mark = self._mark()
         if (self.expect(":")) and (d := self.expression(),):
             return d
         self._reset(mark)
         return None
 
-    def _tmp_42(self) -> Any | None:
-        # _tmp_42: STRING | FSTRING_START
+    def _tmp_35(self) -> Any | None:
+        # _tmp_35: STRING | FSTRING_START
         return self.seq_alts(
             (self.token, "STRING"),
             (self.token, "FSTRING_START"),
         )
 
-    def _tmp_43(self) -> Any | None:
-        # _tmp_43: ptuple | group | genexp
+    def _tmp_36(self) -> Any | None:
+        # _tmp_36: ptuple | group | genexp
         return self.seq_alts(
             self.ptuple,
             self.group,
             self.genexp,
         )
 
-    def _tmp_44(self) -> Any | None:
-        # _tmp_44: plist | listcomp
+    def _tmp_37(self) -> Any | None:
+        # _tmp_37: plist | listcomp
         return self.seq_alts(
             self.plist,
             self.listcomp,
         )
 
-    def _tmp_45(self) -> Any | None:
-        # _tmp_45: dict | set | dictcomp | setcomp
+    def _tmp_38(self) -> Any | None:
+        # _tmp_38: dict | set | dictcomp | setcomp
         return self.seq_alts(
             self.dict,
             self.set,
@@ -4835,22 +4963,22 @@ class XonshParser(Parser):
             self.setcomp,
         )
 
-    def _tmp_46(self) -> Any | None:
-        # _tmp_46: yield_expr | named_expression
+    def _tmp_39(self) -> Any | None:
+        # _tmp_39: yield_expr | named_expression
         return self.seq_alts(
             self.yield_expr,
             self.named_expression,
         )
 
-    def _tmp_47(self) -> Any | None:
-        # _tmp_47: fstring | STRING
+    def _tmp_40(self) -> Any | None:
+        # _tmp_40: fstring | STRING
         return self.seq_alts(
             self.fstring,
             (self.token, "STRING"),
         )
 
-    def _tmp_48(self) -> Any | None:
-        # _tmp_48: star_named_expression ',' star_named_expressions?
+    def _tmp_41(self) -> Any | None:
+        # _tmp_41: star_named_expression ',' star_named_expressions?
         mark = self._mark()
         if (
             (y := self.star_named_expression())
@@ -4861,16 +4989,16 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
-    def _tmp_49(self) -> Any | None:
-        # _tmp_49: 'if' disjunction
+    def _tmp_42(self) -> Any | None:
+        # _tmp_42: 'if' disjunction
         mark = self._mark()
         if (self.expect("if")) and (z := self.disjunction()):
             return z
         self._reset(mark)
         return None
 
-    def _tmp_51(self) -> Any | None:
-        # _tmp_51: assignment_expression | expression !':='
+    def _tmp_44(self) -> Any | None:
+        # _tmp_44: assignment_expression | expression !':='
         mark = self._mark()
         if assignment_expression := self.assignment_expression():
             return assignment_expression
@@ -4880,51 +5008,58 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
-    def _tmp_53(self) -> Any | None:
-        # _tmp_53: starred_expression | (assignment_expression | expression !':=') !'='
+    def _tmp_46(self) -> Any | None:
+        # _tmp_46: starred_expression | (assignment_expression | expression !':=') !'='
         mark = self._mark()
         if starred_expression := self.starred_expression():
             return starred_expression
         self._reset(mark)
-        if (_tmp_51 := self._tmp_51()) and (self.negative_lookahead(self.expect, "=")):
-            return _tmp_51
+        if (_tmp_44 := self._tmp_44()) and (self.negative_lookahead(self.expect, "=")):
+            return _tmp_44
         self._reset(mark)
         return None
 
-    def _tmp_54(self) -> Any | None:
-        # _tmp_54: ',' kwargs
+    def _tmp_47(self) -> Any | None:
+        # _tmp_47: ',' kwargs
         mark = self._mark()
         if (self.expect(",")) and (k := self.kwargs()):
             return k
         self._reset(mark)
         return None
 
-    def _tmp_55(self) -> Any | None:
-        # _tmp_55: ',' star_target
+    def _tmp_48(self) -> Any | None:
+        # _tmp_48: ',' star_target
         mark = self._mark()
         if (self.expect(",")) and (c := self.star_target()):
             return c
         self._reset(mark)
         return None
 
-    def _tmp_57(self) -> Any | None:
-        # _tmp_57: !'*' star_target
+    def _tmp_50(self) -> Any | None:
+        # _tmp_50: !'*' star_target
         mark = self._mark()
         if (self.negative_lookahead(self.expect, "*")) and (star_target := self.star_target()):
             return star_target
         self._reset(mark)
         return None
 
-    def _tmp_58(self) -> Any | None:
-        # _tmp_58: NEWLINE INDENT
+    def _tmp_51(self) -> Any | None:
+        # _tmp_51: NEWLINE INDENT
         mark = self._mark()
         if (_newline := self.token("NEWLINE")) and (_indent := self.token("INDENT")):
             return [_newline, _indent]
         self._reset(mark)
         return None
 
-    def _tmp_59(self) -> Any | None:
-        # _tmp_59: args | expression for_if_clauses
+    def _tmp_52(self) -> Any | None:
+        # _tmp_52: (','.(starred_expression | (assignment_expression | expression !':=') !'=')+ ',' kwargs) | kwargs
+        return self.seq_alts(
+            self._tmp_112,
+            self.kwargs,
+        )
+
+    def _tmp_53(self) -> Any | None:
+        # _tmp_53: args | expression for_if_clauses
         mark = self._mark()
         if args := self.args():
             return args
@@ -4934,39 +5069,39 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
-    def _tmp_60(self) -> Any | None:
-        # _tmp_60: args ','
+    def _tmp_54(self) -> Any | None:
+        # _tmp_54: args ','
         mark = self._mark()
         if (args := self.args()) and (literal := self.expect(",")):
             return [args, literal]
         self._reset(mark)
         return None
 
-    def _tmp_61(self) -> Any | None:
-        # _tmp_61: ',' | ')'
+    def _tmp_55(self) -> Any | None:
+        # _tmp_55: ',' | ')'
         return self.seq_alts(
             (self.expect, ","),
             (self.expect, ")"),
         )
 
-    def _tmp_62(self) -> Any | None:
-        # _tmp_62: 'True' | 'False' | 'None'
+    def _tmp_56(self) -> Any | None:
+        # _tmp_56: 'True' | 'False' | 'None'
         return self.seq_alts(
             (self.expect, "True"),
             (self.expect, "False"),
             (self.expect, "None"),
         )
 
-    def _tmp_63(self) -> Any | None:
-        # _tmp_63: NAME '='
+    def _tmp_57(self) -> Any | None:
+        # _tmp_57: NAME '='
         mark = self._mark()
         if (name := self.name()) and (literal := self.expect("=")):
             return [name, literal]
         self._reset(mark)
         return None
 
-    def _tmp_64(self) -> Any | None:
-        # _tmp_64: NAME STRING | SOFT_KEYWORD
+    def _tmp_58(self) -> Any | None:
+        # _tmp_58: NAME STRING | SOFT_KEYWORD
         mark = self._mark()
         if (name := self.name()) and (_string := self.token("STRING")):
             return [name, _string]
@@ -4976,29 +5111,37 @@ class XonshParser(Parser):
         self._reset(mark)
         return None
 
-    def _tmp_65(self) -> Any | None:
-        # _tmp_65: 'else' | ':'
+    def _tmp_59(self) -> Any | None:
+        # _tmp_59: 'else' | ':'
         return self.seq_alts(
             (self.expect, "else"),
             (self.expect, ":"),
         )
 
-    def _tmp_66(self) -> Any | None:
-        # _tmp_66: FSTRING_MIDDLE | fstring_replacement_field
+    def _tmp_60(self) -> Any | None:
+        # _tmp_60: FSTRING_MIDDLE | fstring_replacement_field
         return self.seq_alts(
             (self.token, "FSTRING_MIDDLE"),
             self.fstring_replacement_field,
         )
 
-    def _tmp_67(self) -> Any | None:
-        # _tmp_67: '=' | ':='
+    def _tmp_61(self) -> Any | None:
+        # _tmp_61: '+' | '-' | '~'
+        return self.seq_alts(
+            (self.expect, "+"),
+            (self.expect, "-"),
+            (self.expect, "~"),
+        )
+
+    def _tmp_62(self) -> Any | None:
+        # _tmp_62: '=' | ':='
         return self.seq_alts(
             (self.expect, "="),
             (self.expect, ":="),
         )
 
-    def _tmp_68(self) -> Any | None:
-        # _tmp_68: plist | ptuple | genexp | 'True' | 'None' | 'False'
+    def _tmp_63(self) -> Any | None:
+        # _tmp_63: plist | ptuple | genexp | 'True' | 'None' | 'False'
         return self.seq_alts(
             self.plist,
             self.ptuple,
@@ -5008,187 +5151,187 @@ class XonshParser(Parser):
             (self.expect, "False"),
         )
 
-    def _tmp_70(self) -> Any | None:
-        # _tmp_70: star_targets '='
+    def _tmp_65(self) -> Any | None:
+        # _tmp_65: star_targets '='
         mark = self._mark()
         if (star_targets := self.star_targets()) and (literal := self.expect("=")):
             return [star_targets, literal]
         self._reset(mark)
         return None
 
-    def _tmp_72(self) -> Any | None:
-        # _tmp_72: '[' | '(' | '{'
+    def _tmp_67(self) -> Any | None:
+        # _tmp_67: '[' | '(' | '{'
         return self.seq_alts(
             (self.expect, "["),
             (self.expect, "("),
             (self.expect, "{"),
         )
 
-    def _tmp_73(self) -> Any | None:
-        # _tmp_73: '[' | '{'
+    def _tmp_68(self) -> Any | None:
+        # _tmp_68: '[' | '{'
         return self.seq_alts(
             (self.expect, "["),
             (self.expect, "{"),
         )
 
-    def _tmp_75(self) -> Any | None:
-        # _tmp_75: slash_no_default | slash_with_default
+    def _tmp_70(self) -> Any | None:
+        # _tmp_70: slash_no_default | slash_with_default
         return self.seq_alts(
             self.slash_no_default,
             self.slash_with_default,
         )
 
-    def _tmp_77(self) -> Any | None:
-        # _tmp_77: ',' | param_no_default
+    def _tmp_72(self) -> Any | None:
+        # _tmp_72: ',' | param_no_default
         return self.seq_alts(
             (self.expect, ","),
             self.param_no_default,
         )
 
-    def _tmp_78(self) -> Any | None:
-        # _tmp_78: ')' | ','
+    def _tmp_73(self) -> Any | None:
+        # _tmp_73: ')' | ','
         return self.seq_alts(
             (self.expect, ")"),
             (self.expect, ","),
         )
 
-    def _tmp_79(self) -> Any | None:
-        # _tmp_79: ')' | ',' (')' | '**')
+    def _tmp_74(self) -> Any | None:
+        # _tmp_74: ')' | ',' (')' | '**')
         mark = self._mark()
         if literal := self.expect(")"):
             return literal
         self._reset(mark)
-        if (literal := self.expect(",")) and (_tmp_119 := self._tmp_119()):
-            return [literal, _tmp_119]
+        if (literal := self.expect(",")) and (_tmp_113 := self._tmp_113()):
+            return [literal, _tmp_113]
         self._reset(mark)
         return None
 
-    def _tmp_80(self) -> Any | None:
-        # _tmp_80: param_no_default | ','
+    def _tmp_75(self) -> Any | None:
+        # _tmp_75: param_no_default | ','
         return self.seq_alts(
             self.param_no_default,
             (self.expect, ","),
         )
 
-    def _tmp_82(self) -> Any | None:
-        # _tmp_82: '*' | '**' | '/'
+    def _tmp_77(self) -> Any | None:
+        # _tmp_77: '*' | '**' | '/'
         return self.seq_alts(
             (self.expect, "*"),
             (self.expect, "**"),
             (self.expect, "/"),
         )
 
-    def _tmp_83(self) -> Any | None:
-        # _tmp_83: lambda_slash_no_default | lambda_slash_with_default
+    def _tmp_78(self) -> Any | None:
+        # _tmp_78: lambda_slash_no_default | lambda_slash_with_default
         return self.seq_alts(
             self.lambda_slash_no_default,
             self.lambda_slash_with_default,
         )
 
-    def _tmp_85(self) -> Any | None:
-        # _tmp_85: ',' | lambda_param_no_default
+    def _tmp_80(self) -> Any | None:
+        # _tmp_80: ',' | lambda_param_no_default
         return self.seq_alts(
             (self.expect, ","),
             self.lambda_param_no_default,
         )
 
-    def _tmp_86(self) -> Any | None:
-        # _tmp_86: ':' | ',' (':' | '**')
+    def _tmp_81(self) -> Any | None:
+        # _tmp_81: ':' | ',' (':' | '**')
         mark = self._mark()
         if literal := self.expect(":"):
             return literal
         self._reset(mark)
-        if (literal := self.expect(",")) and (_tmp_120 := self._tmp_120()):
-            return [literal, _tmp_120]
+        if (literal := self.expect(",")) and (_tmp_114 := self._tmp_114()):
+            return [literal, _tmp_114]
         self._reset(mark)
         return None
 
-    def _tmp_87(self) -> Any | None:
-        # _tmp_87: lambda_param_no_default | ','
+    def _tmp_82(self) -> Any | None:
+        # _tmp_82: lambda_param_no_default | ','
         return self.seq_alts(
             self.lambda_param_no_default,
             (self.expect, ","),
         )
 
-    def _tmp_91(self) -> Any | None:
-        # _tmp_91: expression ['as' star_target]
+    def _tmp_86(self) -> Any | None:
+        # _tmp_86: expression ['as' star_target]
         mark = self._mark()
-        if (expression := self.expression()) and (opt := self._tmp_121(),):
+        if (expression := self.expression()) and (opt := self._tmp_115(),):
             return [expression, opt]
         self._reset(mark)
         return None
 
-    def _tmp_92(self) -> Any | None:
-        # _tmp_92: expressions ['as' star_target]
+    def _tmp_87(self) -> Any | None:
+        # _tmp_87: expressions ['as' star_target]
         mark = self._mark()
-        if (expressions := self.expressions()) and (opt := self._tmp_121(),):
+        if (expressions := self.expressions()) and (opt := self._tmp_115(),):
             return [expressions, opt]
         self._reset(mark)
         return None
 
-    def _tmp_95(self) -> Any | None:
-        # _tmp_95: 'except' | 'finally'
+    def _tmp_90(self) -> Any | None:
+        # _tmp_90: 'except' | 'finally'
         return self.seq_alts(
             (self.expect, "except"),
             (self.expect, "finally"),
         )
 
-    def _tmp_96(self) -> Any | None:
-        # _tmp_96: 'as' NAME
+    def _tmp_91(self) -> Any | None:
+        # _tmp_91: 'as' NAME
         mark = self._mark()
         if (literal := self.expect("as")) and (name := self.name()):
             return [literal, name]
         self._reset(mark)
         return None
 
-    def _tmp_97(self) -> Any | None:
-        # _tmp_97: expression ['as' NAME]
+    def _tmp_92(self) -> Any | None:
+        # _tmp_92: expression ['as' NAME]
         mark = self._mark()
-        if (expression := self.expression()) and (opt := self._tmp_96(),):
+        if (expression := self.expression()) and (opt := self._tmp_91(),):
             return [expression, opt]
         self._reset(mark)
         return None
 
-    def _tmp_100(self) -> Any | None:
-        # _tmp_100: NEWLINE | ':'
+    def _tmp_95(self) -> Any | None:
+        # _tmp_95: NEWLINE | ':'
         return self.seq_alts(
             (self.token, "NEWLINE"),
             (self.expect, ":"),
         )
 
-    def _tmp_103(self) -> Any | None:
-        # _tmp_103: positional_patterns ','
+    def _tmp_98(self) -> Any | None:
+        # _tmp_98: positional_patterns ','
         mark = self._mark()
         if (positional_patterns := self.positional_patterns()) and (literal := self.expect(",")):
             return [positional_patterns, literal]
         self._reset(mark)
         return None
 
-    def _tmp_104(self) -> Any | None:
-        # _tmp_104: '->' expression
+    def _tmp_99(self) -> Any | None:
+        # _tmp_99: '->' expression
         mark = self._mark()
         if (literal := self.expect("->")) and (expression := self.expression()):
             return [literal, expression]
         self._reset(mark)
         return None
 
-    def _tmp_105(self) -> Any | None:
-        # _tmp_105: '(' arguments? ')'
+    def _tmp_100(self) -> Any | None:
+        # _tmp_100: '(' arguments? ')'
         mark = self._mark()
         if (literal := self.expect("(")) and (opt := self.arguments(),) and (literal_1 := self.expect(")")):
             return [literal, opt, literal_1]
         self._reset(mark)
         return None
 
-    def _tmp_107(self) -> Any | None:
-        # _tmp_107: '}' | ','
+    def _tmp_102(self) -> Any | None:
+        # _tmp_102: '}' | ','
         return self.seq_alts(
             (self.expect, "}"),
             (self.expect, ","),
         )
 
-    def _tmp_109(self) -> Any | None:
-        # _tmp_109: '=' | '!' | ':' | '}'
+    def _tmp_104(self) -> Any | None:
+        # _tmp_104: '=' | '!' | ':' | '}'
         return self.seq_alts(
             (self.expect, "="),
             (self.expect, "!"),
@@ -5196,59 +5339,57 @@ class XonshParser(Parser):
             (self.expect, "}"),
         )
 
-    def _tmp_110(self) -> Any | None:
-        # _tmp_110: '!' | ':' | '}'
+    def _tmp_105(self) -> Any | None:
+        # _tmp_105: '!' | ':' | '}'
         return self.seq_alts(
             (self.expect, "!"),
             (self.expect, ":"),
             (self.expect, "}"),
         )
 
-    def _tmp_111(self) -> Any | None:
-        # _tmp_111: '!' NAME
+    def _tmp_106(self) -> Any | None:
+        # _tmp_106: '!' NAME
         mark = self._mark()
         if (literal := self.expect("!")) and (name := self.name()):
             return [literal, name]
         self._reset(mark)
         return None
 
-    def _tmp_112(self) -> Any | None:
-        # _tmp_112: ':' | '}'
+    def _tmp_107(self) -> Any | None:
+        # _tmp_107: ':' | '}'
         return self.seq_alts(
             (self.expect, ":"),
             (self.expect, "}"),
         )
 
-    def _tmp_116(self) -> Any | None:
-        # _tmp_116: 'or' | '||'
-        return self.seq_alts(
-            (self.expect, "or"),
-            (self.expect, "||"),
-        )
-
-    def _tmp_117(self) -> Any | None:
-        # _tmp_117: 'and' | '&&'
-        return self.seq_alts(
-            (self.expect, "and"),
-            (self.expect, "&&"),
-        )
+    def _tmp_112(self) -> Any | None:
+        # _tmp_112: ','.(starred_expression | (assignment_expression | expression !':=') !'=')+ ',' kwargs
+        mark = self._mark()
+        if (
+            (gathered := self.gathered(self._tmp_46, self.expect, ","))
+            and (literal := self.expect(","))
+            and (kwargs := self.kwargs())
+        ):
+            return [gathered, literal, kwargs]
+        self._reset(mark)
+        return None
 
-    def _tmp_119(self) -> Any | None:
-        # _tmp_119: ')' | '**'
+    def _tmp_113(self) -> Any | None:
+        # _tmp_113: ')' | '**'
         return self.seq_alts(
             (self.expect, ")"),
             (self.expect, "**"),
         )
 
-    def _tmp_120(self) -> Any | None:
-        # _tmp_120: ':' | '**'
+    def _tmp_114(self) -> Any | None:
+        # _tmp_114: ':' | '**'
         return self.seq_alts(
             (self.expect, ":"),
             (self.expect, "**"),
         )
 
-    def _tmp_121(self) -> Any | None:
-        # _tmp_121: 'as' star_target
+    def _tmp_115(self) -> Any | None:
+        # _tmp_115: 'as' star_target
         mark = self._mark()
         if (literal := self.expect("as")) and (star_target := self.star_target()):
             return [literal, star_target]
@@ -5257,3 +5398,220 @@ class XonshParser(Parser):
 
     KEYWORDS = ('False', 'None', 'True', 'and', 'as', 'assert', 'async', 'await', 'break', 'class', 'continue', 'def', 'del', 'elif', 'else', 'except', 'finally', 'for', 'from', 'global', 'if', 'import', 'in', 'is', 'lambda', 'nonlocal', 'not', 'or', 'pass', 'raise', 'return', 'try', 'while', 'with', 'yield')  # fmt: skip
     SOFT_KEYWORDS = ('_', 'case', 'match', 'type')  # fmt: skip
+
+    # fmt: off
+    RULES = {
+        'file': ('module_statements? $', ('"match"', '"type"', "'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'@'", "'False'", "'None'", "'True'", "'['", "'assert'", "'async'", "'await'", "'break'", "'class'", "'continue'", "'def'", "'del'", "'for'", "'from'", "'global'", "'if'", "'import'", "'lambda'", "'nonlocal'", "'not'", "'pass'", "'raise'", "'return'", "'try'", "'while'", "'with'", "'yield'", "'{'", "'~'", 'ENDMARKER', 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'interactive': ('statement_newline', ('"match"', '"type"', "'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'@'", "'False'", "'None'", "'True'", "'['", "'assert'", "'async'", "'await'", "'break'", "'class'", "'continue'", "'def'", "'del'", "'for'", "'from'", "'global'", "'if'", "'import'", "'lambda'", "'nonlocal'", "'not'", "'pass'", "'raise'", "'return'", "'try'", "'while'", "'with'", "'yield'", "'{'", "'~'", 'ENDMARKER', 'FSTRING_START', 'NAME', 'NEWLINE', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'eval': ('expressions NEWLINE* $', ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'func_type': ("'(' type_expressions? ')' '->' expression NEWLINE* $", ("'('",)),
+        'fstring': ('FSTRING_START fstring_mid* FSTRING_END', ('FSTRING_START',)),
+        'statements': ('statement+', ('"match"', '"type"', "'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'@'", "'False'", "'None'", "'True'", "'['", "'assert'", "'async'", "'await'", "'break'", "'class'", "'continue'", "'def'", "'del'", "'for'", "'from'", "'global'", "'if'", "'import'", "'lambda'", "'nonlocal'", "'not'", "'pass'", "'raise'", "'return'", "'try'", "'while'", "'with'", "'yield'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'module_statements': ('module_statement+', ('"match"', '"type"', "'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'@'", "'False'", "'None'", "'True'", "'['", "'assert'", "'async'", "'await'", "'break'", "'class'", "'continue'", "'def'", "'del'", "'for'", "'from'", "'global'", "'if'", "'import'", "'lambda'", "'nonlocal'", "'not'", "'pass'", "'raise'", "'return'", "'try'", "'while'", "'with'", "'yield'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'module_statement': ('statement', ('"match"', '"type"', "'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'@'", "'False'", "'None'", "'True'", "'['", "'assert'", "'async'", "'await'", "'break'", "'class'", "'continue'", "'def'", "'del'", "'for'", "'from'", "'global'", "'if'", "'import'", "'lambda'", "'nonlocal'", "'not'", "'pass'", "'raise'", "'return'", "'try'", "'while'", "'with'", "'yield'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'statement': ('compound_stmt | simple_stmts', ('"match"', '"type"', "'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'@'", "'False'", "'None'", "'True'", "'['", "'assert'", "'async'", "'await'", "'break'", "'class'", "'continue'", "'def'", "'del'", "'for'", "'from'", "'global'", "'if'", "'import'", "'lambda'", "'nonlocal'", "'not'", "'pass'", "'raise'", "'return'", "'try'", "'while'", "'with'", "'yield'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'statement_newline': ('compound_stmt NEWLINE | simple_stmts | NEWLINE | $', ('"match"', '"type"', "'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'@'", "'False'", "'None'", "'True'", "'['", "'assert'", "'async'", "'await'", "'break'", "'class'", "'continue'", "'def'", "'del'", "'for'", "'from'", "'global'", "'if'", "'import'", "'lambda'", "'nonlocal'", "'not'", "'pass'", "'raise'", "'return'", "'try'", "'while'", "'with'", "'yield'", "'{'", "'~'", 'ENDMARKER', 'FSTRING_START', 'NAME', 'NEWLINE', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'simple_stmts': ("simple_stmt !';' NEWLINE | ';'.simple_stmt+ ';'? NEWLINE", ('"type"', "'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'assert'", "'await'", "'break'", "'continue'", "'del'", "'from'", "'global'", "'import'", "'lambda'", "'nonlocal'", "'not'", "'pass'", "'raise'", "'return'", "'yield'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'simple_stmt': ('assignment | &"type" type_alias | star_expressions | &\'return\' return_stmt | &(\'import\' | \'from\') import_stmt | &\'raise\' raise_stmt | \'pass\' | &\'del\' del_stmt | &\'yield\' yield_stmt | &\'assert\' assert_stmt | \'break\' | \'continue\' | &\'global\' global_stmt | &\'nonlocal\' nonlocal_stmt', ('"type"', "'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'assert'", "'await'", "'break'", "'continue'", "'del'", "'from'", "'global'", "'import'", "'lambda'", "'nonlocal'", "'not'", "'pass'", "'raise'", "'return'", "'yield'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'compound_stmt': ("&('def' | '@' | 'async') function_def | &'if' if_stmt | &('class' | '@') class_def | &('with' | 'async') with_stmt | &('for' | 'async') for_stmt | &'try' try_stmt | &'while' while_stmt | match_stmt", ('"match"', "'@'", "'async'", "'class'", "'def'", "'for'", "'if'", "'try'", "'while'", "'with'")),
+        'assignment': ("NAME ':' expression ['=' annotated_rhs] | ('(' single_target ')' | single_subscript_attribute_target) ':' expression ['=' annotated_rhs] | ((star_targets '='))+ annotated_rhs !'=' TYPE_COMMENT? | single_target augassign ~ annotated_rhs | invalid_assignment", ("'$'", "'${'", "'('", "'*'", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'annotated_rhs': ('yield_expr | star_expressions', ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'yield'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'augassign': ("'+=' | '-=' | '*=' | '@=' | '/=' | '%=' | '&=' | '|=' | '^=' | '<<=' | '>>=' | '**=' | '//='", ("'%='", "'&='", "'**='", "'*='", "'+='", "'-='", "'//='", "'/='", "'<<='", "'>>='", "'@='", "'^='", "'|='")),
+        'return_stmt': ("'return' star_expressions?", ("'return'",)),
+        'raise_stmt': ("'raise' expression ['from' expression] | 'raise'", ("'raise'",)),
+        'global_stmt': ("'global' ','.NAME+", ("'global'",)),
+        'nonlocal_stmt': ("'nonlocal' ','.NAME+", ("'nonlocal'",)),
+        'del_stmt': ("'del' del_targets &(';' | NEWLINE) | invalid_del_stmt", ("'del'",)),
+        'yield_stmt': ('yield_expr', ("'yield'",)),
+        'assert_stmt': ("'assert' expression [',' expression]", ("'assert'",)),
+        'import_stmt': ('invalid_import | import_name | import_from', ("'from'", "'import'")),
+        'import_name': ("'import' dotted_as_names", ("'import'",)),
+        'import_from': ("'from' (('.' | '...'))* dotted_name 'import' import_from_targets | 'from' (('.' | '...'))+ 'import' import_from_targets", ("'from'",)),
+        'import_from_targets': ("'(' import_from_as_names ','? ')' | import_from_as_names !',' | '*' | invalid_import_from_targets", ("'('", "'*'", 'NAME')),
+        'import_from_as_names': ("','.import_from_as_name+", ('NAME',)),
+        'import_from_as_name': ("NAME ['as' NAME]", ('NAME',)),
+        'dotted_as_names': ("','.dotted_as_name+", ('NAME',)),
+        'dotted_as_name': ("dotted_name ['as' NAME]", ('NAME',)),
+        'dotted_name': ("dotted_name '.' NAME | NAME", ('NAME',)),
+        'block': ('NEWLINE INDENT statements DEDENT | simple_stmts | invalid_block', ('"type"', "'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'assert'", "'await'", "'break'", "'continue'", "'del'", "'from'", "'global'", "'import'", "'lambda'", "'nonlocal'", "'not'", "'pass'", "'raise'", "'return'", "'yield'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NEWLINE', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'decorators': ('decorator+', ("'@'",)),
+        'decorator': ("'@' named_expression NEWLINE", ("'@'",)),
+        'class_def': ('decorators class_def_raw | class_def_raw', ("'@'", "'class'")),
+        'class_def_raw': ("invalid_class_def_raw | 'class' NAME type_params? ['(' arguments? ')'] &&':' block", ("'class'",)),
+        'function_def': ('decorators function_def_raw | function_def_raw', ("'@'", "'async'", "'def'")),
+        'function_def_raw': ("invalid_def_raw | 'def' NAME type_params? &&'(' params? ')' ['->' expression] &&':' func_type_comment? block | 'async' 'def' NAME type_params? &&'(' params? ')' ['->' expression] &&':' func_type_comment? block", ("'async'", "'def'")),
+        'params': ('invalid_parameters | parameters', ("'*'", "'**'", 'NAME')),
+        'parameters': ('slash_no_default param_no_default* param_with_default* star_etc? | slash_with_default param_with_default* star_etc? | param_no_default+ param_with_default* star_etc? | param_with_default+ star_etc? | star_etc', ("'*'", "'**'", 'NAME')),
+        'slash_no_default': ("param_no_default+ '/' ',' | param_no_default+ '/' &')'", ('NAME',)),
+        'slash_with_default': ("param_no_default* param_with_default+ '/' ',' | param_no_default* param_with_default+ '/' &')'", ('NAME',)),
+        'star_etc': ("invalid_star_etc | '*' param_no_default param_maybe_default* kwds? | '*' param_no_default_star_annotation param_maybe_default* kwds? | '*' ',' param_maybe_default+ kwds? | kwds", ("'*'", "'**'")),
+        'kwds': ("invalid_kwds | '**' param_no_default", ("'**'",)),
+        'param_no_default': ("param ',' TYPE_COMMENT? | param TYPE_COMMENT? &')'", ('NAME',)),
+        'param_no_default_star_annotation': ("param_star_annotation ',' TYPE_COMMENT? | param_star_annotation TYPE_COMMENT? &')'", ('NAME',)),
+        'param_with_default': ("param default ',' TYPE_COMMENT? | param default TYPE_COMMENT? &')'", ('NAME',)),
+        'param_maybe_default': ("param default? ',' TYPE_COMMENT? | param default? TYPE_COMMENT? &')'", ('NAME',)),
+        'param': ('NAME annotation?', ('NAME',)),
+        'param_star_annotation': ('NAME star_annotation', ('NAME',)),
+        'annotation': ("':' expression", ("':'",)),
+        'star_annotation': ("':' star_expression", ("':'",)),
+        'default': ("'=' expression | invalid_default", ("'='",)),
+        'if_stmt': ("invalid_if_stmt | 'if' named_expression ':' block elif_stmt | 'if' named_expression ':' block else_block?", ("'if'",)),
+        'elif_stmt': ("invalid_elif_stmt | 'elif' named_expression ':' block elif_stmt | 'elif' named_expression ':' block else_block?", ("'elif'",)),
+        'else_block': ("invalid_else_stmt | 'else' &&':' block", ("'else'",)),
+        'while_stmt': ("invalid_while_stmt | 'while' named_expression ':' block else_block?", ("'while'",)),
+        'for_stmt': ("invalid_for_stmt | 'for' star_targets 'in' ~ star_expressions &&':' TYPE_COMMENT? block else_block? | 'async' 'for' star_targets 'in' ~ star_expressions ':' TYPE_COMMENT? block else_block? | invalid_for_target", ("'async'", "'for'")),
+        'with_stmt': ("invalid_with_stmt_indent | &with_macro_start ~ with_macro_stmt | 'with' '(' ','.with_item+ ','? ')' ':' block | 'with' ','.with_item+ ':' TYPE_COMMENT? block | 'async' 'with' '(' ','.with_item+ ','? ')' ':' block | 'async' 'with' ','.with_item+ ':' TYPE_COMMENT? block | invalid_with_stmt", ("'async'", "'with'")),
+        'with_item': ("expression 'as' star_target &(',' | ')' | ':') | invalid_with_item | expression", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'with_macro_stmt': ('with_macro_start MACRO_PARAM', ("'with'",)),
+        'with_macro_start': ("'with' '!' ~ with_item ':'", ("'with'",)),
+        'try_stmt': ("invalid_try_stmt | 'try' &&':' block finally_block | 'try' &&':' block except_block+ else_block? finally_block? | 'try' &&':' block except_star_block+ else_block? finally_block?", ("'try'",)),
+        'except_block': ("invalid_except_stmt_indent | 'except' expression ['as' NAME] ':' block | 'except' ':' block | invalid_except_stmt", ("'except'",)),
+        'except_star_block': ("invalid_except_star_stmt_indent | 'except' '*' expression ['as' NAME] ':' block | invalid_except_stmt", ("'except'",)),
+        'finally_block': ("invalid_finally_stmt | 'finally' &&':' block", ("'finally'",)),
+        'match_stmt': ('"match" subject_expr \':\' NEWLINE INDENT case_block+ DEDENT | invalid_match_stmt', ('"match"',)),
+        'subject_expr': ("star_named_expression ',' star_named_expressions? | named_expression", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'case_block': ('invalid_case_block | "case" patterns guard? \':\' block', ('"case"',)),
+        'guard': ("'if' named_expression", ("'if'",)),
+        'patterns': ('open_sequence_pattern | pattern', ('"_"', "'('", "'*'", "'-'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'pattern': ('as_pattern | or_pattern', ('"_"', "'('", "'-'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'as_pattern': ("or_pattern 'as' pattern_capture_target | invalid_as_pattern", ('"_"', "'('", "'-'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'or_pattern': ("'|'.closed_pattern+", ('"_"', "'('", "'-'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'closed_pattern': ('literal_pattern | capture_pattern | wildcard_pattern | value_pattern | group_pattern | sequence_pattern | mapping_pattern | class_pattern', ('"_"', "'('", "'-'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'literal_pattern': ("signed_number !('+' | '-') | complex_number | strings | 'None' | 'True' | 'False'", ("'-'", "'False'", "'None'", "'True'", 'FSTRING_START', 'NUMBER', 'STRING')),
+        'literal_expr': ("signed_number !('+' | '-') | complex_number | strings | 'None' | 'True' | 'False'", ("'-'", "'False'", "'None'", "'True'", 'FSTRING_START', 'NUMBER', 'STRING')),
+        'complex_number': ("signed_real_number '+' imaginary_number | signed_real_number '-' imaginary_number", ("'-'", 'NUMBER')),
+        'signed_number': ("NUMBER | '-' NUMBER", ("'-'", 'NUMBER')),
+        'signed_real_number': ("real_number | '-' real_number", ("'-'", 'NUMBER')),
+        'real_number': ('NUMBER', ('NUMBER',)),
+        'imaginary_number': ('NUMBER', ('NUMBER',)),
+        'capture_pattern': ('pattern_capture_target', ('NAME',)),
+        'pattern_capture_target': ('!"_" NAME !(\'.\' | \'(\' | \'=\')', ('NAME',)),
+        'wildcard_pattern': ('"_"', ('"_"',)),
+        'value_pattern': ("attr !('.' | '(' | '=')", ('NAME',)),
+        'attr': ("name_or_attr '.' NAME", ('NAME',)),
+        'name_or_attr': ('attr | NAME', ('NAME',)),
+        'group_pattern': ("'(' pattern ')'", ("'('",)),
+        'sequence_pattern': ("'[' maybe_sequence_pattern? ']' | '(' open_sequence_pattern? ')'", ("'('", "'['")),
+        'open_sequence_pattern': ("maybe_star_pattern ',' maybe_sequence_pattern?", ('"_"', "'('", "'*'", "'-'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'maybe_sequence_pattern': ("','.maybe_star_pattern+ ','?", ('"_"', "'('", "'*'", "'-'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'maybe_star_pattern': ('star_pattern | pattern', ('"_"', "'('", "'*'", "'-'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'star_pattern': ("'*' pattern_capture_target | '*' wildcard_pattern", ("'*'",)),
+        'mapping_pattern': ("'{' '}' | '{' double_star_pattern ','? '}' | '{' items_pattern ',' double_star_pattern ','? '}' | '{' items_pattern ','? '}'", ("'{'",)),
+        'items_pattern': ("','.key_value_pattern+", ("'-'", "'False'", "'None'", "'True'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'key_value_pattern': ("(literal_expr | attr) ':' pattern", ("'-'", "'False'", "'None'", "'True'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'double_star_pattern': ("'**' pattern_capture_target", ("'**'",)),
+        'class_pattern': ("name_or_attr '(' ')' | name_or_attr '(' positional_patterns ','? ')' | name_or_attr '(' keyword_patterns ','? ')' | name_or_attr '(' positional_patterns ',' keyword_patterns ','? ')' | invalid_class_pattern", ('NAME',)),
+        'positional_patterns': ("','.pattern+", ('"_"', "'('", "'-'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'STRING')),
+        'keyword_patterns': ("','.keyword_pattern+", ('NAME',)),
+        'keyword_pattern': ("NAME '=' pattern", ('NAME',)),
+        'type_alias': ('"type" NAME type_params? \'=\' expression', ('"type"',)),
+        'type_params': ("'[' type_param_seq ']'", ("'['",)),
+        'type_param_seq': ("','.type_param+ ','?", ("'*'", "'**'", 'NAME')),
+        'type_param': ("NAME type_param_bound? | '*' NAME ':' expression | '*' NAME | '**' NAME ':' expression | '**' NAME", ("'*'", "'**'", 'NAME')),
+        'type_param_bound': ("':' expression", ("':'",)),
+        'expressions': ("expression ((',' expression))+ ','? | expression ',' | expression", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'expression': ("invalid_expression | invalid_legacy_expression | disjunction 'if' disjunction 'else' expression | disjunction | lambdef", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'yield_expr': ("'yield' 'from' expression | 'yield' star_expressions?", ("'yield'",)),
+        'star_expressions': ("star_expression ((',' star_expression))+ ','? | star_expression ',' | star_expression", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'star_expression': ("'*' bitwise_or | expression", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'star_named_expressions': ("','.star_named_expression+ ','?", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'star_named_expression': ("'*' bitwise_or | named_expression", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'assignment_expression': ("NAME ':=' ~ expression", ('NAME',)),
+        'named_expression': ("assignment_expression | invalid_named_expression | expression !':='", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'disjunction': ('conjunction ((or_op conjunction))+ | conjunction', ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'conjunction': ('inversion ((and_op inversion))+ | inversion', ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'or_op': ("'or' | '||'", ("'or'", "'||'")),
+        'and_op': ("'and' | '&&'", ("'&&'", "'and'")),
+        'inversion': ("invalid_inversion | 'not' inversion | comparison", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'comparison': ('bitwise_or compare_op_bitwise_or_pair+ | bitwise_or', ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'compare_op_bitwise_or_pair': ('eq_bitwise_or | noteq_bitwise_or | lte_bitwise_or | lt_bitwise_or | gte_bitwise_or | gt_bitwise_or | notin_bitwise_or | in_bitwise_or | isnot_bitwise_or | is_bitwise_or', ("'!='", "'<'", "'<='", "'=='", "'>'", "'>='", "'in'", "'is'", "'not'")),
+        'eq_bitwise_or': ("'==' bitwise_or", ("'=='",)),
+        'noteq_bitwise_or': ("'!=' bitwise_or", ("'!='",)),
+        'lte_bitwise_or': ("'<=' bitwise_or", ("'<='",)),
+        'lt_bitwise_or': ("'<' bitwise_or", ("'<'",)),
+        'gte_bitwise_or': ("'>=' bitwise_or", ("'>='",)),
+        'gt_bitwise_or': ("'>' bitwise_or", ("'>'",)),
+        'notin_bitwise_or': ("'not' 'in' bitwise_or", ("'not'",)),
+        'in_bitwise_or': ("'in' bitwise_or", ("'in'",)),
+        'isnot_bitwise_or': ("'is' 'not' bitwise_or", ("'is'",)),
+        'is_bitwise_or': ("'is' bitwise_or", ("'is'",)),
+        'bitwise_or': ("bitwise_or '|' bitwise_xor | bitwise_xor", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'bitwise_xor': ("bitwise_xor '^' bitwise_and | bitwise_and", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'bitwise_and': ("bitwise_and '&' shift_expr | shift_expr", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'shift_expr': ("shift_expr '<<' sum | shift_expr '>>' sum | sum", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'sum': ("sum '+' term | sum '-' term | term", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'term': ("term '*' factor | term '/' factor | term '//' factor | term '%' factor | term '@' factor | factor", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'factor': ("invalid_factor | '+' factor | '-' factor | '~' factor | power", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'power': ("await_primary '**' factor | await_primary", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'await_primary': ("'await' primary | primary", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'primary': ('primary \'.\' NAME | primary genexp | func_macro_start ~ MACRO_PARAM*? &&\')\' | primary \'(\' arguments? \')\' | primary \'[\' slices \']\' | &(\'$(\' | \'$[\' | \'![\' | \'!(\') ~ sub_procs | env_atom | (".".help_atom+) | atom', ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'func_macro_start': ("primary '!('", ()),
+        'sub_procs': ("'$(' ~ proc_cmds ')' | '$[' ~ proc_cmds ']' | '![' ~ proc_cmds ']' | '!(' ~ proc_cmds ')'", ("'!('", "'!['", "'$('", "'$['")),
+        'help_atom': ("atom ('??' | '?')", ("'('", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'env_atom': ("'$' NAME | '${' slices '}'", ("'$'", "'${'")),
+        'proc_cmds': ('proc_cmd+', ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'...'", "'@$('", "'@('", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'OP', 'SEARCH_PATH', 'STRING')),
+        'proc_cmd': ("sub_procs | '@(' ~ (bare_genexp | expressions) ')' | '@$(' ~ proc_cmds ')' | env_atom | help_atom | search_path | proc_macro_start ~ ((cmd_group | any_cmd))* | cmd_group | cmd_name", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'...'", "'@$('", "'@('", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'OP', 'SEARCH_PATH', 'STRING')),
+        'proc_macro_start': ("&cmd_name '!'", ('NAME', 'NUMBER', 'OP', 'STRING')),
+        'cmd_name': ("NAME | NUMBER | STRING | !']' !')' !'}' OP", ('NAME', 'NUMBER', 'OP', 'STRING')),
+        'any_cmd': ('cmd_name | WS | KEYWORD', ('KEYWORD', 'NAME', 'NUMBER', 'OP', 'STRING', 'WS')),
+        'cmd_group': ("('(' | '!(' | '$(') any_cmd* ')' | ('[' | '![' | '$[') any_cmd* ']'", ("'!('", "'!['", "'$('", "'$['", "'('", "'['")),
+        'slices': ("slice !',' | ','.(slice | starred_expression)+ ','?", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'+'", "'-'", "'...'", "':'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'slice': ("expression? ':' expression? [':' expression?] | named_expression", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "':'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'atom': ("invalid_path_literal | search_path | NAME | 'True' | 'False' | 'None' | &(STRING | FSTRING_START) strings | NUMBER | &'(' (ptuple | group | genexp) | &'[' (plist | listcomp) | &'{' (dict | set | dictcomp | setcomp) | '...'", ("'('", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'search_path': ('SEARCH_PATH', ('SEARCH_PATH',)),
+        'group': ("'(' (yield_expr | named_expression) ')' | invalid_group", ("'('",)),
+        'lambdef': ("'lambda' lambda_params? ':' expression", ("'lambda'",)),
+        'lambda_params': ('invalid_lambda_parameters | lambda_parameters', ("'*'", "'**'", 'NAME')),
+        'lambda_parameters': ('lambda_slash_no_default lambda_param_no_default* lambda_param_with_default* lambda_star_etc? | lambda_slash_with_default lambda_param_with_default* lambda_star_etc? | lambda_param_no_default+ lambda_param_with_default* lambda_star_etc? | lambda_param_with_default+ lambda_star_etc? | lambda_star_etc', ("'*'", "'**'", 'NAME')),
+        'lambda_slash_no_default': ("lambda_param_no_default+ '/' ',' | lambda_param_no_default+ '/' &':'", ('NAME',)),
+        'lambda_slash_with_default': ("lambda_param_no_default* lambda_param_with_default+ '/' ',' | lambda_param_no_default* lambda_param_with_default+ '/' &':'", ('NAME',)),
+        'lambda_star_etc': ("invalid_lambda_star_etc | '*' lambda_param_no_default lambda_param_maybe_default* lambda_kwds? | '*' ',' lambda_param_maybe_default+ lambda_kwds? | lambda_kwds", ("'*'", "'**'")),
+        'lambda_kwds': ("invalid_lambda_kwds | '**' lambda_param_no_default", ("'**'",)),
+        'lambda_param_no_default': ("lambda_param ',' | lambda_param &':'", ('NAME',)),
+        'lambda_param_with_default': ("lambda_param default ',' | lambda_param default &':'", ('NAME',)),
+        'lambda_param_maybe_default': ("lambda_param default? ',' | lambda_param default? &':'", ('NAME',)),
+        'lambda_param': ('NAME', ('NAME',)),
+        'fstring_mid': ('fstring_replacement_field | FSTRING_MIDDLE', ("'{'", 'FSTRING_MIDDLE')),
+        'fstring_replacement_field': ("'{' annotated_rhs '='? fstring_conversion? fstring_full_format_spec? '}' | invalid_replacement_field", ("'{'",)),
+        'fstring_conversion': ("'!' NAME", ("'!'",)),
+        'fstring_full_format_spec': ("':' fstring_format_spec*", ("':'",)),
+        'fstring_format_spec': ('FSTRING_MIDDLE | fstring_replacement_field', ("'{'", 'FSTRING_MIDDLE')),
+        'strings': ('((fstring | STRING))+', ('FSTRING_START', 'STRING')),
+        'plist': ("'[' star_named_expressions? ']'", ("'['",)),
+        'ptuple': ("'(' [star_named_expression ',' star_named_expressions?] ')'", ("'('",)),
+        'set': ("'{' star_named_expressions '}'", ("'{'",)),
+        'dict': ("'{' double_starred_kvpairs? '}' | '{' invalid_double_starred_kvpairs '}'", ("'{'",)),
+        'double_starred_kvpairs': ("','.double_starred_kvpair+ ','?", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'**'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'double_starred_kvpair': ("'**' bitwise_or | kvpair", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'**'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'kvpair': ("expression ':' expression", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'for_if_clauses': ('for_if_clause+', ("'async'", "'for'")),
+        'for_if_clause': ("'async' 'for' star_targets 'in' ~ disjunction (('if' disjunction))* | 'for' star_targets 'in' ~ disjunction (('if' disjunction))* | invalid_for_target", ("'async'", "'for'")),
+        'listcomp': ("'[' named_expression for_if_clauses ']' | invalid_comprehension", ("'['",)),
+        'setcomp': ("'{' named_expression for_if_clauses '}' | invalid_comprehension", ("'{'",)),
+        'genexp': ("'(' (assignment_expression | expression !':=') for_if_clauses ')' | invalid_comprehension", ("'('",)),
+        'bare_genexp': ("(assignment_expression | expression !':=') for_if_clauses", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'dictcomp': ("'{' kvpair for_if_clauses '}' | invalid_dict_comprehension", ("'{'",)),
+        'arguments': ("args ','? &')' | invalid_arguments", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'**'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'args': ("','.(starred_expression | (assignment_expression | expression !':=') !'=')+ [',' kwargs] | kwargs", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'**'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'kwargs': ("','.kwarg_or_starred+ ',' ','.kwarg_or_double_starred+ | ','.kwarg_or_starred+ | ','.kwarg_or_double_starred+", ("'*'", "'**'", 'NAME')),
+        'starred_expression': ("invalid_starred_expression | '*' expression", ("'*'",)),
+        'kwarg_or_starred': ("invalid_kwarg | NAME '=' expression | starred_expression", ("'*'", 'NAME')),
+        'kwarg_or_double_starred': ("invalid_kwarg | NAME '=' expression | '**' expression", ("'**'", 'NAME')),
+        'star_targets': ("star_target !',' | star_target ((',' star_target))* ','?", ("'$'", "'${'", "'('", "'*'", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'star_targets_list_seq': ("','.star_target+ ','?", ("'$'", "'${'", "'('", "'*'", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'star_targets_tuple_seq': ("star_target ((',' star_target))+ ','? | star_target ','", ("'$'", "'${'", "'('", "'*'", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'star_target': ("'*' (!'*' star_target) | target_with_star_atom", ("'$'", "'${'", "'('", "'*'", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'target_with_star_atom': ("t_primary '.' NAME !t_lookahead | t_primary '[' slices ']' !t_lookahead | '$' NAME | '${' slices '}' | star_atom", ("'$'", "'${'", "'('", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'star_atom': ("NAME | '(' target_with_star_atom ')' | '(' star_targets_tuple_seq? ')' | '[' star_targets_list_seq? ']'", ("'('", "'['", 'NAME')),
+        'single_target': ("single_subscript_attribute_target | NAME | '(' single_target ')'", ("'('", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'single_subscript_attribute_target': ("t_primary '.' NAME !t_lookahead | t_primary '[' slices ']' !t_lookahead", ("'('", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        't_primary': ("t_primary '.' NAME &t_lookahead | t_primary '[' slices ']' &t_lookahead | t_primary genexp &t_lookahead | t_primary '(' arguments? ')' &t_lookahead | atom &t_lookahead", ("'('", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        't_lookahead': ("'(' | '[' | '.'", ("'('", "'.'", "'['")),
+        'del_targets': ("','.del_target+ ','?", ("'('", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'del_target': ("t_primary '.' NAME !t_lookahead | t_primary '[' slices ']' !t_lookahead | del_t_atom", ("'('", "'...'", "'False'", "'None'", "'True'", "'['", "'{'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'del_t_atom': ("NAME | '(' del_target ')' | '(' del_targets? ')' | '[' del_targets? ']'", ("'('", "'['", 'NAME')),
+        'type_expressions': ("','.expression+ ',' '*' expression ',' '**' expression | ','.expression+ ',' '*' expression | ','.expression+ ',' '**' expression | '*' expression ',' '**' expression | '*' expression | '**' expression | ','.expression+", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'*'", "'**'", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+        'func_type_comment': ('NEWLINE TYPE_COMMENT &(NEWLINE INDENT) | invalid_double_type_comments | TYPE_COMMENT', ('NEWLINE', 'TYPE_COMMENT')),
+        'expression_without_invalid': ("disjunction 'if' disjunction 'else' expression | disjunction | lambdef", ("'!('", "'!['", "'$'", "'$('", "'$['", "'${'", "'('", "'+'", "'-'", "'...'", "'False'", "'None'", "'True'", "'['", "'await'", "'lambda'", "'not'", "'{'", "'~'", 'FSTRING_START', 'NAME', 'NUMBER', 'SEARCH_PATH', 'STRING')),
+    }
+    # fmt: on
@@ -0,0 +1,392 @@
+"""Checks that need to know the enclosing scopes, which CPython only reports when compiling.
+
+``ast.parse`` accepts these sources, so the checks run only when asked for.
+"""
+
+from __future__ import annotations
+
+import ast
+import enum
+import sys
+from typing import TYPE_CHECKING, NoReturn
+
+if TYPE_CHECKING:
+    from collections.abc import Callable
+
+Comprehension = ast.ListComp | ast.SetComp | ast.DictComp | ast.GeneratorExp
+
+if sys.version_info >= (3, 12):
+    AWAIT_IN_ANNOTATION = "await expression cannot be used within an annotation"
+else:
+    AWAIT_IN_ANNOTATION = "'await expression' can not be used within an annotation"
+STARRED_HERE = "can't use starred expression here"
+
+
+class ScopeKind(enum.Enum):
+    MODULE = enum.auto()
+    CLASS = enum.auto()
+    FUNCTION = enum.auto()
+    ASYNC_FUNCTION = enum.auto()
+    LAMBDA = enum.auto()
+    COMPREHENSION = enum.auto()
+
+
+class Binding(enum.Flag):
+    PARAMETER = enum.auto()
+    USED = enum.auto()
+    ANNOTATED = enum.auto()
+    ASSIGNED = enum.auto()
+    IMPORTED = enum.auto()  # binding too, but not reported before a declaration
+
+
+# the bindings making a name local to a function, where nested functions may declare it nonlocal
+LOCAL = Binding.PARAMETER | Binding.ANNOTATED | Binding.ASSIGNED | Binding.IMPORTED
+# the scopes that are functions for CPython's symbol table, comprehensions included
+FUNCTION_SCOPES = (ScopeKind.FUNCTION, ScopeKind.ASYNC_FUNCTION, ScopeKind.LAMBDA, ScopeKind.COMPREHENSION)
+
+
+def raise_error(message: str, node: ast.AST) -> NoReturn:
+    start = (node.lineno, node.col_offset + 1)  # type: ignore[attr-defined]
+    end = (node.end_lineno, node.end_col_offset + 1)  # type: ignore[attr-defined]
+    raise SyntaxError(message, ("<unknown>", *start, None, *end))
+
+
+def awaits_in(node: ast.AST) -> bool:
+    """Whether evaluating the node in the current scope awaits."""
+    if isinstance(node, ast.Await):
+        return True
+    if isinstance(node, ast.Lambda):
+        return any(awaits_in(default) for default in (*node.args.defaults, *node.args.kw_defaults) if default)
+    if isinstance(node, Comprehension):
+        return awaits_in(node.generators[0].iter) or (
+            not isinstance(node, ast.GeneratorExp) and is_async_comprehension(node)
+        )
+    return any(awaits_in(child) for child in ast.iter_child_nodes(node))
+
+
+def is_async_comprehension(node: Comprehension) -> bool:
+    """Whether the comprehension awaits, apart from its first iterable that the enclosing scope runs."""
+    if any(gen.is_async for gen in node.generators):
+        return True
+    first, *rest = node.generators
+    parts: list[ast.AST] = [first.target, *first.ifs, *rest]
+    parts += [node.key, node.value] if isinstance(node, ast.DictComp) else [node.elt]
+    return any(awaits_in(part) for part in parts)
+
+
+# the messages for a name of the scope declared global or nonlocal after it was bound, checked in this order
+BOUND_BEFORE_DECLARATION = {
+    Binding.PARAMETER: "name '{}' is parameter and {}",
+    Binding.USED: "name '{}' is used prior to {} declaration",
+    Binding.ANNOTATED: "annotated name '{}' can't be {}",
+    Binding.ASSIGNED: "name '{}' is assigned to before {} declaration",
+}
+
+
+class Scope:
+    """The names bound and declared in a scope so far, in the order of its statements."""
+
+    def __init__(self, kind: ScopeKind) -> None:
+        self.kind = kind
+        # the names of the scope in the order they first appear, declared names included
+        self.bindings: dict[str, Binding] = {}
+        # the keyword of the first ``global`` or ``nonlocal`` statement declaring a name, with the statement
+        self.declarations: dict[str, tuple[str, ast.Global | ast.Nonlocal]] = {}
+        self.children: list[Scope] = []
+
+    def bind(self, name: str, binding: Binding) -> None:
+        self.bindings[name] = self.bindings.get(name, Binding(0)) | binding
+
+    def local_names(self) -> set[str]:
+        return {name for name, binding in self.bindings.items() if binding & LOCAL} - self.declarations.keys()
+
+
+class DeclarationChecker(ast.NodeVisitor):
+    """Report ``global`` and ``nonlocal`` statements conflicting with the other uses of their names.
+
+    Like CPython's symbol table, the names bound before a declaration are reported at the declaration,
+    and the declarations conflicting with each other only once the whole tree is read. Then the names
+    declared ``nonlocal`` must be bound in an enclosing function, comprehensions and lambdas included,
+    skipping classes and the functions declaring them ``global``.
+    """
+
+    def __init__(self, error: Callable[[str, ast.AST], NoReturn] = raise_error) -> None:
+        self.error = error
+        self.scopes: list[Scope] = []
+        self.future_annotations = False
+        self.deferred: tuple[str, ast.AST] | None = None
+
+    def check(self, tree: ast.AST) -> None:
+        if isinstance(tree, ast.Module):
+            self.future_annotations = has_future_annotations(tree)
+        self.scopes.append(module := Scope(ScopeKind.MODULE))
+        self.generic_visit(tree)
+        self.scopes.pop()
+        if self.deferred:
+            self.error(*self.deferred)
+        self.check_nonlocals(module, None)
+
+    def check_nonlocals(self, scope: Scope, bound: set[str] | None) -> None:
+        """Check that the nonlocal names of a scope and the nested ones are *bound* by enclosing functions."""
+        inherited = set(bound or ())
+        for name in scope.bindings:
+            keyword, node = scope.declarations.get(name, ("", None))
+            if keyword == "global" and bound:
+                bound.discard(name)
+            elif keyword == "nonlocal" and scope.kind != ScopeKind.MODULE and name not in (bound or ()):
+                self.error(f"no binding for nonlocal '{name}' found", node)  # type: ignore[arg-type]
+        if scope.kind == ScopeKind.CLASS:
+            children_bound = inherited | {"__class__"}  # names bound in classes are not seen by methods
+        elif scope.kind in FUNCTION_SCOPES:
+            children_bound = scope.local_names() | (bound or set())
+        else:
+            children_bound = set()
+        for child in scope.children:
+            self.check_nonlocals(child, set(children_bound))
+
+    def visit_scope(self, kind: ScopeKind, *nodes: ast.AST, parameters: ast.arguments | None = None) -> None:
+        self.scopes[-1].children.append(scope := Scope(kind))
+        self.scopes.append(scope)
+        if args := parameters:
+            for arg in (*args.posonlyargs, *args.args, args.vararg, *args.kwonlyargs, args.kwarg):
+                if arg:
+                    scope.bind(arg.arg, Binding.PARAMETER)
+        for node in nodes:
+            self.visit(node)
+        self.scopes.pop()
+
+    def defer(self, message: str, node: ast.AST) -> None:
+        if self.deferred is None:
+            self.deferred = (message, node)
+
+    def visit_declaration(self, node: ast.Global | ast.Nonlocal) -> None:
+        keyword = "global" if isinstance(node, ast.Global) else "nonlocal"
+        scope = self.scopes[-1]
+        for name in node.names:
+            bindings = scope.bindings.get(name, Binding(0))
+            for binding, message in BOUND_BEFORE_DECLARATION.items():
+                if binding in bindings:
+                    self.error(message.format(name, keyword), node)
+            scope.bind(name, Binding(0))
+            first_keyword, first = scope.declarations.setdefault(name, (keyword, node))
+            if first_keyword != keyword:
+                self.defer(f"name '{name}' is nonlocal and global", first)
+        if keyword == "nonlocal" and scope.kind == ScopeKind.MODULE:
+            self.defer("nonlocal declaration not allowed at module level", node)
+
+    visit_Global = visit_Nonlocal = visit_declaration  # noqa: N815
+
+    def visit_Name(self, node: ast.Name) -> None:
+        self.scopes[-1].bind(node.id, Binding.USED if isinstance(node.ctx, ast.Load) else Binding.ASSIGNED)
+
+    def visit_NamedExpr(self, node: ast.NamedExpr) -> None:
+        self.visit(node.value)
+        # the target of an assignment expression is bound in the scope enclosing the comprehensions
+        scope = next(scope for scope in reversed(self.scopes) if scope.kind != ScopeKind.COMPREHENSION)
+        scope.bind(node.target.id, Binding.ASSIGNED)
+
+    def visit_annotation(self, node: ast.expr | None) -> None:
+        if node is not None and not self.future_annotations:
+            self.visit(node)
+
+    def visit_FunctionDef(self, node: ast.FunctionDef | ast.AsyncFunctionDef) -> None:
+        self.scopes[-1].bind(node.name, Binding.ASSIGNED)
+        for expr in (*node.decorator_list, *node.args.defaults, *node.args.kw_defaults):
+            if expr:
+                self.visit(expr)
+        args = node.args
+        for arg in (*args.posonlyargs, *args.args, args.vararg, *args.kwonlyargs, args.kwarg):
+            if arg and isinstance(arg.annotation, ast.Starred):
+                self.visit_annotation(arg.annotation.value)  # as in ``*args: *Ts``
+            elif arg:
+                self.visit_annotation(arg.annotation)
+        self.visit_annotation(node.returns)
+        self.visit_scope(ScopeKind.FUNCTION, *node.body, parameters=node.args)
+
+    visit_AsyncFunctionDef = visit_FunctionDef  # noqa: N815
+
+    def visit_Lambda(self, node: ast.Lambda) -> None:
+        for default in (*node.args.defaults, *node.args.kw_defaults):
+            if default:
+                self.visit(default)
+        self.visit_scope(ScopeKind.LAMBDA, node.body, parameters=node.args)
+
+    def visit_ClassDef(self, node: ast.ClassDef) -> None:
+        self.scopes[-1].bind(node.name, Binding.ASSIGNED)
+        for expr in (*node.decorator_list, *node.bases, *node.keywords):
+            self.visit(expr)
+        self.visit_scope(ScopeKind.CLASS, *node.body)
+
+    def visit_AnnAssign(self, node: ast.AnnAssign) -> None:
+        target = node.target
+        scope = self.scopes[-1]
+        if isinstance(target, ast.Name) and node.simple:
+            if target.id in scope.declarations and scope.kind != ScopeKind.MODULE:
+                keyword = scope.declarations[target.id][0]
+                self.error(BOUND_BEFORE_DECLARATION[Binding.ANNOTATED].format(target.id, keyword), node)
+            scope.bind(target.id, Binding.ANNOTATED | Binding.ASSIGNED)
+        elif not isinstance(target, ast.Name):
+            self.visit(target)
+        elif node.value:
+            scope.bind(target.id, Binding.ASSIGNED)  # a parenthesized name is bound only by a value
+        self.visit_annotation(node.annotation)
+        if node.value:
+            self.visit(node.value)
+
+    def visit_comprehension_scope(self, node: Comprehension) -> None:
+        first, *rest = node.generators
+        self.visit(first.iter)
+        elts = (node.key, node.value) if isinstance(node, ast.DictComp) else (node.elt,)
+        self.visit_scope(ScopeKind.COMPREHENSION, first.target, *first.ifs, *rest, *elts)
+
+    visit_ListComp = visit_SetComp = visit_DictComp = visit_GeneratorExp = visit_comprehension_scope  # noqa: N815
+
+    def visit_bound_name(self, node: ast.ExceptHandler | ast.MatchAs | ast.MatchStar) -> None:
+        if node.name:
+            self.scopes[-1].bind(node.name, Binding.ASSIGNED)
+        self.generic_visit(node)
+
+    visit_ExceptHandler = visit_MatchAs = visit_MatchStar = visit_bound_name  # noqa: N815
+
+    def visit_alias(self, node: ast.alias) -> None:
+        if node.name != "*":
+            self.scopes[-1].bind(node.asname or node.name.partition(".")[0], Binding.IMPORTED)
+
+    def visit_MatchMapping(self, node: ast.MatchMapping) -> None:
+        if node.rest:
+            self.scopes[-1].bind(node.rest, Binding.ASSIGNED)
+        self.generic_visit(node)
+
+
+class ScopeChecker(ast.NodeVisitor):
+    """Report ``await`` used where the enclosing scope cannot suspend, as CPython's compiler does.
+
+    Starred expressions used as values outside of tuples, lists, sets and the arguments of calls, like
+    ``x = *y`` or ``return *x``, are reported too. Defaults, decorators, class bases and evaluated
+    annotations belong to the enclosing scope. The declarations are checked first by
+    `DeclarationChecker`, as CPython builds its symbol table before compiling.
+    """
+
+    def __init__(self, error: Callable[[str, ast.AST], NoReturn] = raise_error) -> None:
+        self.error = error
+        self.scopes: list[ScopeKind] = []
+        self.future_annotations = False
+
+    def check(self, tree: ast.AST) -> None:
+        DeclarationChecker(self.error).check(tree)
+        if isinstance(tree, ast.Module):
+            self.future_annotations = has_future_annotations(tree)
+        self.scopes.append(ScopeKind.MODULE)
+        self.generic_visit(tree)
+        self.scopes.pop()
+
+    def visit_scope(self, kind: ScopeKind, *nodes: ast.AST) -> None:
+        self.scopes.append(kind)
+        for node in nodes:
+            self.visit(node)
+        self.scopes.pop()
+
+    def visit_annotation(self, node: ast.expr | None) -> None:
+        if node is None:
+            return
+        if self.future_annotations:
+            if awaited := next((child for child in ast.walk(node) if isinstance(child, ast.Await)), None):
+                self.error(AWAIT_IN_ANNOTATION, awaited)
+            return
+        self.visit(node)
+
+    def visit_arguments(self, node: ast.arguments) -> None:
+        for default in (*node.defaults, *node.kw_defaults):
+            if default:
+                self.visit(default)
+
+    def visit_FunctionDef(self, node: ast.FunctionDef | ast.AsyncFunctionDef) -> None:
+        for decorator in node.decorator_list:
+            self.visit(decorator)
+        self.visit_arguments(node.args)
+        args = node.args
+        for arg in (*args.posonlyargs, *args.args, args.vararg, *args.kwonlyargs, args.kwarg):
+            if arg and isinstance(arg.annotation, ast.Starred):
+                self.visit_annotation(arg.annotation.value)  # as in ``*args: *Ts``
+            elif arg:
+                self.visit_annotation(arg.annotation)
+        self.visit_annotation(node.returns)
+        kind = ScopeKind.ASYNC_FUNCTION if isinstance(node, ast.AsyncFunctionDef) else ScopeKind.FUNCTION
+        self.visit_scope(kind, *node.body)
+
+    visit_AsyncFunctionDef = visit_FunctionDef  # noqa: N815
+
+    def visit_Lambda(self, node: ast.Lambda) -> None:
+        self.visit_arguments(node.args)
+        self.visit_scope(ScopeKind.LAMBDA, node.body)
+
+    def visit_ClassDef(self, node: ast.ClassDef) -> None:
+        for expr in node.decorator_list:
+            self.visit(expr)
+        self.visit_unpacked(node.bases)
+        for keyword in node.keywords:
+            self.visit(keyword)
+        self.visit_scope(ScopeKind.CLASS, *node.body)
+
+    def visit_AnnAssign(self, node: ast.AnnAssign) -> None:
+        self.visit(node.target)
+        if self.future_annotations or self.scopes[-1] in (ScopeKind.MODULE, ScopeKind.CLASS):
+            self.visit_annotation(node.annotation)  # annotations of locals are never evaluated
+        if node.value:
+            self.visit(node.value)
+
+    def visit_comprehension_scope(self, node: Comprehension) -> None:
+        first, *rest = node.generators
+        self.visit(first.iter)
+        if (
+            not isinstance(node, ast.GeneratorExp)
+            and self.scopes[-1] not in (ScopeKind.ASYNC_FUNCTION, ScopeKind.COMPREHENSION)
+            and is_async_comprehension(node)
+        ):
+            self.error("asynchronous comprehension outside of an asynchronous function", node)
+        elts = (node.key, node.value) if isinstance(node, ast.DictComp) else (node.elt,)
+        self.visit_scope(ScopeKind.COMPREHENSION, first.target, *first.ifs, *rest, *elts)
+
+    visit_ListComp = visit_SetComp = visit_DictComp = visit_GeneratorExp = visit_comprehension_scope  # noqa: N815
+
+    def visit_unpacked(self, nodes: list[ast.expr]) -> None:
+        """Visit the items of a display or the arguments of a call, where starred expressions unpack."""
+        for node in nodes:
+            self.visit(node.value if isinstance(node, ast.Starred) else node)
+
+    def visit_Tuple(self, node: ast.Tuple | ast.List | ast.Set) -> None:
+        self.visit_unpacked(node.elts)
+
+    visit_List = visit_Set = visit_Tuple  # noqa: N815
+
+    def visit_Call(self, node: ast.Call) -> None:
+        self.visit(node.func)
+        self.visit_unpacked(node.args)
+        for keyword in node.keywords:
+            self.visit(keyword)
+
+    def visit_Starred(self, node: ast.Starred) -> None:
+        if isinstance(node.ctx, ast.Load):
+            self.error(STARRED_HERE, node)
+        self.generic_visit(node)
+
+    def visit_Await(self, node: ast.Await) -> None:
+        scope = self.scopes[-1]
+        if scope in (ScopeKind.MODULE, ScopeKind.CLASS):
+            self.error("'await' outside function", node)
+        elif scope in (ScopeKind.FUNCTION, ScopeKind.LAMBDA):
+            self.error("'await' outside async function", node)
+        self.generic_visit(node)
+
+
+def has_future_annotations(tree: ast.Module) -> bool:
+    body = tree.body
+    if body and isinstance(body[0], ast.Expr) and isinstance(body[0].value, ast.Constant):
+        body = body[1:]  # docstring
+    for stmt in body:
+        if not (isinstance(stmt, ast.ImportFrom) and stmt.module == "__future__"):
+            return False
+        if any(alias.name == "annotations" for alias in stmt.names):
+            return True
+    return False
+
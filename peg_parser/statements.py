@@ -0,0 +1,121 @@
+"""Splitting of a source into its top level statements from the tokens alone, like xonsh does for history.
+
+No tree is built, so a paste can be split before it is parsed, and statements before an incomplete one
+can still be run.
+"""
+
+from __future__ import annotations
+
+import io
+from typing import NamedTuple
+
+from .tokenize import Token, TokenError, generate_tokens
+
+# the keywords of the clauses continuing a compound statement at its indentation level
+CONTINUATIONS = {"elif", "else", "except", "finally"}
+
+
+class StatementRange(NamedTuple):
+    """The range of a top level statement, from its first token to the end of its last line."""
+
+    start: int
+    end: int
+    # False for a statement cut off by the end of the source, like an unclosed bracket or a block
+    # without its body, or by a token error like an unterminated single quoted string
+    complete: bool
+
+
+def split_statements(source: str) -> list[StatementRange]:
+    """The ranges of the top level statements of a source, with offsets counting characters.
+
+    The offsets index the source string, not its UTF-8 bytes. Compound statements are a single range
+    along with their indented bodies, decorators and clauses. Comments and blank lines between
+    statements are left out. A statement that cannot be tokenized is an incomplete range running to the
+    end of the source.
+    """
+    offsets = [0]
+    for line in io.StringIO(source):
+        offsets.append(offsets[-1] + len(line))
+
+    def offset(position: tuple[int, int]) -> int:
+        lineno, col = position
+        return min(offsets[lineno - 1] + col, len(source)) if lineno <= len(offsets) else len(source)
+
+    ranges: list[StatementRange] = []
+    start: int | None = None
+    end = 0  # of the last logical line read
+    level = 0
+    line_start = True
+    first = last = ""  # strings of the first and last tokens of the logical line
+    try:
+        for tok in generate_tokens(source, skip_trivia=True):
+            if tok.type == Token.INDENT:
+                level += 1
+            elif tok.type == Token.DEDENT:
+                level -= 1
+            elif tok.type == Token.NEWLINE:
+                line_start = True
+                end = offset(tok.end)
+            elif tok.type == Token.ENDMARKER:
+                break
+            else:
+                if line_start:
+                    if start is not None and level == 0 and first != "@" and tok.string not in CONTINUATIONS:
+                        ranges.append(StatementRange(start, end, True))
+                        start = None
+                    if start is None:
+                        start = offset(tok.start)
+                    first = tok.string
+                line_start = False
+                last = tok.string
+                if tok.type == Token.ERRORTOKEN and not tok.string.isspace():
+                    break  # like an unterminated single quoted string
+    except (TokenError, SyntaxError):
+        line_start = False
+        if start is None and (rest := source[end:].lstrip()):
+            start = len(source) - len(rest)
+    if start is not None:
+        complete = line_start and level == 0 and last != ":" and first != "@"
+        ranges.append(StatementRange(start, end if complete else len(source), complete))
+    return ranges
+
+
+def block_complete(source: str) -> bool | None:
+    """Whether all the blocks of an interactive input are closed, from its tokens alone.
+
+    Unlike `split_statements`, a block is only closed by a line back at the indentation of the first
+    one or by a blank line, as in a REPL, so a source ending within an indented body is not complete.
+    The result is None when the source cannot be tokenized, and False when it ends within brackets, a
+    string, an indented block, after a backslash or with a clause header or decorator waiting for what
+    follows.
+    """
+    level = 0
+    first = last = ""  # strings of the first and last tokens of the logical line
+    complete = True  # whether the input would be complete after the last logical line
+    waiting = False  # whether the last logical line is a clause header or a decorator
+    try:
+        for tok in generate_tokens(source, skip_trivia=True):
+            if tok.type == Token.INDENT:
+                level += 1
+            elif tok.type == Token.DEDENT:
+                level -= 1
+            elif tok.type == Token.NEWLINE:
+                waiting = last == ":" or first == "@"
+                complete = level == 0 and not waiting
+                first = ""
+            elif tok.type == Token.ENDMARKER:
+                break
+            elif tok.type == Token.ERRORTOKEN and not tok.string.isspace():
+                return None  # like an unterminated single quoted string
+            else:
+                first = first or tok.string
+                last = tok.string
+    except TokenError as e:
+        return False if e.args[0] in ("EOF in multi-line statement", "EOF in multi-line string") else None
+    except SyntaxError:
+        return None
+    if not complete and not waiting:
+        # the blank line entered after a body closes all its blocks
+        *_, line = source.splitlines(keepends=True)
+        complete = line.isspace() and line.endswith(("\n", "\r"))
+    return complete
@@ -1,12 +1,17 @@
 from __future__ import annotations
 
 import ast
+import codecs
+import copy
 import enum
+import re
 import sys
+import warnings
 from collections.abc import Callable
-from typing import TYPE_CHECKING, Any, ClassVar, Literal, NoReturn, TypeVar, cast
+from typing import TYPE_CHECKING, Any, ClassVar, Literal, NamedTuple, NoReturn, TypeVar, cast
 
-from peg_parser.tokenize import Token, TokenInfo, generate_tokens
+from peg_parser.scopes import ScopeChecker
+from peg_parser.tokenize import TabPolicy, Token, TokenInfo, Trivia, accepts_pep701, generate_tokens
 from peg_parser.tokenizer import Mark, Tokenizer
 
 if TYPE_CHECKING:
@@ -54,6 +59,280 @@ T = TypeVar("T")
 P = TypeVar("P", bound="Parser")
 F = TypeVar("F", bound=Callable[..., Any])
 
+# longer source lines are cut around the error column when reported
+MAX_ERROR_LINE = 512
+
+# the nodes of newer Pythons, with the version adding them, probed on the ast module when first built
+VERSIONED_NODES = {
+    "TryStar": (3, 11),
+    "TypeAlias": (3, 12),
+    "TypeVar": (3, 12),
+    "ParamSpec": (3, 12),
+    "TypeVarTuple": (3, 12),
+}
+
+STRING_PREFIX_LETTERS = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
+
+# invalid escapes in strings warn at compile time, as SyntaxWarning since Python 3.12
+ESCAPE_WARNING = SyntaxWarning if sys.version_info >= (3, 12) else DeprecationWarning
+ESCAPE = re.compile(r"\\([0-7]{1,3}|.)", re.DOTALL)
+# words that may follow a number without a space, like ``1if x else y``
+NUMBER_END_KEYWORDS = ("and", "else", "for", "if", "in", "is", "not", "or")
+LEADING_ZEROS = (
+    "leading zeros in decimal integer literals are not permitted; use an 0o prefix for octal integers"
+)
+# an escaped ASCII character is left alone, so that a backslash is never read as escaping the next one
+NON_ASCII = re.compile(r"\\[\x00-\x7f]|\\?[^\x00-\x7f]", re.DOTALL)
+
+
+def literal_eval(source: str) -> Any:
+    """`ast.literal_eval` that does not call `ast.parse`, which may be patched to use this parser."""
+    return ast.literal_eval(compile(source, "<unknown>", "eval", ast.PyCF_ONLY_AST))
+
+
+def escape_non_ascii(match: re.Match[str]) -> str:
+    text = match[0]
+    if text[-1].isascii():
+        return text
+    # as in CPython, a backslash before the character stays one
+    return ("\\u005c" if text[0] == "\\" else "") + f"\\U{ord(text[-1]):08x}"
+
+
+def decode_escapes(text: str, is_bytes: bool = False) -> str | bytes:
+    """Decode the escape sequences of the text of a non-raw literal, the way CPython does.
+
+    Invalid escapes keep their backslash, without warning as the parser reports them. Malformed ones raise
+    the `UnicodeDecodeError` (or `ValueError` for bytes) of the codec, with the positions of CPython's
+    messages: the other non-ASCII characters are passed to it as ``\\U`` escapes.
+    """
+    if not is_bytes and "\\" not in text:
+        return text
+    with warnings.catch_warnings():
+        warnings.simplefilter("ignore", DeprecationWarning)
+        if is_bytes:
+            return cast(bytes, codecs.escape_decode(text.encode())[0])  # type: ignore[attr-defined]
+        if not text.isascii():
+            text = NON_ASCII.sub(escape_non_ascii, text)
+        return text.encode("ascii").decode("unicode_escape")
+
+
+def number_literal_error(text: str) -> tuple[str, int, int] | None:
+    """The message and columns of CPython's error about the number starting *text*, if it has one.
+
+    This follows the number reading of CPython's tokenizer, which rejects letters, digits and underscores
+    that the tokenizer here leaves to the next token. The columns are those of the SyntaxError, counted
+    from the start of *text*.
+    """
+    pos = 0
+
+    def nextc() -> str:
+        nonlocal pos
+        pos += 1
+        return text[pos - 1 : pos]
+
+    def error(message: str) -> tuple[str, int, int]:
+        # reported after the last character read, like the tokenizer does
+        return message, pos, pos
+
+    def end_error(c: str, kind: str) -> tuple[str, int, int] | None:
+        nonlocal pos
+        pos -= 1
+        rest = text[pos:]
+        if c and c.isascii() and (c.isalnum() or c == "_") and not rest.startswith(NUMBER_END_KEYWORDS):
+            return error(f"invalid {kind} literal")
+        return None
+
+    def decimal_tail() -> str | tuple[str, int, int]:
+        nonlocal pos
+        while True:
+            c = nextc()
+            while c.isdigit():
+                c = nextc()
+            if c != "_":
+                return c
+            c = nextc()
+            if not c.isdigit():
+                pos -= 1
+                return error("invalid decimal literal")
+
+    def fraction(c: str) -> tuple[str, int, int] | None:
+        nonlocal pos
+        if c.isdigit():
+            c = decimal_tail()
+            if isinstance(c, tuple):
+                return c
+        if c in ("e", "E"):
+            return exponent()
+        if c in ("j", "J"):
+            return end_error(nextc(), "imaginary")
+        return end_error(c, "decimal")
+
+    def exponent() -> tuple[str, int, int] | None:
+        nonlocal pos
+        c = nextc()
+        if c in ("+", "-"):
+            c = nextc()
+            if not c.isdigit():
+                pos -= 1
+                return error("invalid decimal literal")
+        elif not c.isdigit():
+            pos -= 1
+            return end_error("e", "decimal")
+        c = decimal_tail()
+        if isinstance(c, tuple):
+            return c
+        if c in ("j", "J"):
+            return end_error(nextc(), "imaginary")
+        return end_error(c, "decimal")
+
+    c = nextc()
+    if c == ".":
+        return fraction(nextc())
+    if c == "0":
+        c = nextc()
+        base = {"x": ("hexadecimal", 16), "o": ("octal", 8), "b": ("binary", 2)}.get(c.lower())
+        if base:
+            kind, radix = base
+            c = nextc()
+            while True:
+                if c == "_":
+                    c = nextc()
+                if not is_digit(c, radix):
+                    if radix < 10 and c.isdigit():
+                        return error(f"invalid digit '{c}' in {kind} literal")
+                    pos -= 1
+                    return error(f"invalid {kind} literal")
+                while is_digit(c, radix):
+                    c = nextc()
+                if c != "_":
+                    break
+            if radix < 10 and c.isdigit():
+                return error(f"invalid digit '{c}' in {kind} literal")
+            return end_error(c, kind)
+        # only zeros may follow a leading zero, unless it is a float or imaginary
+        while True:
+            if c == "_":
+                c = nextc()
+                if not c.isdigit():
+                    pos -= 1
+                    return error("invalid decimal literal")
+            if c != "0":
+                break
+            c = nextc()
+        zeros_end = pos
+        nonzero = c.isdigit()
+        if nonzero:
+            c = decimal_tail()
+            if isinstance(c, tuple):
+                return c
+        if c == ".":
+            return fraction(nextc())
+        if c in ("e", "E"):
+            return exponent()
+        if c in ("j", "J"):
+            return end_error(nextc(), "imaginary")
+        if nonzero:
+            return LEADING_ZEROS, 1, zeros_end
+        return end_error(c, "decimal")
+    c = decimal_tail()
+    if isinstance(c, tuple):
+        return c
+    if c == ".":
+        return fraction(nextc())
+    if c in ("e", "E"):
+        return exponent()
+    if c in ("j", "J"):
+        return end_error(nextc(), "imaginary")
+    return end_error(c, "decimal")
+
+
+def is_digit(c: str, radix: int) -> bool:
+    return bool(c) and c in "0123456789abcdefABCDEF"[: radix + (radix > 10) * 6]
+
+
+def error_line_text(line: str, col: int, end_col: int | None) -> tuple[str, int, int | None]:
+    """Get the text reported for a SyntaxError and its columns within it.
+
+    Only the first line is kept, always ending with a single ``\\n``. Lines longer than
+    ``MAX_ERROR_LINE`` are cut around ``col`` with ``...`` marking the removed parts.
+    """
+    text = line.partition("\n")[0].rstrip("\r")
+    if not text:
+        return line, col, end_col
+    if len(text) > MAX_ERROR_LINE:
+        right = min(len(text), max(col, 0) + MAX_ERROR_LINE // 2)
+        left = max(0, right - MAX_ERROR_LINE)
+        shift = left - (3 if left else 0)
+        text = ("..." if left else "") + text[left:right] + ("..." if right < len(text) else "")
+        col -= shift
+        if end_col is not None:
+            end_col = min(end_col, right) - shift
+    return text + "\n", col, end_col
+
+
+def format_error(error: SyntaxError) -> str:
+    """Format a SyntaxError like `traceback.format_exception_only`, with its line and a caret under its span.
+
+    The carets run to ``end_offset`` when the error ends on the line it starts on.
+    """
+    lines = [f'  File "{error.filename or "<string>"}", line {error.lineno}\n']
+    text = (error.text or "").partition("\n")[0].rstrip()
+    if text:
+        stripped = text.lstrip(" \f")
+        lines.append(f"    {stripped}\n")
+        if error.offset is not None:
+            spaces = len(text) - len(stripped)
+            start = max(error.offset - 1 - spaces, 0)
+            end = start + 1
+            same_line = error.end_lineno in (None, error.lineno)
+            if same_line and error.end_offset and error.end_offset > error.offset:
+                end = error.end_offset - 1 - spaces
+            lines.append("    " + "".join(c if c.isspace() else " " for c in stripped[:start]))
+            lines.append("^" * (end - start) + "\n")
+    lines.append(f"{type(error).__name__}: {error.msg}\n")
+    return "".join(lines)
+
+
+class ParseWarning(NamedTuple):
+    """A warning about legal but questionable code, reported by `peg_parser.parse` with ``warnings=True``."""
+
+    category: type[Warning]
+    message: str
+    lineno: int
+    col_offset: int
+
+
+def invalid_escapes(text: str, is_bytes: bool) -> Iterator[tuple[int, str]]:
+    """Yield the index and message of each invalid escape sequence of the text of a non-raw literal."""
+    valid = "\n\r\\'\"abfnrtvx" if is_bytes else "\n\r\\'\"abfnrtvxNuU"
+    for match in ESCAPE.finditer(text):
+        sequence = match.group(1)
+        if sequence[0] in "01234567":
+            if int(sequence, 8) > 0o377:
+                yield match.start(), f"invalid octal escape sequence '\\{sequence}'"
+        elif sequence not in valid:
+            yield match.start(), f"invalid escape sequence '\\{sequence}'"
+
+
+def literal_type(node: ast.expr) -> str | None:
+    """The type name of an expression the compiler folds into a constant, like ``-1`` or ``(1, 'a')``."""
+    if isinstance(node, ast.Constant):
+        return type(node.value).__name__
+    if isinstance(node, ast.UnaryOp) and isinstance(node.op, (ast.UAdd, ast.USub, ast.Invert)):
+        operand = node.operand
+        numeric = isinstance(operand, ast.Constant) and isinstance(operand.value, (int, float, complex))
+        return literal_type(operand) if numeric else None
+    if isinstance(node, ast.Tuple) and all(literal_type(elt) for elt in node.elts):
+        return "tuple"
+    return None
+
+
+def decode_fstring_middle(text: str, raw: bool, braces: bool = True) -> str:
+    """The value of the literal text of an f-string, with doubled braces collapsed and escapes decoded."""
+    if braces:
+        text = text.replace("{{", "{").replace("}}", "}")
+    return text if raw else cast(str, decode_escapes(text))
+
 
 def logger(method: F) -> F:
     """For non-memoized functions that we want to be logged.
@@ -219,12 +498,33 @@ def xonsh_call(name: str, *args: Node, **locs: int) -> ast.Call:
         func=load_attribute_chain(name, **locs),
         args=list(args),
         keywords=[],
-        starargs=None,
-        kwargs=None,
         **locs,
     )
 
 
+def named_exprs(
+    node: ast.AST, in_lambda: bool = True, nested: bool = False
+) -> Iterator[tuple[ast.NamedExpr, bool]]:
+    """Yield the assignment expressions within a node in source order.
+
+    Each one comes with whether it sits in a comprehension nested in ``node``.
+    """
+    if isinstance(node, ast.NamedExpr):
+        yield node, nested
+    elif isinstance(node, ast.Lambda) and not in_lambda:
+        return
+    nested = nested or isinstance(node, ast.ListComp | ast.SetComp | ast.DictComp | ast.GeneratorExp)
+    for child in ast.iter_child_nodes(node):
+        yield from named_exprs(child, in_lambda, nested)
+
+
+def walk_in_order(node: ast.AST) -> Iterator[ast.AST]:
+    """Like `ast.walk`, but depth first so nodes come in source order."""
+    yield node
+    for child in ast.iter_child_nodes(node):
+        yield from walk_in_order(child)
+
+
 class Target(enum.Enum):
     FOR_TARGETS = enum.auto()
     STAR_TARGETS = enum.auto()
@@ -245,6 +545,7 @@ class Parser:
         verbose: bool = False,
         filename: str = "<unknown>",
         py_version: tuple[int, ...] | None = None,
+        xonsh_operators: bool = True,
     ) -> None:
         self._tokenizer = tokenizer
         self._verbose = verbose
@@ -266,7 +567,107 @@ class Parser:
         self.call_invalid_rules = False
 
         self.filename = filename
+        self._tokenizer.filename = filename
         self.py_version = min(py_version, sys.version_info) if py_version else sys.version_info
+        if self.py_version < (3, 7):
+            # async and await are names outside of async functions, see `Tokenizer.async_keyword`
+            self._tokenizer.async_keywords = False
+            self.KEYWORDS = tuple(kw for kw in self.KEYWORDS if kw not in ("async", "await"))
+            self.expect = self.expect_async_keyword  # type: ignore[method-assign]
+        # Accept '&&' and '||' as spellings of 'and' and 'or'
+        self.xonsh_operators = xonsh_operators
+
+        # Receives the nodes of each parsed top level statement, see `peg_parser.parse_events`
+        self.event_callback: Callable[[str, dict[str, int], ast.AST], object] | None = None
+        self.event_kinds: tuple[type[ast.AST], ...] = ()
+
+        # Locations of the syntax features used, when asked by `peg_parser.detect_features`
+        self.features: dict[str, list[dict[str, int]]] | None = None
+
+        # Spans of the grouping parentheses, when asked by `peg_parser.paren_map`
+        self.groups: set[tuple[int, int, int, int]] | None = None
+
+        # Legal but questionable code found while parsing, see `check_warnings`
+        self.warnings: list[ParseWarning] = []
+
+        # The values of the number and string literals read, by their text, as generated tables repeat them
+        self._literals: dict[str, Any] = {}
+
+    def parenthesized_with(
+        self, node: ast.With | ast.AsyncWith, parens: tuple[TokenInfo, TokenInfo] | None
+    ) -> ast.With | ast.AsyncWith:
+        """``with (a as b, c):`` needs Python 3.10, unlike a parenthesized tuple of context managers.
+
+        The *parens*, unless followed by a trailing comma, only group a single context manager.
+        """
+        if any(item.optional_vars for item in node.items):
+            self.record_feature("parenthesized_context_managers", node)
+        elif len(node.items) == 1 and parens:
+            self.grouped(node, **parens[0].loc_start(), **parens[1].loc_end())
+        return node
+
+    def record_feature(self, name: str, node: Node) -> Node:
+        """Note the use of a feature only visible in the syntax, like xonsh operators."""
+        if self.features is not None and not self.call_invalid_rules:
+            location = {attr: getattr(node, attr) for attr in node._attributes}
+            found = self.features.setdefault(name, [])
+            if location not in found:  # actions run again after backtracking
+                found.append(location)
+        return node
+
+    def grouped(self, node: Node, **location: int) -> Node:
+        """Note the span of parentheses only grouping a node, which leave no trace in the tree."""
+        if self.groups is not None and not self.call_invalid_rules:
+            keys = ("lineno", "col_offset", "end_lineno", "end_col_offset")
+            self.groups.add(tuple(location[key] for key in keys))  # type: ignore[arg-type]
+        return node
+
+    def warn(self, category: type[Warning], message: str, lineno: int, col_offset: int) -> None:
+        entry = ParseWarning(category, message, lineno, col_offset)
+        if entry not in self.warnings:  # actions run again after backtracking
+            self.warnings.append(entry)
+
+    def check_escapes(self, text: str, start: tuple[int, int], is_bytes: bool = False) -> None:
+        """Warn about the invalid escape sequences of the text of a non-raw literal starting at *start*."""
+        for index, message in invalid_escapes(text, is_bytes):
+            line = text.count("\n", 0, index)
+            col = index - text.rfind("\n", 0, index) - 1 if line else start[1] + index
+            self.warn(ESCAPE_WARNING, message, start[0] + line, col)
+
+    def check_warnings(self, tree: ast.AST, pedantic: bool = False) -> None:
+        """Warn about ``is`` comparisons to literals and assertions that are always true, like the compiler.
+
+        With *pedantic*, ``==`` and ``!=`` comparisons to None, True and False are reported too.
+        """
+        for node in ast.walk(tree):
+            if isinstance(node, ast.Assert) and isinstance(node.test, ast.Tuple) and node.test.elts:
+                message = "assertion is always true, perhaps remove parentheses?"
+                self.warn(SyntaxWarning, message, node.lineno, node.col_offset)
+            elif isinstance(node, ast.Compare):
+                for left, op, right in zip([node.left, *node.comparators], node.ops, node.comparators):
+                    if message := self._comparison_warning(left, op, right, pedantic):
+                        self.warn(SyntaxWarning, message, node.lineno, node.col_offset)
+
+    @staticmethod
+    def _comparison_warning(left: ast.expr, op: ast.cmpop, right: ast.expr, pedantic: bool) -> str | None:
+        singletons = [
+            side
+            for side in (left, right)
+            if isinstance(side, ast.Constant) and any(side.value is each for each in (None, True, False, ...))
+        ]
+        if isinstance(op, (ast.Is, ast.IsNot)):
+            literals = [side for side in (left, right) if literal_type(side) and side not in singletons]
+            if not literals:
+                return None
+            literal = literals[0]
+            name, suggestion = ("is", "==") if isinstance(op, ast.Is) else ("is not", "!=")
+            kind = f"'{literal_type(literal)}'" if sys.version_info >= (3, 12) else "a"
+            return f'"{name}" with {kind} literal. Did you mean "{suggestion}"?'
+        compared = [side for side in singletons if side.value is not ...]
+        if pedantic and compared and isinstance(op, (ast.Eq, ast.NotEq)):
+            name, suggestion = ("==", "is") if isinstance(op, ast.Eq) else ("!=", "is not")
+            return f'"{name}" with {compared[0].value}. Did you mean "{suggestion}"?'
+        return None
 
     def showpeek(self) -> str:
         tok = self._tokenizer.peek()
@@ -305,6 +706,13 @@ class Parser:
             return self._tokenizer.getnext()
         return None
 
+    def expect_async_keyword(self, typ: str) -> TokenInfo | None:
+        """`expect`, but not matching async and await where they are names, before Python 3.7."""
+        tok = self._tokenizer.peek()
+        if tok.string == typ and (tok.type != Token.NAME or typ not in ("async", "await")):
+            return self._tokenizer.getnext()
+        return None
+
     def repeated(self, func: Callable[..., T | None], *args: Any) -> list[T]:
         mark = self._mark()
         children = []
@@ -389,6 +797,11 @@ class Parser:
 
                 res = getattr(self, rule)()
 
+            if last_token.type in (Token.INDENT, Token.DEDENT):
+                # reported like CPython, at the end of the indentation and without an end column
+                msg = "unexpected indent" if last_token.type == Token.INDENT else "unexpected unindent"
+                (lineno, col), line = last_token.end, last_token.line
+                raise IndentationError(msg, (self.filename, lineno, col, line, lineno, -1))
             self.raise_raw_syntax_error("invalid syntax", last_token.start, last_token.end)
 
         return res
@@ -398,11 +811,39 @@ class Parser:
         if self.py_version >= min_version:
             return node
         else:
-            raise SyntaxError(f"{error_msg} is only supported in Python {min_version} and above.")
+            version = ".".join(map(str, min_version))
+            raise SyntaxError(f"{error_msg} only supported in Python {version} and greater")
+
+    def versioned_node(self, name: str, error_msg: str) -> Any:
+        """The class of a node of `VERSIONED_NODES`, once its version is checked as by `check_version`.
+
+        The ast module of the running Python must have the class too, which is checked apart from the
+        targeted version, as building the node would fail otherwise.
+        """
+        min_version = VERSIONED_NODES[name]
+        self.check_version(min_version, error_msg, None)
+        node_class = getattr(ast, name, None)  # probed here, as sources without the node need no probe
+        if node_class is None:
+            version = ".".join(map(str, min_version))
+            raise SyntaxError(
+                f"{error_msg} only supported in Python {version} and greater, this Python has no ast.{name}"
+            )
+        return node_class
+
+    def check_unpacking_version(self, elts: list[Any], error_msg: str, node: T) -> T:
+        """Check the version for the unpacking of PEP 646, in subscripts and ``*args`` annotations."""
+        if any(isinstance(elt, ast.Starred) for elt in elts):
+            return self.check_version((3, 11), error_msg, node)
+        return node
 
     def raise_indentation_error(self, msg: str) -> None:
         """Raise an indentation error."""
         last_token = self._tokenizer.diagnose()
+        if last_token.type in (Token.DEDENT, Token.ENDMARKER) and last_token.start[0] > 1:
+            # the block is missing at the end of the source, CPython points past the end of its last line
+            lineno = last_token.start[0] - 1
+            line = self._tokenizer.get_lines([lineno])[0].rstrip("\r\n")
+            raise IndentationError(msg, (self.filename, lineno, len(line) + 1, line + "\n", lineno, -1))
         args = (self.filename, last_token.start[0], last_token.start[1] + 1, last_token.line)
         args += (last_token.end[0], last_token.end[1] + 1)  # type: ignore
         raise IndentationError(msg, args)
@@ -466,14 +907,34 @@ class Parser:
         node.ctx = context
         return node
 
+    def number(self, tok: TokenInfo) -> int | float | complex:
+        """The value of a NUMBER token, after reporting the malformed literals that CPython rejects.
+
+        These split into more tokens, that a command may take as its arguments, like ``$[head -n 0123]``.
+        So they are only reported when the source failed to parse.
+        """
+        lineno, col = tok.start
+        if self.call_invalid_rules and (error := number_literal_error(tok.line[col:])):
+            message, start, end = error
+            raise self._build_syntax_error(message, (lineno, col + start - 1), (lineno, col + end - 1))
+        text = tok.string
+        if (value := self._literals.get(text)) is not None:
+            return cast(int | float | complex, value)
+        if text.isascii() and text.isdigit() and (text[0] != "0" or not text.strip("0")):
+            value = int(text)  # without compiling the common decimal integers
+        else:
+            value = literal_eval(text)
+        self._literals[text] = value
+        return cast(int | float | complex, value)
+
     def ensure_real(self, number: TokenInfo) -> float | int:
-        value = ast.literal_eval(number.string)
+        value = self.number(number)
         if not isinstance(value, float | int):
             self.raise_syntax_error_known_location("real number required in complex literal", number)
         return value
 
     def ensure_imaginary(self, number: TokenInfo) -> complex:
-        value = ast.literal_eval(number.string)
+        value = self.number(number)
         if not isinstance(value, complex):
             self.raise_syntax_error_known_location("imaginary number required in complex literal", number)
         return value
@@ -488,16 +949,121 @@ class Parser:
 
         return s.encode()[0]
 
+    def emit_events(self, stmts: list[ast.stmt]) -> list[ast.stmt]:
+        # Top level statements are final once parsed, unlike nested nodes which may be backtracked over.
+        if self.event_callback is None or self.call_invalid_rules:
+            return stmts
+        for stmt in stmts:
+            for node in walk_in_order(stmt):
+                if isinstance(node, self.event_kinds):
+                    location = {attr: getattr(node, attr) for attr in node._attributes}
+                    self.event_callback(type(node).__name__, location, node)
+        return stmts
+
+    def check_star_targets(self, target: Node, top: bool = True) -> Node:
+        """Allow one starred target per tuple or list and none outside of them, as CPython's compiler does."""
+        if isinstance(target, ast.Starred):
+            if top:
+                msg = "starred assignment target must be in a list or tuple"
+                self.raise_syntax_error_known_location(msg, target)
+            self.check_star_targets(target.value)
+        elif isinstance(target, ast.Tuple | ast.List):
+            if sum(isinstance(elt, ast.Starred) for elt in target.elts) > 1:
+                self.raise_syntax_error_known_location("multiple starred expressions in assignment", target)
+            for elt in target.elts:
+                self.check_star_targets(elt, top=False)
+        return target
+
+    def check_comprehension(self, node: T) -> T:
+        """Apply the PEP 572 restrictions on assignment expressions in comprehensions.
+
+        Checks are done in the order CPython's symbol table visits the comprehension.
+        """
+        comp = cast(ast.ListComp | ast.SetComp | ast.DictComp | ast.GeneratorExp, node)
+        iter_vars: set[str] = set()
+        targets: set[str] = set()
+
+        def check_iterable(expr: ast.AST) -> None:
+            for named, _ in named_exprs(expr):
+                self.raise_syntax_error_known_location(
+                    "assignment expression cannot be used in a comprehension iterable expression", named
+                )
+
+        def check_expr(expr: ast.AST) -> None:
+            for named, nested in named_exprs(expr, in_lambda=False):
+                name = cast(ast.Name, named.target)
+                if name.id in iter_vars:
+                    self.raise_syntax_error_known_location(
+                        f"assignment expression cannot rebind comprehension iteration variable '{name.id}'",
+                        name,
+                    )
+                if not nested:
+                    targets.add(name.id)
+
+        check_iterable(comp.generators[0].iter)
+        for idx, gen in enumerate(comp.generators):
+            for name in ast.walk(gen.target):
+                if not isinstance(name, ast.Name):
+                    continue
+                if name.id in targets:
+                    msg = f"comprehension inner loop cannot rebind assignment expression target '{name.id}'"
+                    self.raise_syntax_error_known_location(msg, name)
+                iter_vars.add(name.id)
+            if idx:
+                check_iterable(gen.iter)
+            for cond in gen.ifs:
+                check_expr(cond)
+        if isinstance(comp, ast.DictComp):
+            check_expr(comp.key)
+            check_expr(comp.value)
+        else:
+            check_expr(comp.elt)
+
+        for gen in comp.generators:
+            if isinstance(gen.target, ast.Starred):
+                self.raise_syntax_error_known_location(
+                    "starred assignment target must be in a list or tuple", gen.target
+                )
+        return node
+
+    def string_value(self, tok: TokenInfo) -> str | bytes:
+        """The value of a string token, with its escapes decoded unless it is raw."""
+        text = tok.string
+        if (value := self._literals.get(text)) is not None:
+            return cast(str | bytes, value)
+        self._literals[text] = value = self._string_value(tok)
+        return value
+
+    def _string_value(self, tok: TokenInfo) -> str | bytes:
+        text = tok.string
+        prefix = text[: len(text) - len(text.lstrip(STRING_PREFIX_LETTERS))].lower()
+        quote = text[len(prefix) : len(prefix) + 3]
+        quote = quote if quote in ('"""', "'''") else quote[0]
+        body = text[len(prefix) + len(quote) : -len(quote)]
+        if "\r" in body:
+            body = body.replace("\r\n", "\n").replace("\r", "\n")
+        is_bytes = "b" in prefix
+        if is_bytes and not body.isascii():
+            self.raise_syntax_error_known_location("bytes can only contain ASCII literal characters", tok)
+        if "r" in prefix:
+            return body.encode() if is_bytes else body
+        try:
+            return decode_escapes(body, is_bytes)
+        except UnicodeDecodeError as e:
+            self.raise_syntax_error_known_location(f"(unicode error) {e}", tok)
+        except ValueError as e:
+            self.raise_syntax_error_known_location(f"(value error) {e}", tok)
+
     def _concat_strings_in_constant(self, parts: list[TokenInfo]) -> ast.Constant:
-        s = ast.literal_eval(parts[0].string)
+        s = self.string_value(parts[0])
         for ss in parts[1:]:
-            s += ast.literal_eval(ss.string)
+            s += self.string_value(ss)  # type: ignore[operator]
         args = {
             "value": s,
             "lineno": parts[0].start[0],
             "col_offset": parts[0].start[1],
             "end_lineno": parts[-1].end[0],
-            "end_col_offset": parts[0].end[1],
+            "end_col_offset": parts[-1].end[1],
         }
         if parts[0].string.startswith("u"):
             args["kind"] = "u"
@@ -519,6 +1085,20 @@ class Parser:
         values: list[Any] = []  # ast.Constant | ast.FormattedValue
         ss: list[TokenInfo] = []
 
+        is_bytes = set()  # of each part, f-strings being str
+        for part in parts:
+            if isinstance(part, TokenInfo):
+                text = part.string
+                prefix = text[: len(text) - len(text.lstrip(STRING_PREFIX_LETTERS))].lower()
+                is_bytes.add("b" in prefix)
+                if "r" not in prefix:
+                    self.check_escapes(part.string, part.start, is_bytes="b" in prefix)
+            else:
+                is_bytes.add(False)
+        if len(is_bytes) > 1:
+            # at the token following the strings, as in CPython
+            self.raise_syntax_error("cannot mix bytes and nonbytes literals")
+
         if path_tok := (self._strip_path_prefix(parts[0])):
             parts[0] = path_tok
 
@@ -557,7 +1137,10 @@ class Parser:
             )
 
         if path_tok := (path_tok or self._path_token):
-            node = xonsh_call("__xonsh__.path_literal", node, **path_tok.loc())
+            # the call spans the whole literal, even when it starts with an f-string
+            locs = path_tok.loc_start()
+            locs.update(end_lineno=node.end_lineno, end_col_offset=node.end_col_offset)
+            node = xonsh_call("__xonsh__.path_literal", node, **locs)
             self._path_token = None
         return node
 
@@ -567,14 +1150,105 @@ class Parser:
         path_tok = self._strip_path_prefix(a)
         if path_tok:
             self._path_token = path_tok
-        return ast.JoinedStr(values=b, **locs)
+        return ast.JoinedStr(values=self.decode_fstring_values(b, raw="r" in a.string.lower()), **locs)
+
+    def decode_fstring_values(self, values: list[Any], raw: bool) -> list[Any]:
+        """Decode the literal parts of an f-string, including those within its format specifiers."""
+        decoded = []
+        for value in values:
+            if isinstance(value, ast.JoinedStr):  # the source text of a debug field, then its value
+                text, field = value.values
+                text = copy.copy(text)
+                text.value = decode_fstring_middle(text.value, raw, braces=False)
+                decoded += [text, *self.decode_fstring_values([field], raw)]
+                continue
+            value = copy.copy(value)
+            if isinstance(value, ast.Constant):
+                if not raw:
+                    self.check_escapes(value.value, (value.lineno, value.col_offset))
+                try:
+                    value.value = decode_fstring_middle(value.value, raw)
+                except UnicodeDecodeError as e:
+                    self.raise_syntax_error_known_location(f"(unicode error) {e}", value)
+            elif value.format_spec:
+                value.format_spec = copy.copy(value.format_spec)
+                value.format_spec.values = self.decode_fstring_values(value.format_spec.values, raw)
+            decoded.append(value)
+        return decoded
+
+    def add_type_comment(self, arg: ast.arg, type_comment: TokenInfo | None) -> ast.arg:
+        """Give a parameter the type comment following it, if any."""
+        if type_comment:
+            arg.type_comment = type_comment.string
+        return arg
+
+    def type_ignores(self) -> list[ast.TypeIgnore]:
+        """The ``# type: ignore`` comments of a module, read when the tokens have type comments.
+
+        From Python 3.12, the tag of a comment alone on its line ends with the line break.
+        """
+        ignores = []
+        for tok in self._tokenizer.type_ignores:
+            alone = tok.line.lstrip().startswith("#") and sys.version_info >= (3, 12)
+            tag = tok.string + "\n" if alone else tok.string
+            ignores.append(ast.TypeIgnore(lineno=tok.start[0], tag=tag))
+        return ignores
+
+    def format_spec(self, spec: list[ast.expr], **locs: int) -> ast.JoinedStr:
+        """The format spec of a replacement field. Python 3.13 leaves out all its empty literal parts."""
+        if sys.version_info >= (3, 13):
+            values = [v for v in spec if not (isinstance(v, ast.Constant) and v.value == "")]
+        else:
+            values = spec if spec and (len(spec) > 1 or spec[0].value) else []
+        return ast.JoinedStr(values=values, **locs)
+
+    def formatted_value(
+        self,
+        value: ast.expr,
+        debug: TokenInfo | None,
+        conversion: int | None,
+        format_spec: ast.JoinedStr | None,
+        **locs: int,
+    ) -> ast.FormattedValue | ast.JoinedStr:
+        """A replacement field of an f-string. A debug field like ``{x = }`` is preceded by its text."""
+        if conversion is None:
+            conversion = ord("r") if debug and not format_spec else -1
+        node = ast.FormattedValue(value=value, conversion=conversion, format_spec=format_spec, **locs)
+        if debug is None:
+            return node
+        # the text goes from the brace to the conversion, format spec or closing brace after the "="
+        tokens = self._tokenizer._tokens
+        index = self._mark() - 1
+        while tokens[index] is not debug:
+            index -= 1
+        start, end = (locs["lineno"], locs["col_offset"] + 1), tokens[index + 1].start
+        text = ast.Constant(
+            value=self._tokenizer.text_between(start, end),
+            lineno=start[0],
+            col_offset=start[1],
+            end_lineno=end[0],
+            end_col_offset=end[1],
+        )
+        return ast.JoinedStr(values=[text, node], **locs)
+
+    def check_attribute_number(self, number: TokenInfo) -> None:
+        """Report an attribute access on a number like ``a.1``, unless it may be a missing comma."""
+        if number.string.startswith(".") and not self._tokenizer.bracket_depth(self._mark() - 1):
+            self.raise_syntax_error_known_location("invalid syntax", number)
+
+    def check_bytes_path_literal(self, prefix: TokenInfo, string: TokenInfo) -> None:
+        """Report a ``pb"..."`` string, as the prefixes of a path literal cannot make bytes."""
+        letters = prefix.string.lower()
+        valid = {"p", "b"} <= set(letters) <= set("pbr") and len(set(letters)) == len(letters)
+        if valid and prefix.end == string.start:
+            self.raise_syntax_error_known_range("path literals cannot be bytes", prefix, string)
 
     @staticmethod
     def _strip_path_prefix(token: TokenInfo | ast.expr) -> TokenInfo | None:
         if not isinstance(token, TokenInfo):
             return None
         text = token.string
-        idx = text.find("'") if text.find("'") >= 0 else text.find('"')
+        idx = len(text) - len(text.lstrip(STRING_PREFIX_LETTERS))
         if idx > 0:
             prefix, text = text[:idx].lower(), text[idx:]
             if "p" in prefix:
@@ -650,6 +1324,12 @@ class Parser:
             **locs,
         )
 
+    def xonsh_operator(self, tok: TokenInfo, keyword: str) -> TokenInfo:
+        if not self.xonsh_operators:
+            msg = f"'{tok.string}' is only valid in xonsh, use '{keyword}'"
+            self.raise_syntax_error_known_location(msg, tok)
+        return tok
+
     def expand_help(self, atoms: list[tuple[ast.Name, TokenInfo]], **_: int) -> ast.Call | None:
         node: ast.Call | None = None
         for atom, tok in atoms:
@@ -743,14 +1423,15 @@ class Parser:
         return list(self._proc_args(args))
 
     def handle_proc(self, method: str, args: list[ast.AST], **locs: int) -> ast.Call:
-        return xonsh_call(f"__xonsh__.{method}", *args, **locs)
+        return self.record_feature("subprocess", xonsh_call(f"__xonsh__.{method}", *args, **locs))
 
     def proc_inject(self, args: list[ast.AST], **locs: int) -> ast.Starred:
-        return ast.Starred(
+        node = ast.Starred(
             value=xonsh_call("__xonsh__.subproc_captured_inject", *args, **locs),
             ctx=Load,
             **locs,
         )
+        return self.record_feature("subprocess", node)
 
     def proc_pyexpr(self, expr: ast.expr, **locs: int) -> ast.Starred:
         return ast.Starred(
@@ -760,7 +1441,8 @@ class Parser:
         )
 
     def expand_search_path(self, a: TokenInfo, **locs: int) -> ast.Call:
-        return xonsh_call("__xonsh__.pathsearch", ast.Constant(value=a.string, **locs), **locs)
+        node = xonsh_call("__xonsh__.pathsearch", ast.Constant(value=a.string, **locs), **locs)
+        return self.record_feature("search_path", node)
 
     def macro_call(self, a: ast.expr, b: list[TokenInfo], **locs: int) -> ast.Call:
         gbl_call = xonsh_call("globals", **locs)
@@ -768,7 +1450,7 @@ class Parser:
         positionals = ast.Tuple(
             elts=[ast.Constant(value=param.string, **param.loc()) for param in b], ctx=Load, **locs
         )
-        return xonsh_call(
+        node = xonsh_call(
             "__xonsh__.call_macro",
             a,
             positionals,
@@ -776,6 +1458,7 @@ class Parser:
             loc_call,
             **locs,
         )
+        return self.record_feature("macro", node)
 
     def handle_with_macro_stmt(self, a: ast.withitem, b: TokenInfo, **locs: int) -> ast.With:
         gblcall = xonsh_call("globals", **locs)
@@ -783,7 +1466,7 @@ class Parser:
         body = ast.Constant(value=b.string, **b.loc())
         a.context_expr = xonsh_call("__xonsh__.enter_macro", a.context_expr, body, gblcall, loccall, **locs)
         self._tokenizer._with_macro = False
-        return ast.With(items=[a], body=[ast.Pass(**locs)], **locs)
+        return self.record_feature("macro", ast.With(items=[a], body=[ast.Pass(**locs)], **locs))
 
     def handle_func_macro_start(self, a: ast.expr) -> ast.expr:
         self._tokenizer._call_macro = True
@@ -815,17 +1498,14 @@ class Parser:
             start = start or tok.start
             end = end or tok.end
 
-        if line_from_token:
-            line = tok.line
-        else:
-            # End is used only to get the proper text
-            line = "\\n".join(self._tokenizer.get_lines(list(range(start[0], end[0] + 1))))
+        line = tok.line if line_from_token else self._tokenizer.get_lines([start[0]])[0]
+        line, col, end_col = error_line_text(line, start[1], end[1] if end[0] == start[0] else None)
 
         # tokenize.py index column offset from 0 while Cpython index column
         # offset at 1 when reporting SyntaxError, so we need to increment
         # the column offset when reporting the error.
-        args = (self.filename, start[0], start[1] + 1, line)
-        args += (end[0], end[1] + 1)  # type: ignore
+        args = (self.filename, start[0], col + 1, line)
+        args += (end[0], (end[1] if end_col is None else end_col) + 1)  # type: ignore
 
         return SyntaxError(message, args)
 
@@ -854,6 +1534,9 @@ class Parser:
     def raise_syntax_error(self, message: str) -> NoReturn:
         """Raise a syntax error."""
         tok = self._tokenizer.diagnose()
+        if tok.type in (Token.INDENT, Token.DEDENT):
+            # CPython's tokens of indentation have no columns, so it reports where its tokenizer stopped
+            raise SyntaxError(message, (self.filename, tok.start[0], tok.end[1], tok.line, tok.start[0], -1))
         raise self._build_syntax_error(
             message,
             tok.start,
@@ -922,18 +1605,42 @@ class Parser:
         path: Path,
         py_version: tuple[int, ...] | None = None,
         verbose: bool = False,
+        max_source_bytes: int | None = None,
+        max_tokens: int | None = None,
+        check_scopes: bool = False,
+        xonsh_operators: bool = True,
+        tab_policy: TabPolicy = TabPolicy.STRICT,
     ) -> ast.Module | None:
-        """Parse a file or string."""
+        """Parse a file or string.
+
+        With *check_scopes*, errors that CPython reports only when compiling
+        (like a misplaced ``await``, ``x = *y``, or a ``global`` statement after a
+        name is bound) are raised too. Without *xonsh_operators*, ``&&`` and ``||`` are
+        rejected as they are by CPython. Tabs in indentation are read by *tab_policy*,
+        `TabPolicy.EXPAND` accepting the files of older xonsh.
+        """
         with open(path) as f:
-            tok_stream = generate_tokens(f.readline)
+            tok_stream = generate_tokens(
+                f,
+                max_source_bytes=max_source_bytes,
+                max_tokens=max_tokens,
+                skip_trivia=lambda: tokenizer.skips_trivia(),
+                pep701=accepts_pep701(py_version),
+                trivia=Trivia.COALESCED,
+                tab_policy=tab_policy,
+            )
             tokenizer = Tokenizer(tok_stream, verbose=verbose, path=str(path))
             parser = cls(
                 tokenizer,
                 verbose=verbose,
                 filename=path.name,
                 py_version=py_version,
+                xonsh_operators=xonsh_operators,
             )
-            return parser.parse("file")  # type: ignore
+            tree = parser.parse("file")
+            if check_scopes and tree:
+                ScopeChecker(parser.raise_syntax_error_known_location).check(tree)
+            return tree  # type: ignore
 
     @classmethod
     def parse_string(
@@ -942,11 +1649,67 @@ class Parser:
         mode: Literal["eval", "exec"] = "eval",
         py_version: tuple[int, ...] | None = None,
         verbose: bool = False,
+        max_source_bytes: int | None = None,
+        max_tokens: int | None = None,
+        check_scopes: bool = False,
+        xonsh_operators: bool = True,
+        tab_policy: TabPolicy = TabPolicy.STRICT,
     ) -> Any:
-        """Parse a string."""
+        """Parse a string. The options from *check_scopes* on are the same as for `parse_file`."""
         import io
 
-        tok_stream = generate_tokens(io.StringIO(source).readline)
+        tok_stream = generate_tokens(
+            io.StringIO(source).readline,
+            max_source_bytes=max_source_bytes,
+            max_tokens=max_tokens,
+            skip_trivia=lambda: tokenizer.skips_trivia(),
+            pep701=accepts_pep701(py_version),
+            trivia=Trivia.COALESCED,
+            tab_policy=tab_policy,
+        )
+        tokenizer = Tokenizer(tok_stream, verbose=verbose)
+        parser = cls(tokenizer, verbose=verbose, py_version=py_version, xonsh_operators=xonsh_operators)
+        tree = parser.parse(mode if mode == "eval" else "file")
+        if check_scopes and tree:
+            ScopeChecker(parser.raise_syntax_error_known_location).check(tree)
+        return tree
+
+    @classmethod
+    def parse_expression_str(
+        cls,
+        source: str,
+        py_version: tuple[int, ...] | None = None,
+        verbose: bool = False,
+    ) -> ast.expr:
+        """Parse a string that must consist of a single expression and return the bare node."""
+        tree: ast.Expression = cls.parse_string(source, mode="eval", py_version=py_version, verbose=verbose)
+        return tree.body
+
+    @classmethod
+    def parse_suite(
+        cls,
+        source: str,
+        indent_ok: bool = True,
+        py_version: tuple[int, ...] | None = None,
+        verbose: bool = False,
+        tab_policy: TabPolicy = TabPolicy.STRICT,
+    ) -> list[ast.stmt]:
+        """Parse a block of statements without the module wrapper.
+
+        With *indent_ok*, the block may start at a nonzero indentation (e.g. a fragment of a function body).
+        *tab_policy* is the same as for `parse_file`.
+        """
+        import io
+
+        tok_stream = generate_tokens(
+            io.StringIO(source).readline,
+            indent_ok=indent_ok,
+            skip_trivia=lambda: tokenizer.skips_trivia(),
+            pep701=accepts_pep701(py_version),
+            trivia=Trivia.COALESCED,
+            tab_policy=tab_policy,
+        )
         tokenizer = Tokenizer(tok_stream, verbose=verbose)
         parser = cls(tokenizer, verbose=verbose, py_version=py_version)
-        return parser.parse(mode if mode == "eval" else "file")
+        tree: ast.Module = parser.parse("file")  # type: ignore
+        return tree.body
@@ -34,11 +34,13 @@ import functools
 import io
 import itertools as _itertools
 import re
+import sys
 from enum import Enum, auto
 from typing import TYPE_CHECKING, Any, Final, NamedTuple
 
 if TYPE_CHECKING:
-    from collections.abc import Callable, Generator, Iterator
+    from collections.abc import Callable, Generator, Iterator, Sequence
+    from typing import IO
 
 OPS = {
     "!=",
@@ -103,6 +105,71 @@ OPS = {
     "@$(",
     ">&",  # stream combine
 }
+# the name of each operator, as in `token.EXACT_TOKEN_TYPES`, for the exact type of OP tokens
+EXACT_TOKEN_TYPES = {
+    "!": "EXCLAMATION",
+    "!=": "NOTEQUAL",
+    "%": "PERCENT",
+    "%=": "PERCENTEQUAL",
+    "&": "AMPER",
+    "&=": "AMPEREQUAL",
+    "(": "LPAR",
+    ")": "RPAR",
+    "*": "STAR",
+    "**": "DOUBLESTAR",
+    "**=": "DOUBLESTAREQUAL",
+    "*=": "STAREQUAL",
+    "+": "PLUS",
+    "+=": "PLUSEQUAL",
+    ",": "COMMA",
+    "-": "MINUS",
+    "-=": "MINEQUAL",
+    "->": "RARROW",
+    ".": "DOT",
+    "...": "ELLIPSIS",
+    "/": "SLASH",
+    "//": "DOUBLESLASH",
+    "//=": "DOUBLESLASHEQUAL",
+    "/=": "SLASHEQUAL",
+    ":": "COLON",
+    ":=": "COLONEQUAL",
+    ";": "SEMI",
+    "<": "LESS",
+    "<<": "LEFTSHIFT",
+    "<<=": "LEFTSHIFTEQUAL",
+    "<=": "LESSEQUAL",
+    "=": "EQUAL",
+    "==": "EQEQUAL",
+    ">": "GREATER",
+    ">=": "GREATEREQUAL",
+    ">>": "RIGHTSHIFT",
+    ">>=": "RIGHTSHIFTEQUAL",
+    "@": "AT",
+    "@=": "ATEQUAL",
+    "[": "LSQB",
+    "]": "RSQB",
+    "^": "CIRCUMFLEX",
+    "^=": "CIRCUMFLEXEQUAL",
+    "{": "LBRACE",
+    "|": "VBAR",
+    "|=": "VBAREQUAL",
+    "}": "RBRACE",
+    "~": "TILDE",
+    # xonsh operators
+    "$": "DOLLAR",
+    "?": "QUESTION",
+    "??": "DOUBLEQUESTION",
+    "||": "DOUBLEVBAR",
+    "&&": "DOUBLEAMPER",
+    "@(": "AT_LPAR",
+    "!(": "EXCLAMATION_LPAR",
+    "![": "EXCLAMATION_LSQB",
+    "$(": "DOLLAR_LPAR",
+    "$[": "DOLLAR_LSQB",
+    "${": "DOLLAR_LBRACE",
+    "@$(": "AT_DOLLAR_LPAR",
+    ">&": "GREATER_AMPER",
+}
 
 
 class Token(Enum):
@@ -135,7 +202,33 @@ class Token(Enum):
     WS = auto()
 
 
+class Trivia(Enum):
+    """The WS tokens produced for the whitespace between tokens, see `generate_tokens`."""
+
+    NONE = auto()  # none, like CPython
+    COALESCED = auto()  # one for all the whitespace between two tokens
+    EXACT = auto()  # one for each run of spaces, and for each tab or form feed
+
+
+class TabPolicy(Enum):
+    """How tabs in the indentation of statements are read, see `generate_tokens`."""
+
+    STRICT = auto()  # as by CPython, a TabError when the meaning depends on the tab size
+    EXPAND = auto()  # to the next multiple of 8 columns, as by the tokenizer of older xonsh
+    FORBID = auto()  # not at all, a TabError for any tab
+
+
+# the length of the strings of tokens shown by their repr, longer ones being cut
+REPR_PREVIEW = 40
+
+
 class TokenInfo(NamedTuple):
+    """A token, compared and hashed as a tuple of its fields.
+
+    Tokens of a line share its string, so comparing tokens of the same source does not compare the
+    contents of their lines.
+    """
+
     type: Token
     string: str
     start: tuple[int, int]
@@ -143,11 +236,20 @@ class TokenInfo(NamedTuple):
     line: str
 
     def __repr__(self) -> str:
-        return f"<{self.type.name}>({self.string!r}) at {self.start[0]}"
+        string = self.string if len(self.string) <= REPR_PREVIEW else self.string[: REPR_PREVIEW - 3] + "..."
+        (lineno, col), (end_lineno, end_col) = self.start, self.end
+        return f"<{self.type.name}>({string!r}) at {lineno}:{col}-{end_lineno}:{end_col}"
 
     def is_exact_type(self, typ: str) -> bool:
         return self.type == Token.OP and self.string == typ
 
+    @property
+    def exact_type(self) -> str:
+        """The name of the operator of an OP token, like ``LPAR``, otherwise the name of its type."""
+        if self.type == Token.OP and self.string in EXACT_TOKEN_TYPES:
+            return EXACT_TOKEN_TYPES[self.string]
+        return self.type.name
+
     def loc_start(self) -> dict[str, int]:
         """helper method to construct AST node location"""
         return {
@@ -196,6 +298,7 @@ def maybe(*choices: str) -> str:
 # Note: we use unicode matching for names ("\w") but ascii matching for
 # number literals.
 Whitespace = r"[ \f\t]+"
+WhitespaceRun = re.compile(r" +|[\f\t]")
 Comment = r"#[^\r\n]*"
 Name = r"\w+"
 
@@ -267,21 +370,80 @@ PseudoToken = choice(
 #  to match the remainder of that string. _prefix can be empty, for
 #  a normal single or triple quoted string (with no prefix).
 endpats: Final = {
-    "'": r"(?:[^'\\]|\\.)*'",
-    '"': r'(?:[^"\\]|\\.)*"',
+    "'": r"(?:[^'\\\r\n]|\\.|\\\r?\n)*'",
+    '"': r'(?:[^"\\\r\n]|\\.|\\\r?\n)*"',
     "'''": r"(?:[^'\\]|\\.|'(?!''))*'''",
     '"""': r'(?:[^"\\]|\\.|"(?!""))*"""',
 }
-StartLBrace = r".*?(?=\{(?!\{)){"
-EndRBrace = r".*?(?=\}(?!\}))}"
+# a format specifier ends at the first brace, which either closes it or opens a nested replacement field
+FormatSpec = choice(LBrace=r"[^{}]*\{", RBrace=r"[^{}]*\}")
 
 tabsize = 8
+INCONSISTENT_TABS = "inconsistent use of tabs and spaces in indentation"
+# the start of a comment read as a type comment, and the ignore comments among these, as by CPython
+TypeCommentPrefix = re.compile(r"#[ \t]*type:[ \t]*")
+TypeIgnore = re.compile(r"ignore(?![0-9A-Za-z]|[^\x00-\x7f])")
+# whether the running Python accepts the f-strings of PEP 701, which reuse quotes within replacement fields
+PEP701: Final = sys.version_info >= (3, 12)
+
+
+@functools.cache
+def fstring_middle(quote: str, raw: bool) -> str:
+    """Pattern for the literal text of an f-string up to the next replacement field or the closing quote.
+
+    Doubled braces stand for themselves, and so do the braces of ``\\N{...}`` escapes in non-raw f-strings.
+    """
+    # a single quoted f-string ends with its line unless the line ending is escaped
+    newline = "" if len(quote) == 3 else r"\r\n"
+    chars = [rf"[^{{}}\\{quote[0]}{newline}]", r"\{\{", r"\}\}", r"\\(?=[{}])", r"\\\r\n"]
+    if raw:
+        chars.append(r"\\[^{}]")
+    else:
+        chars += [r"\\N\{[^{}\n]*\}", r"\\N(?!\{)", r"\\[^{}N]"]
+    if len(quote) == 3:
+        chars.append(f"{quote[0]}(?!{quote[:2]})")
+    body = group(*chars) + "*"
+    return choice(LBrace=body + r"\{(?!\{)", End=body + quote)
+
+
+def accepts_pep701(py_version: tuple[int, ...] | None = None) -> bool:
+    """Whether f-strings are tokenized as by Python 3.12, when parsing for *py_version* on this Python."""
+    return PEP701 and (py_version is None or tuple(py_version) >= (3, 12))
+
+
+def closes(outer: str, quote: str) -> bool:
+    """Whether a string starting with *quote* would have ended an f-string quoted with *outer* before 3.12."""
+    return quote.startswith(outer) if len(outer) == 1 else quote == outer
 
 
 class TokenError(Exception):
     pass
 
 
+class LimitExceeded(TokenError):
+    """Raised when the source crosses one of the limits passed to `generate_tokens`"""
+
+    def __init__(self, limit: str, value: int, position: tuple[int, int]):
+        super().__init__(f"{limit}={value} exceeded at line {position[0]}", position)
+        self.limit = limit
+        self.value = value
+        self.position = position
+
+
+class SourceReadError(OSError):
+    """Raised when reading the file given to `generate_tokens` fails, with the position reached.
+
+    ``lineno`` is the line that could not be read, and ``offset`` the number of bytes read before it.
+    The tokens are cut short: the ENDMARKER token is only produced for sources read to their end.
+    """
+
+    def __init__(self, error: OSError, filename: str | None, lineno: int, offset: int):
+        strerror = f"{error.strerror or error} (reading line {lineno}, after {offset} bytes)"
+        super().__init__(error.errno, strerror, filename)
+        self.lineno = lineno
+        self.offset = offset
+
+
 class ModeMiddle(NamedTuple):
     # in the string portion of an f-string (outside braces)
     parenlevel: int
@@ -301,16 +463,39 @@ Mode = ModeMiddle | ModeInBraces | ModeInColon
 
 
 class TokenizerState:
-    def __init__(self) -> None:
-        self.lnum = 0
+    def __init__(
+        self,
+        indent_ok: bool = False,
+        skip_trivia: bool | Callable[[], bool] = False,
+        lnum: int = 0,
+        pep701: bool = True,
+        trivia: Trivia = Trivia.COALESCED,
+        tab_policy: TabPolicy = TabPolicy.STRICT,
+        type_comments: bool = False,
+    ) -> None:
+        self.lnum = lnum  # of the line before the first one
         self.parenlev = 0
         self.continued = False
         self.indents = [0]
+        # the indentation levels counting each tab as one column, to find those changing with the tab size
+        self.alt_indents = [0]
+        self.tab_policy = tab_policy
+        # accept an indented first statement as the base indentation level (code fragments)
+        self.indent_ok = indent_ok
         self.last_line = ""
         self.line = ""
+        # the current statement line holds only whitespace or a comment
+        self.blank_line = False
         self.pos = 0
         self.max = 0
         self.end_progs: list[EndProg] = []
+        # whether WS, COMMENT and NL tokens are left out, asked for each of them
+        self.skip_trivia = skip_trivia if callable(skip_trivia) else lambda: skip_trivia
+        self.trivia = trivia
+        # replacement fields are read as by Python 3.12, else their new syntax is an error as before
+        self.pep701 = pep701
+        # comments like ``# type: int`` are TYPE_COMMENT tokens, and ``# type: ignore`` TYPE_IGNORE ones
+        self.type_comments = type_comments
 
     def move_next_line(self, readline: Callable[[], str]) -> None:
         self.last_line = self.line
@@ -334,7 +519,13 @@ class TokenizerState:
 
     def add_prog(self, start: int, end: int, **kwargs: Any) -> None:
         self.end_progs.append(
-            EndProg(text=self.line[start:end], contline=self.line, start=(self.lnum, start), **kwargs)
+            EndProg(
+                text=self.line[start:end],
+                contline=self.line,
+                last_lnum=self.lnum,
+                start=(self.lnum, start),
+                **kwargs,
+            )
         )
 
     def prog_token(self, end: int, tok: Token) -> TokenInfo:
@@ -360,13 +551,21 @@ class TokenizerState:
     def in_colon(self) -> bool:
         return self.in_mode(ModeInColon)
 
+    def fstrings(self) -> list[EndProg]:
+        """The f-strings being read, from the outermost one."""
+        return [prog for prog in self.end_progs if isinstance(prog.mode, ModeMiddle)]
+
+    def syntax_error(self, msg: str, start: tuple[int, int], end: tuple[int, int]) -> SyntaxError:
+        line = self.line if start[0] == self.lnum else ""
+        return SyntaxError(msg, ("<tokenize>", start[0], start[1] + 1, line, end[0], end[1] + 1))
+
     def in_multi_line_string(self) -> bool:
         return bool(self.end_progs) and (len(self.end_progs[-1].quote) == 3)
 
     def pop_mode(self, end: tuple[int, int] | None = None) -> EndProg:
         prog = self.end_progs.pop()
         if self.end_progs and end:
-            self.end_progs[-1].reset(end)
+            self.end_progs[-1].reset(end, self.line)
         return prog
 
     def at_parenlev(self) -> bool:
@@ -387,42 +586,68 @@ class EndProg:
     mode: Mode | None = None
     pattern: re.Pattern[str] | str = ""  # end pattern
     text: str = ""
-    contline: str = ""  # str
+    contline: str = ""  # physical lines spanned so far
+    last_lnum: int = 0  # the number of the last of them
     start: tuple[int, int] = (0, 0)
     quote: str = ""
+    opening: tuple[int, int] = (0, 0)  # the start of an f-string, with its prefix
 
     def join(self, state: TokenizerState, end: int) -> None:
         self.text += state.line[state.pos : end]
+        self.add_line(state)
 
     def join_line(self, state: TokenizerState) -> None:
         self.text += state.line[state.pos :]
-        self.contline += state.line
+        self.add_line(state)
+
+    def add_line(self, state: TokenizerState) -> None:
+        # each physical line is added once, however many times the token is extended on it
+        if state.lnum > self.last_lnum:
+            self.contline += state.line
+            self.last_lnum = state.lnum
 
-    def reset(self, start: tuple[int, int]) -> None:
+    def reset(self, start: tuple[int, int], line: str) -> None:
         self.start = start
         self.text = ""
-        self.contline = ""
+        self.contline = line
+        self.last_lnum = start[0]
 
 
 def next_statement(state: TokenizerState) -> Generator[TokenInfo, None, bool | None]:
     if not state.line:
         return False  # break parent loop
-    column = 0
+    column = alt_column = 0
     while state.pos < state.max:  # measure leading whitespace
         if state.line[state.pos] == " ":
             column += 1
+            alt_column += 1
         elif state.line[state.pos] == "\t":
             column = (column // tabsize + 1) * tabsize
+            alt_column += 1
         elif state.line[state.pos] == "\f":
-            column = 0
+            column = alt_column = 0
         else:
             break
         state.pos += 1
 
-    if state.pos == state.max:
-        return False  # break parent loop
+    state.blank_line = state.pos == state.max or state.line[state.pos] in "#\r\n"
+    if state.pos == state.max:  # whitespace-only last line without a line ending
+        if not state.skip_trivia():
+            yield TokenInfo(Token.NL, "", (state.lnum, state.pos), (state.lnum, state.pos + 1), state.line)
+        return True  # continue
 
     if state.line[state.pos] in "#\r\n":  # skip comments or blank lines
+        if state.type_comments and (type_comment := type_comment_token(state, state.pos)):
+            yield type_comment
+            state.pos = state.max
+            if type_comment.type == Token.TYPE_COMMENT:
+                # a type comment on a line of its own still ends a statement, as for a function's signature
+                col = len(state.line.rstrip("\r\n"))
+                start, end = (state.lnum, col), (state.lnum, col + 1)
+                yield TokenInfo(Token.NEWLINE, state.line[col:], start, end, state.line)
+            return True
+        if state.skip_trivia():
+            return True
         if state.line[state.pos] == "#":
             comment_token = state.line[state.pos :].rstrip("\r\n")
             yield TokenInfo(
@@ -434,17 +659,29 @@ def next_statement(state: TokenizerState) -> Generator[TokenInfo, None, bool | N
             )
             state.pos += len(comment_token)
 
+        # like the implicit NEWLINE, a missing line ending still takes up a column
+        end_col = len(state.line) + (not state.line.endswith("\n"))
         yield TokenInfo(
             Token.NL,
             state.line[state.pos :],
             (state.lnum, state.pos),
-            (state.lnum, len(state.line)),
+            (state.lnum, end_col),
             state.line,
         )
         return True  # continue
 
+    if state.tab_policy == TabPolicy.FORBID and "\t" in state.line[: state.pos]:
+        raise TabError("tab in indentation", ("<tokenize>", state.lnum, state.pos, state.line))
+    if state.indent_ok:
+        state.indent_ok = False
+        state.indents = [column]
+        state.alt_indents = [alt_column]
+    strict = state.tab_policy == TabPolicy.STRICT
     if column > state.indents[-1]:  # count indents or dedents
+        if strict and alt_column <= state.alt_indents[-1]:
+            raise TabError(INCONSISTENT_TABS, ("<tokenize>", state.lnum, state.pos, state.line))
         state.indents.append(column)
+        state.alt_indents.append(alt_column)
         yield TokenInfo(
             Token.INDENT, state.line[: state.pos], (state.lnum, 0), (state.lnum, state.pos), state.line
         )
@@ -455,13 +692,16 @@ def next_statement(state: TokenizerState) -> Generator[TokenInfo, None, bool | N
                 ("<tokenize>", state.lnum, state.pos, state.line),
             )
         state.indents = state.indents[:-1]
+        state.alt_indents = state.alt_indents[:-1]
 
         yield TokenInfo(Token.DEDENT, "", (state.lnum, state.pos), (state.lnum, state.pos), state.line)
+    if strict and state.alt_indents[-1] != alt_column:
+        raise TabError(INCONSISTENT_TABS, ("<tokenize>", state.lnum, state.pos, state.line))
     return None
 
 
 def next_psuedo_matches(state: TokenizerState) -> TokenInfo | None:
-    if state.pos == state.max or state.in_fstring():
+    if state.pos == state.max or state.in_fstring() or state.in_colon():
         return None
     match = state.match(PseudoToken)
     if (not match) or (not match.lastgroup):
@@ -469,24 +709,35 @@ def next_psuedo_matches(state: TokenizerState) -> TokenInfo | None:
     start, end = match.span(match.lastgroup)
     spos, epos, state.pos = (state.lnum, start), (state.lnum, end), end
     token = state.line[start:end]
+    if not state.pep701 and state.fstrings():
+        check_replacement_field(state, match)
 
     if match.lastgroup == "StringStart":
         quote = match.group("Quote") or '"'
         if "f" in token.lower():
             token_type = Token.FSTRING_START
-            pattern = choice(LBrace=StartLBrace, End=endpats[quote])
-            state.add_prog(end, end, pattern=pattern, quote=quote, mode=ModeMiddle(state.parenlev))
+            pattern = fstring_middle(quote, raw="r" in token.lower())
+            mode = ModeMiddle(state.parenlev)
+            state.add_prog(end, end, pattern=pattern, quote=quote, mode=mode, opening=spos)
         else:
             pattern = endpats[quote]
             state.add_prog(start, end, pattern=pattern, quote=quote)
             return None
+    elif match.lastgroup == "ws" and state.trivia is not Trivia.COALESCED:
+        if state.trivia is Trivia.NONE:
+            return None
+        token = WhitespaceRun.match(token).group()  # type: ignore[union-attr]
+        epos, state.pos = (state.lnum, start + len(token)), start + len(token)
+        token_type = Token.WS
     elif tok := {
         "ws": Token.WS,
         "Comment": Token.COMMENT,
         "SearchPath": Token.SEARCH_PATH,
-        "Name": Token.NAME,
     }.get(match.lastgroup):
         token_type = tok
+    elif match.lastgroup == "Name":
+        # share one string object per identifier, like CPython does
+        token_type, token = Token.NAME, sys.intern(token)
     elif match.lastgroup == "Number" or (token[0] == "." and token not in (".", "...")):
         token_type = Token.NUMBER
     elif match.lastgroup == "NL":
@@ -499,7 +750,7 @@ def next_psuedo_matches(state: TokenizerState) -> TokenInfo | None:
                 state.pop_mode((state.lnum, end))
             state.parenlev -= 1
         elif token == ":" and state.in_braces() and state.at_parenlev():
-            state.add_prog(start + 1, end, mode=ModeInColon(state.parenlev), pattern=choice(RBrace=EndRBrace))
+            state.add_prog(start + 1, end, mode=ModeInColon(state.parenlev), pattern=FormatSpec)
         token_type = Token.OP
     elif match.lastgroup == "End":  # // continuation
         state.continued = True
@@ -507,15 +758,76 @@ def next_psuedo_matches(state: TokenizerState) -> TokenInfo | None:
     else:
         raise TokenError(f"Bad token: {token!r} at line {state.lnum}", spos)
 
+    if token_type == Token.COMMENT and state.type_comments:
+        if type_comment := type_comment_token(state, start):
+            return type_comment
+    if token_type in (Token.WS, Token.COMMENT, Token.NL) and state.skip_trivia():
+        return None
     # Yield Token if Found
     if token_type:
         return TokenInfo(token_type, token, spos, epos, state.line)
     return None
 
 
+def type_comment_token(state: TokenizerState, start: int) -> TokenInfo | None:
+    """The TYPE_COMMENT or TYPE_IGNORE token of the comment at *start*, if CPython reads it as one.
+
+    A TYPE_COMMENT holds the text after ``type:``, and a TYPE_IGNORE the tag after ``ignore``, which
+    must not be followed by a letter or digit.
+    """
+    prefix = TypeCommentPrefix.match(state.line, start)
+    if not prefix:
+        return None
+    col, end = prefix.end(), len(state.line.rstrip("\r\n"))
+    token_type = Token.TYPE_COMMENT
+    if TypeIgnore.match(state.line, col, end):
+        token_type, col = Token.TYPE_IGNORE, col + len("ignore")
+    return TokenInfo(token_type, state.line[col:end], (state.lnum, col), (state.lnum, end), state.line)
+
+
+def check_replacement_field(state: TokenizerState, match: re.Match[str]) -> None:
+    """Raise the error of Python 3.11 for a token of a replacement field that needs PEP 701.
+
+    These are strings ending the f-string, comments, backslashes, and line breaks in single quoted
+    f-strings.
+    """
+    fstrings = state.fstrings()
+    start, end = match.span(match.lastgroup)
+    if match.lastgroup == "StringStart":
+        for prog in fstrings:
+            if closes(prog.quote, match.group("Quote")):
+                # the string ends the f-string with its first quote
+                col = start + len(match.group("StringPrefix")) + len(prog.quote)
+                raise state.syntax_error("f-string: expecting '}'", (state.lnum, col), (state.lnum, col + 1))
+    elif (
+        # a single quoted f-string not closed before the line ends
+        len(quote := fstrings[-1].quote) == 1
+        and (match.lastgroup == "NL" or match.lastgroup == "Comment" and quote not in match.group("Comment"))
+    ):
+        msg = f"unterminated string literal (detected at line {state.lnum})"
+        raise state.syntax_error(msg, fstrings[-1].opening, fstrings[-1].opening)
+    elif match.lastgroup == "Comment":
+        msg = "f-string expression part cannot include '#'"
+        raise state.syntax_error(msg, (state.lnum, start), (state.lnum, start))
+    elif match.lastgroup == "End" and end > start:
+        msg = "f-string expression part cannot include a backslash"
+        raise state.syntax_error(msg, (state.lnum, start), (state.lnum, start))
+
+
+def check_backslash(state: TokenizerState, tok: TokenInfo) -> TokenInfo:
+    """Raise the error of Python 3.11 for a backslash in a string within a replacement field."""
+    if state.pep701 or "\\" not in tok.string or len(state.fstrings()) <= (tok.type == Token.FSTRING_MIDDLE):
+        return tok
+    index = tok.string.index("\\")
+    lnum = tok.start[0] + tok.string.count("\n", 0, index)
+    col = index - tok.string.rfind("\n", 0, index) - 1 if lnum > tok.start[0] else tok.start[1] + index
+    msg = "f-string expression part cannot include a backslash"
+    raise state.syntax_error(msg, (lnum, col), (lnum, col))
+
+
 def next_end_tokens(state: TokenizerState) -> Iterator[TokenInfo]:
     # Add an implicit NEWLINE if the input doesn't end in one
-    if state.last_line and state.last_line[-1] not in "\r\n" and not state.last_line.strip().startswith("#"):
+    if state.last_line and state.last_line[-1] not in "\r\n" and not state.blank_line:
         yield TokenInfo(
             Token.NEWLINE,
             "",
@@ -536,7 +848,7 @@ def handle_fstring_progs(state: TokenizerState, endprog: EndProg) -> Iterator[To
     if endmatch.lastgroup == "End":  # quote match
         middle_end = end - len(endprog.quote)
         if (middle_end > state.pos) or endprog.text:
-            yield state.prog_token(middle_end, Token.FSTRING_MIDDLE)
+            yield check_backslash(state, state.prog_token(middle_end, Token.FSTRING_MIDDLE))
         yield TokenInfo(
             Token.FSTRING_END,
             endprog.quote,
@@ -547,8 +859,10 @@ def handle_fstring_progs(state: TokenizerState, endprog: EndProg) -> Iterator[To
         state.pop_mode()
     else:  # "{" or "}"
         middle_end = end - 1
-        if (middle_end > state.pos) or (endprog.text):  # has buffer
-            yield state.prog_token(middle_end, Token.FSTRING_MIDDLE)
+        # like CPython, the end of a format specifier always comes with a middle, even an empty one
+        spec_end = state.in_colon() and endmatch.lastgroup == "RBrace"
+        if (middle_end > state.pos) or (endprog.text) or spec_end:  # has buffer
+            yield check_backslash(state, state.prog_token(middle_end, Token.FSTRING_MIDDLE))
         if endmatch.lastgroup == "LBrace":
             yield TokenInfo(
                 Token.OP,
@@ -583,6 +897,7 @@ def handle_end_progs(state: TokenizerState) -> Iterator[TokenInfo]:
     if state.in_braces():
         return
 
+    pos = state.pos
     if state.in_fstring() or state.in_colon():
         yield from handle_fstring_progs(state, state.end_progs[-1])
         # else:
@@ -590,7 +905,7 @@ def handle_end_progs(state: TokenizerState) -> Iterator[TokenInfo]:
 
     elif endmatch := state.match(state.end_progs[-1].pattern):  # all on one line
         end = endmatch.end(0)
-        yield state.prog_token(end, Token.STRING)
+        yield check_backslash(state, state.prog_token(end, Token.STRING))
         state.pop_mode()
         return
 
@@ -598,17 +913,43 @@ def handle_end_progs(state: TokenizerState) -> Iterator[TokenInfo]:
         return
 
     if (
-        (state.pos == 0)  # called at start of the line
-        or ((state.in_multi_line_string()) or (state.in_continued_string()))
+        state.in_multi_line_string()
+        or state.in_continued_string()
+        or (state.pos == 0 and state.in_colon())  # called at start of the line
     ):
         state.end_progs[-1].join_line(state)
         state.pos = state.max
-    # else:
-    #     raise TokenError(f"Invalid string quotes at {state.pos} in {state.line}", (state.lnum, state.pos))
+    elif state.pos == pos:  # neither the end of the string nor a replacement field on this line
+        yield from unterminated_string(state)
+
 
+def unterminated_string(state: TokenizerState) -> Iterator[TokenInfo]:
+    """The rest of a line ending inside a single quoted string becomes an ERRORTOKEN.
 
-def _tokenize(readline: Callable[[], str]) -> Iterator[TokenInfo]:
-    state = TokenizerState()
+    Tokenizing goes on with the line ending, so the following statements are still tokenized.
+    For an f-string, the token holds its literal text from the last replacement field.
+    """
+    end = len(state.line.rstrip("\r\n"))
+    yield state.prog_token(end, Token.ERRORTOKEN)
+    state.pop_mode((state.lnum, end))
+
+
+def _tokenize(
+    readline: Callable[[], str],
+    indent_ok: bool = False,
+    skip_trivia: bool | Callable[[], bool] = False,
+    lineno: int = 1,
+    indents: Sequence[int] = (0,),
+    pep701: bool = True,
+    trivia: Trivia = Trivia.COALESCED,
+    tab_policy: TabPolicy = TabPolicy.STRICT,
+    alt_indents: Sequence[int] = (0,),
+    type_comments: bool = False,
+) -> Iterator[TokenInfo]:
+    state = TokenizerState(indent_ok, skip_trivia, lineno - 1, pep701, trivia, tab_policy, type_comments)
+    # of the statements enclosing the first line
+    state.indents = list(indents)
+    state.alt_indents = list(alt_indents)
 
     while True:  # loop over lines in stream
         state.move_next_line(readline)
@@ -629,8 +970,8 @@ def _tokenize(readline: Callable[[], str]) -> Iterator[TokenInfo]:
                 raise TokenError("EOF in multi-line statement", (state.lnum, 0))
             state.continued = False
 
-        pos = state.pos
         while state.pos < state.max:
+            pos = state.pos
             yield from handle_end_progs(state)
             if token := next_psuedo_matches(state):
                 yield token
@@ -643,17 +984,237 @@ def _tokenize(readline: Callable[[], str]) -> Iterator[TokenInfo]:
                     state.line,
                 )
                 state.pos += 1
-                pos = state.pos
+
+        # a single quoted string opened at the end of the source has no line ending to stop at
+        if state.end_progs and not state.line.endswith("\n") and not state.in_multi_line_string():
+            yield from handle_end_progs(state)
 
     yield from next_end_tokens(state)
 
 
-def generate_tokens(readline: Callable[[], str] | str) -> Iterator[TokenInfo]:
+def _limit_source_bytes(readline: Callable[[], str], max_source_bytes: int, lineno: int) -> Callable[[], str]:
+    consumed = 0
+    lnum = lineno - 1
+
+    def limited_readline() -> str:
+        nonlocal consumed, lnum
+        line = readline()
+        lnum += 1
+        # most sources are pure ASCII, where the size in bytes is the length
+        size = len(line) if line.isascii() else len(line.encode("utf-8", "surrogatepass"))
+        if consumed + size > max_source_bytes:
+            data = line.encode("utf-8", "surrogatepass")
+            col = len(data[: max_source_bytes - consumed].decode(errors="ignore"))
+            raise LimitExceeded("max_source_bytes", max_source_bytes, (lnum, col))
+        consumed += size
+        return line
+
+    return limited_readline
+
+
+def _read_file(
+    file: IO[str] | IO[bytes], lineno: int, max_source_bytes: int | None = None
+) -> Callable[[], str | bytes]:
+    offset = 0
+    lnum = lineno - 1
+    filename = getattr(file, "name", None)
+    # a character takes a byte or more, so `_limit_source_bytes` trips on a line cut short by these,
+    # while cutting the lines of binary files could split the characters read for their encoding
+    left = max_source_bytes + 1 if max_source_bytes is not None and isinstance(file, io.TextIOBase) else None
+
+    def file_readline() -> str | bytes:
+        nonlocal offset, lnum, left
+        lnum += 1
+        try:
+            if left is None:
+                line = file.readline()
+            else:
+                line = file.readline(left)
+                left -= len(line)
+        except OSError as e:
+            raise SourceReadError(e, filename if isinstance(filename, str) else None, lnum, offset) from e
+        if isinstance(line, bytes) or line.isascii():
+            offset += len(line)
+        else:
+            offset += len(line.encode("utf-8", "surrogatepass"))
+        return line
+
+    return file_readline
+
+
+def _offset_first_line(tokens: Iterator[TokenInfo], lineno: int, col_offset: int) -> Iterator[TokenInfo]:
+    for tok in tokens:
+        if tok.start[0] == lineno:
+            end = (lineno, tok.end[1] + col_offset) if tok.end[0] == lineno else tok.end
+            tok = tok._replace(start=(lineno, tok.start[1] + col_offset), end=end)
+        yield tok
+
+
+def _limit_tokens(tokens: Iterator[TokenInfo], max_tokens: int) -> Iterator[TokenInfo]:
+    for count, tok in enumerate(tokens, 1):
+        if count > max_tokens:
+            raise LimitExceeded("max_tokens", max_tokens, tok.start)
+        yield tok
+
+
+def _decoded_lines(readline: Callable[[], str | bytes]) -> Callable[[], str]:
+    """Read the lines of a *readline* returning str or bytes as str.
+
+    Bytes are decoded with the encoding of their coding cookie or BOM, and UTF-8 otherwise, as by
+    `tokenize.detect_encoding`. The end of the source is an empty line or StopIteration.
+    """
+    import codecs
+    import tokenize
+
+    def next_line() -> str | bytes:
+        try:
+            return readline()
+        except StopIteration:
+            return ""
+
+    first = next_line()
+    if not isinstance(first, bytes):
+        pending: list[str | bytes] = [first]
+        return lambda: pending.pop() if pending else next_line()  # type: ignore[return-value]
+
+    unread = [first]
+    encoding, read = tokenize.detect_encoding(lambda: unread.pop() if unread else next_line())
+    decoder = codecs.getincrementaldecoder(encoding)()
+    # the cookie lines read to find the encoding come first
+    pending = list(reversed(read))
+
+    def decoded_readline() -> str:
+        line = pending.pop() if pending else next_line() or b""
+        return decoder.decode(line, final=not line)  # type: ignore[arg-type]
+
+    return decoded_readline
+
+
+def generate_tokens(
+    readline: Callable[[], str | bytes] | IO[str] | IO[bytes] | str,
+    *,
+    max_source_bytes: int | None = None,
+    max_tokens: int | None = None,
+    indent_ok: bool = False,
+    skip_trivia: bool | Callable[[], bool] = False,
+    initial_lineno: int = 1,
+    initial_col_offset: int = 0,
+    pep701: bool = True,
+    trivia: Trivia = Trivia.NONE,
+    tab_policy: TabPolicy = TabPolicy.STRICT,
+    type_comments: bool = False,
+) -> Iterator[TokenInfo]:
     """Tokenize a source reading Python code as unicode strings.
 
-    This has the same API as tokenize(), except that it expects the *readline*
-    callable to return str objects instead of bytes.
+    This has the same API as tokenize(), except that *readline* may return str objects as well as bytes.
+    It can also be a file object, or the source itself. Exceptions raised by *readline* are propagated,
+    while a file failing to be read raises a `SourceReadError` telling how far it was read.
+    Lone surrogates are kept in the tokens of strings and comments, and are an ERRORTOKEN elsewhere.
+
+    The optional limits are checked while the source is being read, so untrusted
+    input fails with `LimitExceeded` before it is fully materialized. Sources and file objects are read
+    in pieces bounded by *max_source_bytes*, while binary files and *readline* callables are checked
+    after each line.
+
+    With *indent_ok*, the indentation of the first statement is taken as the base level
+    instead of producing an INDENT token. Columns still refer to the original source.
+
+    With *skip_trivia*, the WS, COMMENT and NL tokens are not produced, for consumers that would drop
+    them anyway. A callable is asked before each of them, so a consumer can keep them for a while.
+    The WS tokens produced otherwise are chosen by *trivia*: with `Trivia.NONE`, the default, the tokens
+    are those of CPython. The parser reads those of `Trivia.COALESCED`, one for the whitespace between
+    two tokens, and with `Trivia.EXACT` whitespace mixing spaces, tabs and form feeds is split at each
+    tab or form feed, for formatters.
+
+    Tabs in the indentation of statements are read by *tab_policy*. With `TabPolicy.STRICT`, a
+    TabError is raised as by CPython when the indentation levels would change with the tab size. With
+    `TabPolicy.EXPAND` tabs are expanded to the next multiple of 8 columns, as by older xonsh, and
+    with `TabPolicy.FORBID` any tab there is a TabError. Tabs within statements are always accepted.
+
+    For code taken from a larger file, positions start at *initial_lineno*, and the columns of the
+    first line are shifted by *initial_col_offset*.
+
+    Without *pep701*, the f-strings of Python 3.12 are rejected with the errors of Python 3.11: strings
+    ending the enclosing f-string, comments, backslashes and line breaks in single quoted f-strings are
+    not allowed in replacement fields. See `accepts_pep701` for the value matching a parser's version.
+
+    With *type_comments*, comments read as type comments by CPython are TYPE_COMMENT tokens holding the
+    text after ``type:``, and ``# type: ignore`` comments are TYPE_IGNORE tokens holding their tag. A
+    TYPE_COMMENT alone on its line is followed by a NEWLINE, as a function's signature may be.
     """
     if isinstance(readline, str):
-        readline = io.StringIO(readline).readline
-    return _tokenize(readline)
+        if max_source_bytes is None:
+            readline = io.StringIO(readline).readline
+        else:
+            readline = _read_file(io.StringIO(readline), initial_lineno, max_source_bytes)
+    elif not callable(readline):
+        readline = _read_file(readline, initial_lineno, max_source_bytes)
+    readline = _decoded_lines(readline)
+    if max_source_bytes is not None:
+        readline = _limit_source_bytes(readline, max_source_bytes, initial_lineno)
+    tokens = _tokenize(
+        readline,
+        indent_ok,
+        skip_trivia,
+        initial_lineno,
+        pep701=pep701,
+        trivia=trivia,
+        tab_policy=tab_policy,
+        type_comments=type_comments,
+    )
+    if initial_col_offset:
+        tokens = _offset_first_line(tokens, initial_lineno, initial_col_offset)
+    if max_tokens is not None:
+        tokens = _limit_tokens(tokens, max_tokens)
+    return tokens
+
+
+def source_end(source: str) -> tuple[int, int]:
+    """Get the (row, col) just past the last character of *source*.
+
+    For sources ending with a newline, this is where the ENDMARKER token starts.
+    """
+    return source.count("\n") + 1, len(source) - source.rfind("\n") - 1
+
+
+def dump_tokens(source: str, encoding: str = "utf-8", *, exact: bool = False) -> str:
+    """The tokens of a source in the text format of ``python -m tokenize``, one per line.
+
+    The output starts with the ENCODING token that CPython reads from files. There are no WS tokens, so
+    the output for plain Python can be compared with CPython's, and other xonsh tokens are named as here.
+    With *exact*, operators are named by their `TokenInfo.exact_type`, like with ``python -m tokenize -e``.
+    """
+    lines = [f"{'0,0-0,0:':<20}{'ENCODING':<15}{encoding!r:<15}"]
+    for tok in generate_tokens(source):
+        token_range = "%d,%d-%d,%d:" % (tok.start + tok.end)
+        name = tok.exact_type if exact else tok.type.name
+        lines.append(f"{token_range:<20}{name:<15}{tok.string!r:<15}")
+    return "".join(line + "\n" for line in lines)
+
+
+def main(args: list[str] | None = None) -> None:
+    """Print the tokens of a file, or stdin, like ``python -m tokenize``."""
+    import argparse
+    import tokenize
+
+    parser = argparse.ArgumentParser(prog="python -m peg_parser.tokenize")
+    parser.add_argument("filename", nargs="?", help="the file to tokenize; defaults to stdin")
+    parser.add_argument("-e", "--exact", action="store_true", help="display token names using the exact type")
+    options = parser.parse_args(args)
+    filename = options.filename or "<stdin>"
+    try:
+        if options.filename:
+            with tokenize.open(options.filename) as file:
+                source, encoding = file.read(), file.encoding
+        else:
+            source, encoding = sys.stdin.read(), "utf-8"
+        sys.stdout.write(dump_tokens(source, encoding, exact=options.exact))
+    except TokenError as err:
+        line, column = err.args[1]
+        sys.exit(f"{filename}:{line}:{column}: error: {err.args[0]}")
+    except (OSError, SyntaxError) as err:
+        sys.exit(f"{filename}: error: {err}")
+
+
+if __name__ == "__main__":
+    main()
@@ -1,14 +1,65 @@
 from __future__ import annotations
 
+import re
+import sys
 from typing import TYPE_CHECKING, Final, NewType
 
-from .tokenize import Token, TokenInfo
+from .tokenize import Token, TokenError, TokenInfo, generate_tokens
 
 if TYPE_CHECKING:
     from collections.abc import Iterator
 
 Mark = NewType("Mark", int)
 
+# an ERRORTOKEN from the opening quote of a string to the end of its line
+UNTERMINATED_STRING = re.compile(r"[a-zA-Z]*['\"]")
+# the closing bracket of each opening one, also ending xonsh's openers like ``$(`` and ``![``
+CLOSING: Final = {"(": ")", "[": "]", "{": "}"}
+
+
+def skip_balanced(
+    tokens: Iterator[TokenInfo], opener: TokenInfo, filename: str = "<unknown>"
+) -> tuple[TokenInfo, list[TokenInfo]]:
+    """Read the tokens following an opening bracket up to the closing one matching it.
+
+    The result is the closing token and the tokens read before it, within which brackets are balanced.
+    Strings are single tokens, and the braces of f-string replacement fields are OP tokens like others.
+    A closing bracket not matching the innermost one open raises the SyntaxError of CPython, and the
+    end of the tokens raises "'(' was never closed" for the innermost one.
+    """
+    opened = [opener]
+    skipped: list[TokenInfo] = []
+    try:
+        for tok in tokens:
+            if tok.type == Token.OP and tok.string[-1] in CLOSING:
+                opened.append(tok)
+            elif tok.type == Token.OP and tok.string in CLOSING.values():
+                if CLOSING[opened[-1].string[-1]] != tok.string:
+                    raise mismatched_bracket(opened[-1], tok, filename)
+                opened.pop()
+                if not opened:
+                    return tok, skipped
+            elif tok.type == Token.ENDMARKER:
+                break
+            skipped.append(tok)
+    except TokenError as e:
+        if e.args[0] != "EOF in multi-line statement":
+            raise
+    last = skipped[-1] if skipped else opener
+    (lineno, col), line = opened[-1].start, opened[-1].line.partition("\n")[0] + "\n"
+    message = f"'{opened[-1].string}' was never closed"
+    raise SyntaxError(message, (filename, lineno, col + 1, line, last.end[0], last.end[1] + 1))
+
+
+def mismatched_bracket(opener: TokenInfo, closer: TokenInfo, filename: str) -> SyntaxError:
+    (lineno, col), line = closer.start, closer.line.partition("\n")[0]
+    message = (
+        f"closing parenthesis '{closer.string}' does not match opening parenthesis '{opener.string[-1]}'"
+    )
+    if opener.start[0] != lineno:
+        message += f" on line {opener.start[0]}"
+    return SyntaxError(message, (filename, lineno, col + 1, line, lineno, col + 1))
+
 
 class Tokenizer:
     """Caching wrapper for the tokenize module"""
@@ -18,14 +69,25 @@ class Tokenizer:
     def __init__(self, tokengen: Iterator[TokenInfo], *, path: str = "", verbose: bool = False):
         self._tokengen = tokengen
         self._tokens = []
+        # the depth of brackets before each token, and after the last one
+        self._bracket_depths = [0]
         self._index = Mark(0)
         self._verbose = verbose
         self._lines: dict[int, str] = {}
         self._path = path
+        self.filename = path or "<unknown>"
+        # FSTRING_START of each f-string being read, None for each brace opened within them
+        self._fstrings: list[TokenInfo | None] = []
         self._stack: list[TokenInfo] = []  # temporarily hold tokens
         self._call_macro = False
         self._with_macro = False
         self._proc_macro = False
+        # False to read async and await as keywords only within async functions, as Python 3.6 did
+        self.async_keywords = True
+        self._async_defs: list[int] = []  # indentation depth of each enclosing async function
+        # the ``# type: ignore`` comments read, which are not tokens of the grammar
+        self.type_ignores: list[TokenInfo] = []
+        self._depth = 0
         self._end_parens: Final = {
             ")": "(",
             "]": "[",
@@ -53,15 +115,70 @@ class Tokenizer:
             elif self._stack:
                 tok = self._stack.pop()
             else:
-                tok = next(self._tokengen)
+                tok = self.next_token()
             if self.is_blank(tok):
                 continue
+            if tok.type == Token.TYPE_IGNORE:
+                self.type_ignores.append(tok)
+                continue
+            self.check_strings(tok)
+            if not self.async_keywords:
+                tok = self.async_keyword(tok)
+            if tok.type == Token.OP and tok.string == "@(" and self.is_line_start():
+                tok = self.split_decorator(tok)
 
             self._tokens.append(tok)
+            depth = self._bracket_depths[-1]
+            if tok.type == Token.OP and tok.string[-1] in "([{":
+                depth += 1
+            elif tok.type == Token.OP and tok.string in self._end_parens:
+                depth -= 1
+            self._bracket_depths.append(depth)
             if not self._path and tok.start[0] not in self._lines:
                 self._lines[tok.start[0]] = tok.line
         return self._tokens[self._index]
 
+    def next_token(self) -> TokenInfo:
+        try:
+            return next(self._tokengen)
+        except TabError as e:
+            # reported like CPython, at the start of the line
+            _, lineno, _, line = e.args[1]
+            raise TabError(e.msg, (self.filename, lineno, 1, line, lineno, 0)) from None
+        except IndentationError as e:
+            # reported like CPython, at the end of the line and without an end column
+            _, lineno, _, line = e.args[1]
+            col = len(line.rstrip("\r\n")) + 1
+            raise IndentationError(e.msg, (self.filename, lineno, col, line, lineno, -1)) from None
+        except SyntaxError as e:
+            if e.filename != "<tokenize>":
+                raise
+            raise SyntaxError(e.msg, (self.filename, *e.args[1][1:])) from None
+        except TokenError as e:
+            if e.args[0] != "EOF in multi-line statement" or not self._tokens:
+                raise
+            raise self.unexpected_eof() from None
+
+    def unexpected_eof(self) -> SyntaxError:
+        """The SyntaxError of a source ending within brackets, spanning from the innermost one left open.
+
+        Otherwise the source ends after a line continuation, reported at the end of its line.
+        """
+        opened: list[TokenInfo] = []
+        for tok in self._tokens:
+            if tok.type == Token.OP and tok.string[-1] in "([{":
+                opened.append(tok)
+            elif tok.type == Token.OP and tok.string in self._end_parens and opened:
+                opened.pop()
+        last = self._tokens[-1]
+        if opened:
+            (lineno, col), line = opened[-1].start, opened[-1].line.partition("\n")[0] + "\n"
+            message = f"'{opened[-1].string}' was never closed"
+            return SyntaxError(message, (self.filename, lineno, col + 1, line, last.end[0], last.end[1] + 1))
+        lineno, line = last.end[0], last.line.splitlines(keepends=True)[-1]
+        col = len(line.rstrip("\r\n")) + 1
+        return SyntaxError("unexpected EOF while parsing", (self.filename, lineno, col, line, lineno, -1))
+
     def is_blank(self, tok: TokenInfo) -> bool:
         if self._proc_macro and tok.type == Token.WS:
             return False
@@ -73,39 +190,95 @@ class Tokenizer:
             return True
         return False
 
-    def consume_macro_params(self) -> TokenInfo:  # noqa: C901, PLR0912
+    def skips_trivia(self) -> bool:
+        """Whether the tokens dropped by `is_blank` can be left out of the stream, as outside of macros."""
+        return not (self._call_macro or self._with_macro or self._proc_macro)
+
+    def check_strings(self, tok: TokenInfo) -> None:
+        """Raise a SyntaxError for the ERRORTOKEN of a string not closed on its line."""
+        if tok.type == Token.FSTRING_START:
+            self._fstrings.append(tok)
+        elif tok.type == Token.FSTRING_END:
+            self._fstrings.pop()
+        elif self._fstrings and tok.type == Token.OP and tok.string[-1] == "{":
+            self._fstrings.append(None)
+        elif self._fstrings and tok.type == Token.OP and tok.string == "}" and self._fstrings[-1] is None:
+            self._fstrings.pop()
+        elif tok.type == Token.ERRORTOKEN:
+            kind = "string"
+            if self._fstrings and (start := self._fstrings[-1]):
+                kind = "f-string" if sys.version_info >= (3, 12) else "string"
+            elif UNTERMINATED_STRING.match(tok.string):
+                start = tok
+            else:
+                return
+            (lineno, col), line = start.start, start.line.partition("\n")[0] + "\n"
+            message = f"unterminated {kind} literal (detected at line {tok.end[0]})"
+            raise SyntaxError(message, (self.filename, lineno, col + 1, line, lineno, col + 1))
+
+    def async_keyword(self, tok: TokenInfo) -> TokenInfo:
+        """Type async and await as ASYNC and AWAIT where they are keywords, leaving them names elsewhere.
+
+        Like the tokenizer of Python 3.6, they are keywords within async functions and for ``async def``.
+        """
+        if tok.type == Token.INDENT:
+            self._depth += 1
+        elif tok.type == Token.DEDENT:
+            self._depth -= 1
+        elif self.is_line_start():
+            while self._async_defs and self._async_defs[-1] >= self._depth:
+                self._async_defs.pop()
+        if tok.type != Token.NAME or tok.string not in ("async", "await"):
+            return tok
+        if not self._async_defs:
+            if tok.string == "await" or self.peek_raw().string != "def":
+                return tok
+            self._async_defs.append(self._depth)
+        return tok._replace(type=Token.ASYNC if tok.string == "async" else Token.AWAIT)
+
+    def peek_raw(self) -> TokenInfo:
+        """The next token not dropped by `is_blank`, read ahead of `peek`."""
+        read = [self._stack.pop() if self._stack else self.next_token()]
+        while self.is_blank(read[-1]):
+            read.append(self._stack.pop() if self._stack else self.next_token())
+        self._stack.extend(reversed(read))
+        return read[-1]
+
+    def is_line_start(self) -> bool:
+        return not self._tokens or self._tokens[-1].type in {Token.NEWLINE, Token.INDENT, Token.DEDENT}
+
+    def split_decorator(self, tok: TokenInfo) -> TokenInfo:
+        """`@(` starting a line is a decorator followed by a parenthesized expression."""
+        (lnum, col), line = tok.start, tok.line
+        self._stack.append(TokenInfo(Token.OP, "(", (lnum, col + 1), tok.end, line))
+        return TokenInfo(Token.OP, "@", tok.start, (lnum, col + 1), line)
+
+    def consume_macro_params(self) -> TokenInfo:
         # loop until we get , or ) without consuming it
         start: tuple[int, int] | None = None
         end: tuple[int, int] | None = None
-        paren_level: list[str] = []
         # join strings while handling whitespace
         string = ""
         line = ""
         while True:
-            tok = next(self._tokengen)
-            if tok.type == Token.OP and tok.string[-1] in "([{":  # push paren level
-                paren_level.append(tok.string[-1])
-            if paren_level:
-                if (tok.type == Token.OP) and (opener := self._end_parens.get(tok.string)):
-                    if paren_level[-1] == opener:
-                        paren_level.pop()
-                    else:
-                        raise SyntaxError(f"Unmatched closing paren {tok.string} at {tok.start}")
-            else:
-                if tok.is_exact_type(")"):
-                    self._stack.append(tok)
-                    self._call_macro = False
-                    break
-
-                if tok.is_exact_type(","):
-                    break
-            end = tok.end
+            tok = self.next_token()
+            if tok.is_exact_type(")"):
+                self._stack.append(tok)
+                self._call_macro = False
+                break
+            if tok.is_exact_type(","):
+                break
+            text, end = tok.string, tok.end
+            if tok.type == Token.OP and tok.string[-1] in CLOSING:
+                closer, skipped = skip_balanced(self._tokengen, tok, self.filename)
+                text += "".join(skipped_tok.string for skipped_tok in skipped) + closer.string
+                end = closer.end
             if start is None:
                 start = tok.start
                 line = tok.line
-                string = tok.string
+                string = text
             else:
-                string += tok.string
+                string += text
 
         if (not string) and self._stack:
             # empty params
@@ -171,6 +344,11 @@ class Tokenizer:
             idx -= 1
         return self._tokens[-1]
 
+    def bracket_depth(self, index: int) -> int:
+        """Number of brackets left open before the token at *index*, indexing as a slice end does."""
+        index = max(len(self._tokens) + index, 0) if index < 0 else min(index, len(self._tokens))
+        return max(self._bracket_depths[index], 0)
+
     def get_lines(self, line_numbers: list[int]) -> list[str]:
         """Retrieve source lines corresponding to line numbers."""
         if self._lines:
@@ -189,7 +367,24 @@ class Tokenizer:
                         if seen == n:
                             break
 
-        return [lines[n] for n in line_numbers]
+        # the blank and comment lines of strings have no token to be recorded from
+        return [lines.get(n, "\n") for n in line_numbers]
+
+    def text_between(self, start: tuple[int, int], end: tuple[int, int]) -> str:
+        """The source text from *start* to *end*, without its comments, as in the debug text of f-strings."""
+        numbers = list(range(start[0], end[0] + 1))
+        # blank and comment lines have no token to be recorded from
+        lines = [self._lines.get(n, "\n") for n in numbers] if self._lines else self.get_lines(numbers)
+        lines[-1] = lines[-1][: end[1]]
+        lines[0] = lines[0][start[1] :]
+        if "#" in "".join(lines):
+            # within brackets, the lines of an expression are tokenized without indentation
+            tokens = generate_tokens("(" + "".join(lines) + ")")
+            for tok in reversed([tok for tok in tokens if tok.type == Token.COMMENT]):
+                (row, col), end_col = tok.start, tok.end[1]
+                shift = row == 1  # for the opening bracket
+                lines[row - 1] = lines[row - 1][: col - shift] + lines[row - 1][end_col - shift :]
+        return "".join(lines)
 
     def mark(self) -> Mark:
         return self._index
@@ -6,18 +6,25 @@ from typing import IO, Any
 from peg_parser.tokenize import Token
 from pegen import grammar
 from pegen.build import build_parser
+from pegen.first_sets import FirstSetCalculator
 from pegen.grammar import (
     Alt,
+    Cut,
+    Forced,
     Gather,
+    Group,
     Item,
+    Lookahead,
     NamedItem,
     NameLeaf,
     NegativeLookahead,
+    Opt,
     PositiveLookahead,
     Repeat0,
     Repeat1,
     Rhs,
     Rule,
+    StringLeaf,
 )
 from pegen.parser_generator import ParserGenerator
 from pegen.python_generator import (
@@ -132,7 +139,82 @@ class XonshCallMakerVisitor(PythonCallMakerVisitor):
         return self.cache[node]
 
 
+class StartTokensCalculator(FirstSetCalculator):
+    """The tokens a rule can start with, leaving out the ones only reported as errors."""
+
+    def visit_NameLeaf(self, item: NameLeaf) -> set[str]:
+        if item.value.startswith("invalid_"):
+            return set()
+        return super().visit_NameLeaf(item)
+
+
+class FixedPointFirstSets(FirstSetCalculator):
+    """The tokens each rule can start with, with the ones of invalid rules.
+
+    Unlike `FirstSetCalculator`, rules in an indirect left recursion get the tokens of the whole cycle.
+    """
+
+    def calculate(self) -> dict[str, set[str]]:
+        changed = True
+        while changed:
+            changed = False
+            for name, rule in self.rules.items():
+                tokens = self.visit(rule.rhs) | ({""} if rule.nullable else set())
+                if tokens != self.first_sets.get(name):
+                    self.first_sets[name] = tokens
+                    changed = True
+        return self.first_sets
+
+    def visit_NameLeaf(self, item: NameLeaf) -> set[str]:
+        if item.value not in self.rules:
+            return {item.value}
+        return self.first_sets.get(item.value, set())
+
+    def visit_Forced(self, item: Forced) -> set[str]:
+        return self.visit(item.node)
+
+
+def leading_literals(alt: Alt) -> set[str] | None:
+    """The strings an alternative must start with, when it starts with one or a lookahead for them."""
+    item = alt.items[0].item
+    if isinstance(item, StringLeaf):
+        return {item.value}
+    node = item.node if isinstance(item, PositiveLookahead) else None
+    if isinstance(node, StringLeaf):
+        return {node.value}
+    if isinstance(node, Group) and all(
+        len(a.items) == 1 and isinstance(a.items[0].item, StringLeaf) for a in node.rhs.alts
+    ):
+        return {a.items[0].item.value for a in node.rhs.alts}
+    return None
+
+
+def may_start_with(first: set[str], literal: str) -> bool:
+    """Whether an alternative starting with one of the *first* tokens can start with a (quoted) *literal*."""
+    value = literal[1:-1]
+    hard_keyword = literal[0] == "'" and value.isidentifier()
+    for token in first:
+        if token[:1] in ("'", '"'):
+            if token[1:-1] == value:
+                return True
+        elif token == "NAME":
+            if value.isidentifier() and not hard_keyword:
+                return True
+        elif token == "KEYWORD":
+            if hard_keyword:
+                return True
+        elif token == "SOFT_KEYWORD":
+            if literal[0] == '"':
+                return True
+        elif token not in ("NUMBER", "STRING", "FSTRING_START", "SEARCH_PATH", "NEWLINE", "ENDMARKER"):
+            return True  # the empty string of nullable items, other tokens, and methods of the parser
+    return False
+
+
 class XonshParserGenerator(PythonParserGenerator):
+    # a rule choosing between as many hard keywords jumps to the alternatives for its first token
+    DISPATCH_KEYWORDS = 4
+
     def __init__(
         self,
         grammar: grammar.Grammar,
@@ -150,6 +232,8 @@ class XonshParserGenerator(PythonParserGenerator):
         self.unreachable_formatting = unreachable_formatting or "None  # pragma: no cover"
         self.location_formatting = "**self.span(_lnum, _col)"
         self.cleanup_statements: list[str] = []
+        self.first_sets = FixedPointFirstSets(self.rules)
+        self.first_sets.calculate()
 
     def artifical_rule_from_rhs(self, rhs: Rhs) -> str:
         self.counter += 1
@@ -182,11 +266,27 @@ class XonshParserGenerator(PythonParserGenerator):
         with self.indent():
             self.print(f"KEYWORDS = {tuple(sorted(self.callmakervisitor.keywords))} # fmt: skip")
             self.print(f"SOFT_KEYWORDS = {tuple(sorted(self.callmakervisitor.soft_keywords))} # fmt: skip")
+            self.print_rules_table()
 
         trailer = self.grammar.metas.get("trailer", MODULE_SUFFIX.format(class_name=cls_name))
         if trailer is not None:
             self.print(trailer.rstrip("\n"))
 
+    def print_rules_table(self) -> None:
+        """The grammar of each rule and the tokens it can start with, for `peg_parser.grammar_rules`."""
+        first_sets = StartTokensCalculator(self.rules).calculate()
+        self.print()
+        self.print("# fmt: off")
+        self.print("RULES = {")
+        with self.indent():
+            for name, rule in self.rules.items():
+                if name.startswith(("_", "invalid_")):
+                    continue
+                start = tuple(sorted(first_sets.get(name, set()) - {""}))
+                self.print(f"{name!r}: ({str(rule.flatten())!r}, {start!r}),")
+        self.print("}")
+        self.print("# fmt: on")
+
     def add_return(self, ret_val: str) -> None:
         for stmt in self.cleanup_statements:
             self.print(stmt)
@@ -232,7 +332,10 @@ class XonshParserGenerator(PythonParserGenerator):
                 self.print("_lnum, _col = self._tokenizer.peek().start")
             if is_loop:
                 self.print("children = []")
-            self.visit(rhs, is_loop=is_loop, is_gather=is_gather)
+            if not is_loop and (cases := self.keyword_cases(rhs)):
+                self.print_keyword_dispatch(cases)
+            else:
+                self.visit(rhs, is_loop=is_loop, is_gather=is_gather)
             if is_loop:
                 self.add_return("children")
             else:
@@ -241,6 +344,59 @@ class XonshParserGenerator(PythonParserGenerator):
         if node.name.endswith("without_invalid"):
             self.cleanup_statements.pop()
 
+    def keyword_cases(self, rhs: Rhs) -> dict[tuple[str, ...], list[Alt]] | None:
+        """The alternatives to try for each first token of a statement, as in ``match tok.string:``.
+
+        Alternatives starting with a lookahead for literals are only tried for these, without the
+        lookahead. The others are tried for every token they may start with, and for the default case.
+        """
+        leading = [leading_literals(alt) for alt in rhs.alts]
+        literals = list(dict.fromkeys(lit for lits in leading if lits for lit in sorted(lits)))
+        if sum(lit[0] == "'" and lit[1:-1].isidentifier() for lit in literals) < self.DISPATCH_KEYWORDS:
+            return None
+        first = [self.visit_first(alt) for alt in rhs.alts]
+        # the indexes of the alternatives to try -> the literals trying them
+        choices: dict[tuple[int, ...], list[str]] = {}
+        for literal in literals:
+            indexes = tuple(
+                index
+                for index, (lits, tokens) in enumerate(zip(leading, first))
+                if (literal in lits if lits else may_start_with(tokens, literal))
+            )
+            choices.setdefault(indexes, []).append(literal)
+        cases = {
+            tuple(lits): [self.without_lookahead(rhs.alts[index]) for index in indexes]
+            for indexes, lits in choices.items()
+        }
+        cases[()] = [alt for alt, lits in zip(rhs.alts, leading) if lits is None]
+        return cases
+
+    @staticmethod
+    def without_lookahead(alt: Alt) -> Alt:
+        if isinstance(alt.items[0].item, PositiveLookahead) and len(alt.items) > 1:
+            return Alt(alt.items[1:], action=alt.action)
+        return alt
+
+    def visit_first(self, alt: Alt) -> set[str]:
+        """The tokens an alternative can start with, and the empty string if it can match nothing."""
+        tokens = self.first_sets.visit(alt)
+        nullable = (Lookahead, Opt, Repeat0, Cut)
+        if all(isinstance(item.item, nullable) or "" in self.first_sets.visit(item) for item in alt.items):
+            tokens.add("")
+        return tokens
+
+    def print_keyword_dispatch(self, cases: dict[tuple[str, ...], list[Alt]]) -> None:
+        self.print("match self._tokenizer.peek().string:")
+        with self.indent():
+            for literals, alts in cases.items():
+                pattern = " | ".join(f'"{lit[1:-1]}"' for lit in literals) or "_"
+                self.print(f"case {pattern}:")
+                with self.indent():
+                    for alt in alts:
+                        self.visit(alt, is_loop=False, is_gather=False)
+                    if not alts:
+                        self.print("pass")
+
     def print_action(
         self,
         action: str | None,
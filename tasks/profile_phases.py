@@ -0,0 +1,58 @@
+"""Time spent tokenizing and parsing the files of a corpus, to tell which phase to optimize first.
+
+Tokenization is timed on its own, reading all the tokens of a file, and the rest of a parse is the
+grammar running along with the construction of the nodes, which are interleaved.
+
+    python tasks/profile_phases.py [paths...]
+"""
+
+from __future__ import annotations
+
+import sys
+import time
+from pathlib import Path
+
+CORPUS = Path(__file__).parent.parent / "tests" / "data"
+
+
+def profile(path: Path, repeat: int = 5) -> dict[str, float]:
+    """The best times in seconds of tokenizing and parsing a file, over *repeat* runs."""
+    from peg_parser.parser import XonshParser
+    from peg_parser.tokenize import Trivia, generate_tokens
+
+    source = path.read_text()
+    tokenize = parse = float("inf")
+    for _ in range(repeat):
+        start = time.perf_counter()
+        for _tok in generate_tokens(source, trivia=Trivia.COALESCED):
+            pass
+        middle = time.perf_counter()
+        XonshParser.parse_string(source, mode="exec")
+        end = time.perf_counter()
+        tokenize, parse = min(tokenize, middle - start), min(parse, end - middle)
+    return {"tokenize": tokenize, "grammar and nodes": max(parse - tokenize, 0.0), "total": parse}
+
+
+def main(paths: list[Path]) -> None:
+    files = [file for path in paths for file in (sorted(path.rglob("*.py")) if path.is_dir() else [path])]
+    rows: list[tuple[str, dict[str, float]]] = []
+    for file in files:
+        try:
+            rows.append((file.name, profile(file)))
+        except SyntaxError as e:
+            print(f"skipped {file}: {e}", file=sys.stderr)
+    phases = ("tokenize", "grammar and nodes", "total")
+    rows.append(("(all)", {phase: sum(row[phase] for _, row in rows) for phase in phases}))
+
+    width = max(len(name) for name, _ in rows)
+    print(f"{'file':{width}}  {'tokenize':>14}  {'grammar and nodes':>20}  {'total':>9}")
+    for name, row in rows:
+        share = row["tokenize"] / row["total"] if row["total"] else 0.0
+        print(
+            f"{name:{width}}  {row['tokenize'] * 1000:8.2f}ms {share:4.0%}"
+            f"  {row['grammar and nodes'] * 1000:14.2f}ms {1 - share:4.0%}  {row['total'] * 1000:7.2f}ms"
+        )
+
+
+if __name__ == "__main__":
+    main([Path(arg) for arg in sys.argv[1:]] or [CORPUS])
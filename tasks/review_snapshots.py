@@ -0,0 +1,37 @@
+"""Review the changed snapshots written by ``tests/test_snapshots.py``, accepting or rejecting each one.
+
+    python tasks/review_snapshots.py [--accept-all | --reject-all]
+"""
+
+from __future__ import annotations
+
+import difflib
+import sys
+from pathlib import Path
+
+SNAPSHOTS = Path(__file__).parent.parent / "tests" / "snapshots"
+
+
+def review(new: Path, answer: str | None = None) -> None:
+    approved = new.with_suffix("")
+    old = approved.read_text().splitlines(keepends=True) if approved.exists() else []
+    diff = difflib.unified_diff(old, new.read_text().splitlines(keepends=True), str(approved), str(new))
+    sys.stdout.writelines(diff)
+    while answer not in ("a", "r", "s"):
+        answer = input(f"\n{approved.name}: [a]ccept, [r]eject or [s]kip? ").strip().lower()[:1]
+    if answer == "a":
+        new.replace(approved)
+    elif answer == "r":
+        new.unlink()
+
+
+def main(args: list[str]) -> None:
+    answer = {"--accept-all": "a", "--reject-all": "r"}.get(args[0]) if args else None
+    pending = sorted(SNAPSHOTS.glob("*.new"))
+    for new in pending:
+        review(new, answer)
+    print(f"{len(pending)} snapshots reviewed")
+
+
+if __name__ == "__main__":
+    main(sys.argv[1:])
@@ -19,6 +19,8 @@ def nodes_equal(x, y):
         assert x.value == y.value, (
             f"Constant ast nodes do not have the same value: " f"{x.value!r} != {y.value!r}"
         )
+        assert type(x.value) is type(y.value), f"Constant values differ in type: {x.value!r} != {y.value!r}"
+        assert x.kind == y.kind, f"Constant ast nodes do not have the same kind: {x.kind!r} != {y.kind!r}"
     if isinstance(x, ast.Expr | ast.FunctionDef | ast.ClassDef):
         assert x.lineno == y.lineno, f"Ast nodes do not have the same line number : {x.lineno} != {y.lineno}"
         assert (
@@ -121,6 +123,22 @@ def check_ast(parse_str):
     return factory
 
 
+@pytest.fixture
+def check_ast_attributes():
+    """Compare the tree of peg_parser.parse with that of ast.parse, locations included, and return it"""
+
+    def factory(source: str | bytes, parser=None, **kwargs):
+        # the keyword arguments are given to both, unless *parser* replaces peg_parser.parse
+        from peg_parser import parse
+
+        expected = ast.dump(ast.parse(source, **kwargs), include_attributes=True)
+        tree = parser(source) if parser else parse(source, **kwargs)
+        assert ast.dump(tree, include_attributes=True) == expected
+        return tree
+
+    return factory
+
+
 @pytest.fixture
 def eval_code(parse_str):
     def factory(text: str, mode="eval", **locs):
@@ -9,3 +9,27 @@ def f():
 @d[a]
 def f():
     pass
+
+
+@buttons[0].clicked.connect
+def f():
+    pass
+
+
+@d := deco
+@d if cond else e
+def f():
+    pass
+
+
+@(lambda f: f)
+class A:
+    @(lambda f: f)
+    def f(self):
+        pass
+
+
+@a.b[0](c)  # comment
+@ (lambda f: f)  # comment
+def f():
+    pass
@@ -0,0 +1,25 @@
+# leading comment
+x = 1  # trailing comment
+y = 2# no space before comment
+
+  # comment indented deeper than the code
+# comment at column zero
+
+
+def f(a,  # after a parameter
+      b):  # after the header
+    # first line of the body
+    c = [
+        1,  # inside brackets
+        # comment-only line inside brackets
+        2,
+    ]  # after the closing bracket
+        # over-indented comment
+    return c
+    # trailing comment in the body
+
+# between definitions
+class C:
+    pass
+	# tab indented comment
+# last line comment without newline
\ No newline at end of file
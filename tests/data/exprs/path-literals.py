@@ -36,3 +36,9 @@ __xonsh__.path_literal(f'/foo{1 + 1}')
 
 # Fp"/foo{1+1}"
 __xonsh__.path_literal(f'/foo{1 + 1}')
+
+# pr"C:\foo\{x}"
+__xonsh__.path_literal('C:\\foo\\{x}')
+
+# p"/foo" "/bar"
+__xonsh__.path_literal('/foo/bar')
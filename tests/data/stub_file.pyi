@@ -0,0 +1,42 @@
+from collections.abc import Callable, Iterator
+from typing import Any, ClassVar, Generic, TypeVar, overload
+
+_T = TypeVar("_T")
+__version__: str
+DEFAULT: int = ...
+
+def f() -> int: ...
+def g(x: int, /, y: str = ..., *args: Any, z: bytes = ..., **kwargs: Any) -> None: ...
+async def fetch(url: str) -> bytes: ...
+def documented() -> None:
+    """A docstring as the only body."""
+def both() -> None:
+    """A docstring and an ellipsis."""
+    ...
+
+class Empty: ...
+class EmptyParens(): ...
+class Base(Generic[_T]):
+    attr: ClassVar[int]
+    other: _T
+    def __init__(self, value: _T) -> None: ...
+    @overload
+    def get(self, key: int) -> _T: ...
+    @overload
+    def get(self, key: str, default: _T = ...) -> _T: ...
+    @property
+    def value(self) -> _T: ...
+    @value.setter
+    def value(self, new: _T) -> None: ...
+    def __iter__(self) -> Iterator[_T]: ...
+    class Nested: ...
+
+class Documented:
+    """Only a docstring."""
+
+callback: Callable[[int], str]
+
+
+def one_liner(x: int) -> int: ...
+class OneLiner: x: int; y: str
+def spaced(   ) -> None   :    ...   # trailing comment
@@ -0,0 +1,68 @@
+"""Compare generated parameter lists against CPython's arguments nodes."""
+
+import ast
+import random
+
+import pytest
+
+
+def gen_params(rng: random.Random, is_lambda: bool) -> str:
+    """Generate a random valid parameter list as a def or lambda source."""
+    names = (f"a{idx}" for idx in range(100))
+    seen_default = False
+
+    def annotation():
+        return ": int" if (not is_lambda and rng.random() < 0.3) else ""
+
+    def positional(count: int) -> list[str]:
+        nonlocal seen_default
+        params = []
+        for _ in range(count):
+            if seen_default or rng.random() < 0.3:
+                seen_default = True
+                params.append(f"{next(names)}{annotation()}={rng.randint(0, 9)}")
+            else:
+                params.append(f"{next(names)}{annotation()}")
+        return params
+
+    params = positional(rng.randint(0, 3))
+    if params:
+        params.append("/")
+    params += positional(rng.randint(0, 3))
+
+    star = rng.random()
+    if star < 0.6:
+        params.append(f"*{next(names)}{annotation()}" if star < 0.3 else "*")
+        for _ in range(rng.randint(1 if star >= 0.3 else 0, 3)):
+            default = f"={rng.randint(0, 9)}" if rng.random() < 0.5 else ""
+            params.append(f"{next(names)}{annotation()}{default}")
+    if rng.random() < 0.3:
+        params.append(f"**{next(names)}{annotation()}")
+
+    source = ", ".join(params)
+    if is_lambda:
+        return f"lambda {source}: 0"
+    return f"def f({source}{',' if params and rng.random() < 0.2 else ''}): pass"
+
+
+def dump_field(node: ast.arguments, field: str) -> list[str] | str:
+    value = getattr(node, field)
+    if isinstance(value, list):
+        return [ast.dump(item, include_attributes=True) if item else repr(item) for item in value]
+    return ast.dump(value, include_attributes=True) if value else repr(value)
+
+
+def get_arguments(tree: ast.Module) -> ast.arguments:
+    node = tree.body[0]
+    return node.value.args if isinstance(node, ast.Expr) else node.args
+
+
+@pytest.mark.parametrize("seed", range(10))
+def test_random_parameter_lists(python_parse_str, seed):
+    rng = random.Random(seed)
+    for _ in range(100):
+        source = gen_params(rng, is_lambda=rng.random() < 0.3)
+        exp = get_arguments(ast.parse(source))
+        obs = get_arguments(python_parse_str(source, mode="exec"))
+        for field in exp._fields:
+            assert dump_field(exp, field) == dump_field(obs, field), (source, field)
@@ -28,41 +28,41 @@ def dump_diff(**trees: ast.AST):
 marks = {"marks": pytest.mark.xfail} if sys.version_info < (3, 12) else {}
 
 
-@pytest.mark.parametrize(
-    "filename",
-    [
-        "advanced_decorators.py",
-        pytest.param("assignment.py", **marks),
-        "async.py",
-        "call.py",
-        "comprehensions.py",
-        "expressions.py",
-        pytest.param("fstrings.py", **marks),
-        "function_def.py",
-        "imports.py",
-        "lambdas.py",
-        "multi_statement_per_line.py",
-        "no_newline_at_end_of_file.py",
-        "no_newline_at_end_of_file_with_comment.py",
-        pytest.param("pattern_matching.py", **marks),
-        "simple_decorators.py",
-        "statements.py",
-        "with_statement_multi_items.py",
-        pytest.param(
-            "try_except_group.py",
-            marks=pytest.mark.skipif(
-                sys.version_info <= (3, 11), reason="except* allowed only in Python 3.11+"
-            ),
-        ),
-        pytest.param(
-            "type_params.py",
-            marks=pytest.mark.skipif(
-                sys.version_info <= (3, 12),
-                reason="type declarations allowed only in Python 3.12+",
-            ),
+DATA_FILES = [
+    "advanced_decorators.py",
+    pytest.param("assignment.py", **marks),
+    "async.py",
+    "call.py",
+    "comments.py",
+    "comprehensions.py",
+    "expressions.py",
+    pytest.param("fstrings.py", **marks),
+    "function_def.py",
+    "imports.py",
+    "lambdas.py",
+    "multi_statement_per_line.py",
+    "no_newline_at_end_of_file.py",
+    "no_newline_at_end_of_file_with_comment.py",
+    pytest.param("pattern_matching.py", **marks),
+    "simple_decorators.py",
+    "statements.py",
+    "stub_file.pyi",
+    "with_statement_multi_items.py",
+    pytest.param(
+        "try_except_group.py",
+        marks=pytest.mark.skipif(sys.version_info <= (3, 11), reason="except* allowed only in Python 3.11+"),
+    ),
+    pytest.param(
+        "type_params.py",
+        marks=pytest.mark.skipif(
+            sys.version_info <= (3, 12),
+            reason="type declarations allowed only in Python 3.12+",
         ),
-    ],
-)
+    ),
+]
+
+
+@pytest.mark.parametrize("filename", DATA_FILES)
 def test_pure_python_parsing(python_parse_file, parse_str, filename):
     path = Path(__file__).parent / "data" / filename
     with open(path) as f:
@@ -88,6 +88,39 @@ def test_pure_python_parsing(python_parse_file, parse_str, filename):
     assert not diff
 
 
+def code_summary(code) -> list:
+    """The constants and names of a code object and those nested in it, to compare compiled trees."""
+    consts = [const for const in code.co_consts if not hasattr(const, "co_code")]
+    nested = [code_summary(const) for const in code.co_consts if hasattr(const, "co_code")]
+    return [code.co_name, code.co_names, code.co_varnames, consts, nested]
+
+
+@pytest.mark.parametrize("filename", DATA_FILES)
+def test_pure_python_unparse_and_compile(python_parse_file, parse_str, filename):
+    """Compare the unparsed source and the compiled code, which also show the attributes left out of dumps."""
+    path = Path(__file__).parent / "data" / filename
+    original = ast.parse(path.read_text())
+    pp_ast = python_parse_file(path)
+
+    report = []
+    if diff := unparse_diff(cpython=original, pegen=pp_ast):
+        report.append(f"Unparsed sources differ\n{diff}")
+    unparsed = ast.unparse(pp_ast)
+    if diff := unparse_diff(unparsed=pp_ast, reparsed=parse_str(unparsed, mode="exec")):
+        report.append(f"Unparsed source parses to a different tree\n{diff}")
+    for part in path.read_text().split("\n\n\n"):
+        try:
+            compiled = code_summary(compile(ast.parse(part), str(path), "exec", dont_inherit=True))
+        except SyntaxError:  # fixtures may hold code that only parses
+            continue
+        pp_code = compile(parse_str(part, mode="exec"), str(path), "exec", dont_inherit=True)
+        if compiled != code_summary(pp_code):
+            report.append(f"Compiled constants or names differ for\n{part}")
+    if report and not dump_diff(cpython=original, pegen=pp_ast):
+        report.insert(0, "The dumps match, but not:")
+    assert not report, "\n".join(report)
+
+
 @pytest.mark.parametrize(
     "inp",
     [
@@ -0,0 +1,113 @@
+"""Tests parsing token buffers, and tokenizing them again after edits."""
+
+import ast
+import io
+
+import pytest
+
+from peg_parser import parse, parse_tokens_buffer, tokenize_for_parse
+from peg_parser.tokenize import TokenError, Trivia, generate_tokens
+
+
+def tokens(source: str) -> list:
+    return list(generate_tokens(io.StringIO(source).readline, trivia=Trivia.COALESCED))
+
+
+def large_module(command: str) -> str:
+    lines = []
+    for index in range(1000):
+        if index % 10 == 0:
+            lines.append(f"def f{index}(x):\n")
+        elif index % 10 == 5:
+            lines.append("    if x:\n        y = {'a': [1,\n            2]}\n")
+        else:
+            lines.append(f"    x = x + {index}  # add\n")
+    lines[501] = f"    {command}\n"
+    return "".join(lines)
+
+
+def test_wrap_subprocess_line():
+    # as the execer, wrapping a line that is not Python in ![...]
+    source = large_module("ls -l")
+    buffer = tokenize_for_parse(source)
+    assert buffer.tokenized_lines > 1000
+    start = source.index("ls -l")
+    wrapped = buffer.replace_range(start, start + len("ls -l"), "![ls -l]")
+
+    assert wrapped.source == large_module("![ls -l]")
+    assert wrapped.tokenized_lines == 1
+    assert wrapped.tokens == tokens(wrapped.source)
+    assert wrapped.tokens[-10] is buffer.tokens[-10]
+    assert ast.dump(parse_tokens_buffer(wrapped)) == ast.dump(parse(wrapped.source))
+
+
+@pytest.mark.parametrize(
+    "old, new, tokenized_lines",
+    [
+        ("ls -l", "a = 1\n    b = (2,\n3)", 3),
+        ("ls -l", "", 2),
+        ("ls -l", "if x:\n    \tpass", 3),
+        ("    ls -l\n", "", 1),
+        ("y = {'a': [1,\n            2]}", "y = 2", 1),
+    ],
+)
+def test_edits_changing_lines(old, new, tokenized_lines):
+    source = large_module("ls -l")
+    start = source.index(old)
+    edited = tokenize_for_parse(source).replace_range(start, start + len(old), new)
+    assert edited.source == source.replace(old, new, 1)
+    assert edited.tokenized_lines == tokenized_lines
+    assert edited.tokens == tokens(edited.source)
+
+
+def test_edit_with_inconsistent_tabs():
+    source = large_module("ls -l")
+    start = source.index("ls -l")
+    edited = tokenize_for_parse(source).replace_range(start, start + len("ls -l"), "if x:\n\tpass")
+    with pytest.raises(TabError):
+        tokens(edited.source)
+    with pytest.raises(TabError):
+        parse_tokens_buffer(edited)
+
+
+@pytest.mark.parametrize(
+    "source, start, end, text",
+    [
+        ("x = 1\n", 6, 6, "if x:\n    y = 2\n"),
+        ("if x:\n    y = 1\n", 16, 16, "    z = 2\n"),
+        ("if x:\n    y = 1\n", 16, 16, "z = 2"),
+        ("x = 1\ny = 2", 10, 11, "(3 +\n 4)"),
+        ("x = 1\n", 0, 0, "# first\n\n"),
+    ],
+)
+def test_edits_at_the_ends(source, start, end, text):
+    edited = tokenize_for_parse(source).replace_range(start, end, text)
+    assert edited.tokens == tokens(edited.source)
+    assert ast.dump(parse_tokens_buffer(edited)) == ast.dump(parse(edited.source))
+
+
+@pytest.mark.parametrize(
+    "old, new, exc, msg",
+    [
+        ("def f0(x):", "x = (", SyntaxError, "invalid syntax"),
+        ("ls -l", "ls\n      -l", IndentationError, "unexpected indent"),
+        ("ls -l", "if x:\n        z\n      w", IndentationError, "unindent does not match"),
+        ("ls -l", "x = '''", TokenError, "EOF in multi-line string"),
+    ],
+)
+def test_errors_of_edited_source(old, new, exc, msg):
+    source = large_module("ls -l")
+    start = source.index(old)
+    edited = tokenize_for_parse(source).replace_range(start, start + len(old), new)
+    with pytest.raises(exc, match=msg) as from_buffer:
+        parse_tokens_buffer(edited)
+    with pytest.raises(exc) as from_source:
+        parse(edited.source)
+    assert from_buffer.value.args == from_source.value.args
+
+
+def test_parse_modes():
+    buffer = tokenize_for_parse("$HOME")
+    assert isinstance(parse_tokens_buffer(buffer, mode="eval"), ast.Expression)
+    buffer = buffer.replace_range(0, 5, "x = 1")
+    assert isinstance(parse_tokens_buffer(buffer, mode="single"), ast.Interactive)
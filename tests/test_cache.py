@@ -0,0 +1,85 @@
+"""Tests the in-process cache of parsed trees."""
+
+import ast
+
+import pytest
+
+import peg_parser.tokenize
+from peg_parser import cache_clear, cache_info, parse_cached, set_cache_size
+
+
+@pytest.fixture(autouse=True)
+def fresh_cache():
+    cache_clear()
+    yield
+    set_cache_size(128)
+    cache_clear()
+
+
+@pytest.fixture
+def tokenize_calls(monkeypatch):
+    calls = []
+    generate_tokens = peg_parser.tokenize.generate_tokens
+
+    def counting(*args, **kwargs):
+        calls.append(args)
+        return generate_tokens(*args, **kwargs)
+
+    monkeypatch.setattr(peg_parser.tokenize, "generate_tokens", counting)
+    return calls
+
+
+def test_hit_skips_tokenizing(tokenize_calls):
+    first = parse_cached("x = 1\n")
+    second = parse_cached("x = 1\n")
+    assert len(tokenize_calls) == 1
+    assert ast.dump(first) == ast.dump(second)
+    assert cache_info() == (1, 1, 128, 1)
+
+
+def test_hits_return_fresh_copies():
+    first = parse_cached("x = [1, 2]\n")
+    first.body.clear()
+    second = parse_cached("x = [1, 2]\n")
+    assert len(second.body) == 1
+    assert second is not parse_cached("x = [1, 2]\n")
+
+
+def test_copy_opt_out_shares_the_tree():
+    assert parse_cached("x = 1\n", copy=False) is parse_cached("x = 1\n", copy=False)
+
+
+def test_mode_is_part_of_the_key():
+    assert isinstance(parse_cached("x"), ast.Module)
+    assert isinstance(parse_cached("x", mode="eval"), ast.Expression)
+    assert cache_info().misses == 2
+
+
+def test_least_recently_used_is_evicted(tokenize_calls):
+    set_cache_size(2)
+    parse_cached("a\n")
+    parse_cached("b\n")
+    parse_cached("a\n")
+    parse_cached("c\n")  # evicts "b"
+    assert cache_info() == (1, 3, 2, 2)
+    parse_cached("a\n")
+    parse_cached("b\n")
+    assert cache_info() == (2, 4, 2, 2)
+    assert len(tokenize_calls) == 4
+
+
+def test_errors_are_not_cached():
+    for _ in range(2):
+        with pytest.raises(SyntaxError):
+            parse_cached("x = = 1\n")
+    assert cache_info() == (0, 2, 128, 0)
+
+
+def test_zero_size_disables_the_cache(tokenize_calls):
+    parse_cached("x\n")
+    set_cache_size(0)
+    parse_cached("x\n")
+    assert cache_info() == (0, 2, 0, 0)
+    assert len(tokenize_calls) == 2
+    with pytest.raises(ValueError):
+        set_cache_size(-1)
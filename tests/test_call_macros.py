@@ -91,3 +91,18 @@ def test_macro_call_one_trailing_space(run, s, xsh):
     method = run(f)
     args = method.call_args.args[1]
     assert [ar.strip() for ar in args] == [s.strip()]
+
+
+@pytest.mark.parametrize(
+    "source, message, offset",
+    [
+        ("f!(a, (b ]))", "closing parenthesis ']' does not match opening parenthesis '('", 10),
+        ("f!(a, $(ls ]))", "closing parenthesis ']' does not match opening parenthesis '('", 12),
+        ("f!(a, (b", "'(' was never closed", 7),
+        ("f!(a, b", "'!(' was never closed", 2),
+    ],
+)
+def test_macro_call_unbalanced(python_parse_str, source, message, offset):
+    with pytest.raises(SyntaxError) as e:
+        python_parse_str(source, mode="exec")
+    assert (e.value.msg, e.value.offset) == (message, offset)
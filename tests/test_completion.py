@@ -0,0 +1,108 @@
+"""Tests the context reported at a cursor for completers."""
+
+import pytest
+
+from peg_parser import context_at
+
+
+def context(source: str) -> dict:
+    """The context at the ``|`` in *source*, with the text after the cursor kept in place."""
+    offset = source.index("|")
+    return context_at(source[:offset] + source[offset + 1 :], offset)
+
+
+@pytest.mark.parametrize(
+    "source, calls",
+    [
+        ("f(a, g(b, |", [("f", 1), ("g", 1)]),
+        ("f(a, g(b), |)", [("f", 2)]),
+        ("f(a, [1, 2], {'k': (3, 4)}, |", [("f", 3)]),
+        ("obj.meth(1)(2, |", [("obj.meth(1)", 1)]),
+        ("x = mod.f(\n    a,\n    |", [("mod.f", 1)]),
+        ("print($HOME, $(ls -|", [("print", 1)]),
+        ("f(x, 'a,b|", [("f", 1)]),
+        ("if (|", []),
+        ("def f(a, |", []),
+        ("class A(B, |", [("A", 1)]),
+    ],
+)
+def test_calls(source, calls):
+    assert [(call["func"], call["arg_index"]) for call in context(source)["calls"]] == calls
+
+
+@pytest.mark.parametrize(
+    "source, base",
+    [
+        ("x.y.|", "x.y"),
+        ("x.y.ab|", "x.y"),
+        ("x.y.|ab", "x.y"),
+        ("if x: return a.b(c)[0].|", "a.b(c)[0]"),
+        ("'abc'.|", "'abc'"),
+        ("f(a, foo(b).|", "foo(b)"),
+        ("$HOME.|", "$HOME"),
+        ("x = 1.|", None),
+        ("from .|", None),
+        ("f(a, |", None),
+        ("x. y|", "x"),
+    ],
+)
+def test_attribute(source, base):
+    attribute = context(source)["attribute"]
+    if base is None:
+        assert attribute is None
+    else:
+        assert attribute["base"] == base
+        assert source[attribute["start"] : attribute["end"]] == base
+
+
+@pytest.mark.parametrize(
+    "source, statement",
+    [
+        ("import os.pa|", "import"),
+        ("from os import (pa|", "from"),
+        ("if x: return a.|", "return"),
+        ("for x in y: z.|", None),
+        ("if x:\n    foo(a|", None),
+        ("x = 1; del y|", "del"),
+        ("async def f(|", "def"),
+        ("x: int = f(|", None),
+        ("if lambda: 1: raise E(|", "raise"),
+        ("import os\n|", None),
+    ],
+)
+def test_statement(source, statement):
+    assert context(source)["statement"] == statement
+
+
+@pytest.mark.parametrize(
+    "source, string",
+    [
+        ("f('abc|", ("string", 2, False)),
+        ("x = '''ab\ncd|", ("string", 4, False)),
+        ('f"ab{x}c|', ("fstring", 0, False)),
+        ('f"{a.|', ("fstring", 0, True)),
+        ('f"{x:>{wid|', ("fstring", 0, True)),
+        ("f'{x}\"abc|", ("fstring", 0, False)),
+        ('f"{g("ab|', ("string", 5, False)),
+        ("x = f('a', f'{y}|", ("fstring", 11, False)),
+        ("x = 'a' + |", None),
+    ],
+)
+def test_string(source, string):
+    found = context(source)["string"]
+    assert (found and (found["kind"], found["start"], found["in_field"])) == string
+
+
+def test_fstring_replacement_field():
+    found = context('print(f"{user.na|me} is here")')
+    assert found["calls"] == [{"func": "print", "arg_index": 0}]
+    assert found["attribute"] == {"base": "user", "start": 9, "end": 13}
+    assert found["string"] == {"kind": "fstring", "start": 6, "in_field": True}
+
+
+def test_mid_import():
+    found = context("import sys\nfrom os.pa|th import join\n")
+    assert found["statement"] == "from"
+    assert found["attribute"] == {"base": "os", "start": 16, "end": 18}
+    assert found["calls"] == []
+    assert found["string"] is None
@@ -0,0 +1,79 @@
+"""Tests the decoding of escape sequences in string literals, with `ast.literal_eval` as the reference."""
+
+import ast
+import sys
+
+import pytest
+
+from peg_parser import parse
+from peg_parser.subheader import decode_escapes
+
+UNICODE_ERROR = "(unicode error) 'unicodeescape' codec can't decode bytes in"
+ESCAPE = SyntaxWarning if sys.version_info >= (3, 12) else DeprecationWarning
+
+ESCAPES = [
+    r"\\ \' \" \a \b \f \n \r \t \v",
+    "a\\\nb",
+    r"\x41\x7f\xff",
+    r"é€",
+    r"\U0001F600\U00000041",
+    r"\0 \7 \01 \101 \377 \1234",
+    r"\N{GREEK SMALL LETTER ALPHA}\N{bullet}\N{EM DASH}",
+    r"\ud800 \udfff 😀",
+    r"\d \w \% \8",
+    "é\\x41 \\é \\\\é ☃\\u2603",
+]
+
+
+@pytest.mark.parametrize("text", ESCAPES)
+def test_escape_classes(text):
+    assert decode_escapes(text) == ast.literal_eval(f'"""{text}"""')
+
+
+@pytest.mark.parametrize("text", [esc for esc in ESCAPES if esc.isascii() and "\\N" not in esc])
+def test_bytes_escape_classes(text):
+    assert decode_escapes(text, is_bytes=True) == ast.literal_eval(f'b"""{text}"""')
+
+
+@pytest.mark.parametrize("prefix", ["", "u", "r", "b", "rb", "f"])
+@pytest.mark.parametrize("text", ESCAPES)
+def test_string_values(prefix, text):
+    if "b" in prefix and not text.isascii():
+        pytest.skip("bytes can only contain ASCII literal characters")
+    source = f'{prefix}"""{text}"""'
+    assert ast.dump(parse(source, mode="eval")) == ast.dump(ast.parse(source, mode="eval"))
+
+
+def test_lone_surrogates():
+    tree = parse('"\\ud800" + f"{x}\\udc00"', mode="eval")
+    assert (tree.body.left.value, tree.body.right.values[1].value) == ("\ud800", "\udc00")
+
+
+def test_invalid_escapes_warn():
+    tree, found = parse('x = 1\ny = "a\\q" + f"\\N{BULLET}\\d{x}"\n', warnings=True)
+    assert [tuple(entry) for entry in found] == [
+        (ESCAPE, "invalid escape sequence '\\q'", 2, 6),
+        (ESCAPE, "invalid escape sequence '\\d'", 2, 24),
+    ]
+    assert tree.body[1].value.left.value == "a\\q"
+
+
+@pytest.mark.parametrize(
+    "source, msg",
+    [
+        ('x = 1\ny = "\\N{BOGUS}"', f"{UNICODE_ERROR} position 0-8: unknown Unicode character name"),
+        ('x = 1\ny = "ab" "é\\x4"', f"{UNICODE_ERROR} position 10-12: truncated \\xXX escape"),
+        ('x = 1\ny = b"\\x4"', "(value error) invalid \\x escape at position 0"),
+        ('x = 1\ny = rb"é"', "bytes can only contain ASCII literal characters"),
+    ],
+)
+def test_malformed_escapes(source, msg):
+    with pytest.raises(SyntaxError) as expected:
+        ast.parse(source)
+    with pytest.raises(SyntaxError) as e:
+        parse(source)
+    assert e.value.msg.startswith(msg)
+    assert (e.value.msg, e.value.lineno) == (expected.value.msg, expected.value.lineno)
+    # before Python 3.12, the errors are reported at the end of the string
+    if sys.version_info >= (3, 12):
+        assert (e.value.offset, e.value.end_offset) == (expected.value.offset, expected.value.end_offset)
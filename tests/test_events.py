@@ -0,0 +1,79 @@
+"""Tests the streaming events reported while parsing."""
+
+import ast
+from pathlib import Path
+
+import pytest
+
+from peg_parser import parse_events
+
+DATA = Path(__file__).parent / "data"
+
+
+def bare_excepts(source: str) -> list[int]:
+    """A lint check that only needs the except handlers."""
+    found = []
+
+    def on_event(kind, location, node):
+        if node.type is None:
+            found.append(location["lineno"])
+
+    parse_events(source, on_event, kinds=[ast.ExceptHandler])
+    return found
+
+
+def test_bare_except_linter():
+    source = (DATA / "statements.py").read_text()
+    handlers = [node for node in ast.walk(ast.parse(source)) if isinstance(node, ast.ExceptHandler)]
+    expected = sorted(handler.lineno for handler in handlers if handler.type is None)
+    assert expected
+    assert bare_excepts(source) == expected
+
+
+def test_events_in_source_order():
+    source = (
+        "import os\n\ndef f(x=[]):\n    try:\n        g(h())\n    except:\n        pass\n\nclass C: y = 1\n"
+    )
+    events = []
+    parse_events(source, lambda kind, location, node: events.append((kind, location["lineno"])))
+    assert events == [
+        ("Import", 1),
+        ("FunctionDef", 3),
+        ("Call", 5),
+        ("Call", 5),
+        ("ExceptHandler", 6),
+        ("ClassDef", 9),
+        ("Assign", 9),
+    ]
+
+
+def test_events_location():
+    events = []
+    parse_events("x = f(1)\n", lambda *event: events.append(event), kinds=[ast.Call])
+    ((kind, location, node),) = events
+    assert kind == "Call"
+    assert location == {"lineno": 1, "col_offset": 4, "end_lineno": 1, "end_col_offset": 8}
+    assert isinstance(node, ast.Call)
+
+
+def test_callback_aborts_parsing():
+    seen = []
+
+    class Abort(Exception):
+        pass
+
+    def on_event(kind, location, node):
+        seen.append(location["lineno"])
+        raise Abort
+
+    # the syntax error on the last line is never reached
+    with pytest.raises(Abort):
+        parse_events("a = 1\nb = 2\nc = = 3\n", on_event)
+    assert seen == [1]
+
+
+def test_events_before_syntax_error():
+    seen = []
+    with pytest.raises(SyntaxError):
+        parse_events("a = 1\nb = = 2\n", lambda kind, location, node: seen.append(kind))
+    assert seen == ["Assign"]
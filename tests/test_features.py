@@ -0,0 +1,78 @@
+"""Tests the report of the syntax features used by a source."""
+
+import sys
+
+import pytest
+
+from peg_parser import detect_features
+from peg_parser.features import FEATURE_VERSIONS
+
+
+def starts(source: str) -> dict[str, list[tuple[int, int]]]:
+    return {
+        name: [(loc["lineno"], loc["col_offset"]) for loc in locations]
+        for name, locations in detect_features(source).items()
+    }
+
+
+@pytest.mark.parametrize(
+    "source, expected",
+    [
+        ("if (n := 10) > 5:\n    pass", {"walrus": [(1, 4)]}),
+        ("def f(a, /, b): pass\ng = lambda x, /: x", {"positional_only_parameters": [(1, 6), (2, 11)]}),
+        ("with (open(a) as f, open(b)):\n    pass", {"parenthesized_context_managers": [(1, 0)]}),
+        (
+            "async def f():\n    async with (a as b,):\n        pass",
+            {"parenthesized_context_managers": [(2, 4)]},
+        ),
+        ("match x:\n    case 1:\n        pass", {"match_statement": [(1, 0)]}),
+        pytest.param(
+            "try:\n    pass\nexcept* E:\n    pass",
+            {"except_star": [(1, 0)]},
+            marks=pytest.mark.skipif(sys.version_info < (3, 11), reason="requires python3.11"),
+        ),
+        ('s = f"{"a"}"', {"fstring_nesting": [(1, 7)]}),
+        ("s = f'{x # comment\n}'", {"fstring_nesting": [(1, 9), (1, 18)]}),
+        ("s = f\"{'\\n'}\"", {"fstring_nesting": [(1, 7)]}),
+        ('s = f"{f"{1}"}"', {"fstring_nesting": [(1, 7)]}),
+        ("x = $(ls @$(which x)) + ![echo]", {"subprocess": [(1, 4), (1, 9), (1, 24)]}),
+        ("y = `.*\\.py`", {"search_path": [(1, 4)]}),
+        ("f!(a b)\nwith! ctx:\n    body\n", {"macro": [(1, 0), (2, 0)]}),
+    ],
+)
+def test_features(source, expected):
+    assert starts(source) == expected
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "with (a, b):\n    pass",
+        "with (a):\n    pass",
+        "s = f\"{'a'}\" + f'{\"a\"}' + f\"\"\"{\"a\"}\"\"\" + f'''{f\"{x}\"}'''",
+        "s = f'''{x\n}'''",
+        "def f(a, *, b): pass",
+        "x = __xonsh__.pathsearch('a')",
+    ],
+)
+def test_older_syntax(source):
+    assert detect_features(source) == {}
+
+
+@pytest.mark.skipif(sys.version_info < (3, 12), reason="requires python3.12")
+def test_type_parameters():
+    found = detect_features("type T = int\ndef h[T](x: T): pass\nclass C[T, U]: pass\n")
+    assert found == {
+        "type_statement": [{"lineno": 1, "col_offset": 0, "end_lineno": 1, "end_col_offset": 12}],
+        "type_parameters": [
+            {"lineno": 2, "col_offset": 6, "end_lineno": 2, "end_col_offset": 7},
+            {"lineno": 3, "col_offset": 8, "end_lineno": 3, "end_col_offset": 9},
+        ],
+    }
+
+
+def test_features_in_source_order():
+    found = detect_features("match (y := 1):\n    case _:\n        x = $(ls)\n(z := 2)\n")
+    assert list(found) == ["walrus", "match_statement", "subprocess"]
+    assert [loc["lineno"] for loc in found["walrus"]] == [1, 4]
+    assert all(name in FEATURE_VERSIONS for name in list(found)[:2])
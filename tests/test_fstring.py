@@ -1,5 +1,10 @@
+import re
+import sys
+
 import pytest
 
+requires_py312 = pytest.mark.skipif(sys.version_info < (3, 12), reason="requires python3.12")
+
 
 @pytest.mark.parametrize(
     "inp",
@@ -16,3 +21,119 @@ import pytest
 )
 def test_f_env_var(inp, parse_str):
     parse_str(inp)
+
+
+@pytest.mark.skipif(sys.version_info < (3, 12), reason="f-string nodes differ before Python 3.12")
+@pytest.mark.parametrize("inp", ['f"{a:{w}}"', 'f"{x:{y[0]}.{z}}"', 'f"{a:>{w}} {c}"'])
+def test_nested_format_spec(inp, parse_str):
+    import ast
+
+    assert ast.dump(parse_str(inp, mode="exec")) == ast.dump(ast.parse(inp))
+
+
+@pytest.mark.parametrize(
+    "inp",
+    [
+        r'f"\n{x}"',
+        r'rf"\n{x}"',
+        r'Rf"\t\\{x}\\"',
+        r'f"\t\\ {x} \\"',
+        r'f"{{x}} {x} }}"',
+        r'rf"{{\d+}}{x}"',
+        r'f"a}}b{{c"',
+        'f"""{{\n}}{x}"""',
+        r'f"\N{BULLET} {x} \u00e9 \U0001F600 \x41"',
+        r'rf"\N{x}"',
+        r'f"{x:\t}"',
+        r'f"{x!r:>10} \N{EM DASH}"',
+    ],
+)
+def test_fstring_literal_parts(inp, parse_str):
+    import ast
+
+    assert ast.dump(parse_str(inp, mode="exec")) == ast.dump(ast.parse(inp))
+
+
+@pytest.mark.parametrize(
+    "inp",
+    [
+        'f"{x=}"',
+        'f"{ x + 1 = }"',
+        "f'{  x\t=\t}'",
+        'f"{x = !r}"',
+        'f"{x=!s:>10}"',
+        'f"a{x = :.2f}b{y=}"',
+        'f"{x=}" "a" f"{y =}"',
+        'f"""{\nx\n=\n}"""',
+        'f"{ f(a, b) = }"',
+        'f"{x==1=}"',
+        "f'{(x:=1)=}'",
+        pytest.param('f"{x = :{y}}"', marks=requires_py312),
+        pytest.param(r"""f'{"\n"=}'""", marks=requires_py312),
+        pytest.param(r"""rf'{"\n"=}'""", marks=requires_py312),
+        "f' {{1}} {x=}'",
+    ],
+)
+def test_debug_fields(inp, parse_str):
+    import ast
+
+    # the source text of a field is kept as written, up to its conversion or format spec
+    locations = sys.version_info >= (3, 12)
+    expected = ast.dump(ast.parse(inp), include_attributes=locations)
+    assert ast.dump(parse_str(inp, mode="exec"), include_attributes=locations) == expected
+
+
+@requires_py312
+def test_debug_field_comments(parse_str):
+    import ast
+
+    inp = 'f"{x # comment\n=}"'
+    assert ast.dump(parse_str(inp, mode="exec")) == ast.dump(ast.parse(inp))
+    assert parse_str(inp, mode="exec").body[0].value.values[0].value == "x \n="
+
+
+# PEP 701 replacement fields -> the error of Python 3.11 and its position (lineno, offset, end_offset)
+PEP701_FIELDS = [
+    ('f"{"a"}"', "f-string: expecting '}'", (1, 5, 6)),
+    ("f'''{'''a'''}'''", "f-string: expecting '}'", (1, 9, 10)),
+    ('x = f"{f"{1}"}"', "f-string: expecting '}'", (1, 10, 11)),
+    ("f'{x!r:{'a'}}'", "f-string: expecting '}'", (1, 10, 11)),
+    ("f'{x}' f'{'a'}'", "f-string: expecting '}'", (1, 12, 13)),
+    ("f\"{'\\n'}\"", "f-string expression part cannot include a backslash", (1, 5, 5)),
+    ("f'{x:{\"\\n\"}}'", "f-string expression part cannot include a backslash", (1, 8, 8)),
+    ("f'''{x}\n{'a\\\\b'}'''", "f-string expression part cannot include a backslash", (2, 4, 4)),
+    ("f'''{x # c\n}'''", "f-string expression part cannot include '#'", (1, 8, 8)),
+    ("y = 1 + f'{x\n}'", "unterminated string literal (detected at line 1)", (1, 9, 9)),
+    ("f'{x # c\n}'", "unterminated string literal (detected at line 1)", (1, 1, 1)),
+]
+
+
+@pytest.mark.parametrize("inp, msg, position", PEP701_FIELDS)
+def test_pep701_fields_before_312(inp, msg, position):
+    from peg_parser import parse
+
+    with pytest.raises(SyntaxError) as excinfo:
+        parse(inp, feature_version=11)
+    exc = excinfo.value
+    assert (exc.msg, exc.lineno, exc.offset, exc.end_offset) == (msg, *position)
+    if sys.version_info < (3, 12):
+        with pytest.raises(SyntaxError, match=re.escape(msg)):
+            compile(inp, "<string>", "exec")
+
+
+@pytest.mark.parametrize(
+    "inp",
+    ["f'{\"a\"}'", 'f"""{"a"}"""', "f'''{x\n}'''", "f'{x:\\n}'", "f'{x}' 'a{\\n}'", "f'{$HOME}{x!r:>{w}}'"],
+)
+def test_fields_before_312(inp):
+    from peg_parser import parse
+
+    parse(inp, feature_version=11)
+
+
+@requires_py312
+@pytest.mark.parametrize("inp", [inp for inp, _, _ in PEP701_FIELDS if "unterminated" not in _])
+def test_pep701_fields(inp, parse_str):
+    import ast
+
+    assert ast.dump(parse_str(inp, mode="exec")) == ast.dump(ast.parse(inp))
@@ -0,0 +1,52 @@
+"""Tests the description of the grammar rules."""
+
+import inspect
+
+from peg_parser import grammar_rules
+from peg_parser.parser import XonshParser
+
+
+def test_every_rule_listed():
+    methods = {
+        name
+        for name, value in vars(XonshParser).items()
+        if inspect.isfunction(value) and not name.startswith(("_", "invalid_"))
+    }
+    assert {rule["name"] for rule in grammar_rules()} == methods
+
+
+def test_rule_comments_match():
+    for rule in grammar_rules():
+        source = inspect.getsource(getattr(XonshParser, rule["name"]))
+        assert f"# {rule['name']}: {rule['grammar']}\n" in source
+
+
+def test_first_tokens():
+    rules = {rule["name"]: rule for rule in grammar_rules()}
+    assert rules["import_stmt"]["first"] == ["'from'", "'import'"]
+    assert rules["match_stmt"]["first"] == ['"match"']
+    assert {"NAME", "NUMBER", "'('", "'$('", "'lambda'"} <= set(rules["expression"]["first"])
+    assert "'if'" not in rules["expression"]["first"]
+    assert "NEWLINE" in rules["block"]["first"]
+
+
+def test_statement_keyword_dispatch():
+    import io
+
+    from peg_parser.tokenize import generate_tokens
+    from peg_parser.tokenizer import Tokenizer
+
+    class CountingParser(XonshParser):
+        def expect(self, typ):
+            tok = super().expect(typ)
+            if tok is None and typ in keywords:
+                missed.append(typ)
+            return tok
+
+    # the statements jump to the rule for their first token, instead of trying each keyword in turn
+    keywords = {"pass", "break", "continue", "global", "nonlocal", "del", "assert", "raise", "return"}
+    missed: list[str] = []
+    source = "def f(x):\n    global y\n    del x\n    assert y\n    return y\nwhile c:\n    break\n"
+    source += "for a in b:\n    continue\nimport os\npass\nraise E\nx = 1\n"
+    CountingParser(Tokenizer(generate_tokens(io.StringIO(source).readline))).parse("file")
+    assert missed == []
@@ -0,0 +1,25 @@
+"""Tests that importing the package stays cheap."""
+
+import subprocess
+import sys
+
+import peg_parser
+
+
+def run(*args: str) -> subprocess.CompletedProcess:
+    return subprocess.run([sys.executable, *args], capture_output=True, text=True, check=True)
+
+
+def test_import_skips_parser():
+    loaded = run("-c", "import sys, peg_parser; print([m for m in sys.modules if m.startswith('peg')])")
+    assert loaded.stdout.strip() == "['peg_parser']"
+
+
+def test_lazy_attributes():
+    from peg_parser.parser import XonshParser
+    from peg_parser.subheader import ParseWarning
+    from peg_parser.tokenizer import Tokenizer
+
+    assert (peg_parser.XonshParser, peg_parser.Tokenizer) == (XonshParser, Tokenizer)
+    assert peg_parser.ParseWarning is ParseWarning
+    assert not hasattr(peg_parser, "missing")
@@ -0,0 +1,201 @@
+"""Tests reading the interface of a module without parsing its blocks."""
+
+import pytest
+
+from peg_parser import interface, scan_functions, scan_module_interface
+
+MODULE = """\
+from __future__ import annotations
+
+import os, sys as system
+from typing import TYPE_CHECKING
+from . import sibling
+from ..pkg.mod import a as b, c
+
+if TYPE_CHECKING:
+    from collections.abc import Iterator
+    import typing as t
+
+    class OnlyTyped:
+        import hidden
+try:
+    import ujson as json
+except ImportError:  # the fallback
+    import json
+else:
+    def dumps(x):
+        import inner
+        return json.dumps(x)
+
+__all__ = ["Outer", "helper"]
+__all__ += ("later",)
+
+
+@decorator
+class Outer:
+    class Nested:
+        def method(self):
+            import deep
+
+    def method(self):
+        pass
+
+
+async def helper(
+    x,
+):
+    from nowhere import nothing
+    if x:
+        import deeper
+    return x
+"""
+
+
+def test_module_interface():
+    found = scan_module_interface(MODULE)
+    assert [
+        (entry["module"], entry["name"], entry["alias"], entry["lineno"], entry["conditional"])
+        for entry in found["imports"]
+    ] == [
+        ("__future__", "annotations", None, 1, False),
+        ("os", None, None, 3, False),
+        ("sys", None, "system", 3, False),
+        ("typing", "TYPE_CHECKING", None, 4, False),
+        (".", "sibling", None, 5, False),
+        ("..pkg.mod", "a", "b", 6, False),
+        ("..pkg.mod", "c", None, 6, False),
+        ("collections.abc", "Iterator", None, 9, True),
+        ("typing", None, "t", 10, True),
+        ("ujson", None, "json", 15, True),
+        ("json", None, None, 17, True),
+    ]
+    assert found["__all__"] == ["Outer", "helper", "later"]
+    assert found["definitions"] == [
+        {"name": "OnlyTyped", "kind": "class", "lineno": 12, "conditional": True},
+        {"name": "dumps", "kind": "function", "lineno": 19, "conditional": True},
+        {"name": "Outer", "kind": "class", "lineno": 28, "conditional": False},
+        {"name": "helper", "kind": "function", "lineno": 37, "conditional": False},
+    ]
+
+
+def test_blocks_are_skipped(monkeypatch):
+    scanned = []
+    scan_statement = interface.scan_statement
+
+    def counting_scan_statement(source, offsets, line, conditional, found):
+        scanned.append(line[0].start[0])
+        scan_statement(source, offsets, line, conditional, found)
+
+    monkeypatch.setattr(interface, "scan_statement", counting_scan_statement)
+    scan_module_interface(MODULE)
+    assert scanned == [1, 3, 4, 5, 6, 9, 10, 12, 15, 17, 19, 23, 24, 27, 28, 37]
+
+
+@pytest.mark.parametrize(
+    "source, expected",
+    [
+        ("x = 1", None),
+        ("__all__ = ['a', 'b']", ["a", "b"]),
+        ("__all__ = ('a',)\n__all__ = ['b']", ["b"]),
+        ("__all__ = []\n__all__ += ['a']", ["a"]),
+        ("__all__ += ['a']", None),
+        ("__all__ = [name for name in dir()]", None),
+        ("__all__ = ['a', b]", None),
+        ("__all__ = ['a'] + other.__all__", None),
+        ("__all__ = ['a']\n__all__.append('b')", None),
+        ("__all__ = ['a']\n__all__[0] = 'b'", None),
+        ("__all__ = ['a']\n__all__ += other", None),
+        ("__all__ = ['a']\nif x:\n    __all__ += ['b']", None),
+        ("__all__ = ['a']\nif x: __all__ = ['b']", None),
+        ("__all__ = ['a']\nfor name in x:\n    __all__.append(name)", None),
+        ("__all__ = ['a']\nfor name in x:\n    if name:\n        __all__.append(name)", None),
+        ("__all__ = ['a']\nprint(__all__, __all__[0])", ["a"]),
+        ("__all__ = ['a']\ndef __dir__():\n    return __all__", ["a"]),
+        ("__all__ = ['a']\nx = $(ls)\n", ["a"]),
+    ],
+)
+def test_literal_all(source, expected):
+    assert scan_module_interface(source)["__all__"] == expected
+
+
+def test_one_line_conditional_blocks():
+    source = "if TYPE_CHECKING: import a; from b import c\ntry: import d\nexcept: pass\n"
+    found = scan_module_interface(source)
+    assert [(entry["module"], entry["conditional"]) for entry in found["imports"]] == [
+        ("a", True),
+        ("b", True),
+        ("d", True),
+    ]
+
+
+FUNCTIONS = """\
+def top(a, b=1, *args, c, d=2, **kw):
+    def inner(x, /, y=lambda z: z):
+        class Local:
+            def method(self): pass
+    return inner
+
+class Shape:
+    def __init__(self, sides):
+        self.sides = sides
+
+    @property
+    def area(self): ...
+
+    @area.setter
+    def area(self, value): ...
+
+    @staticmethod
+    def unit(): ...
+
+    @classmethod
+    @functools.cache
+    def square(cls, side=1): ...
+
+    @register("shape")
+    async def draw(self, canvas): ...
+
+    def nested(this):
+        def helper(self): pass
+
+key = lambda item: item[0]
+"""
+
+
+def test_scan_functions():
+    functions = scan_functions(FUNCTIONS)
+    assert [(f["qualname"], f["enclosing"], f["lineno"]) for f in functions] == [
+        ("top", None, 1),
+        ("top.<locals>.inner", "top", 2),
+        ("top.<locals>.inner.<locals>.Local.method", "top.<locals>.inner.<locals>.Local", 4),
+        ("Shape.__init__", "Shape", 8),
+        ("Shape.area", "Shape", 12),
+        ("Shape.area", "Shape", 15),
+        ("Shape.unit", "Shape", 18),
+        ("Shape.square", "Shape", 22),
+        ("Shape.draw", "Shape", 25),
+        ("Shape.nested", "Shape", 27),
+        ("Shape.nested.<locals>.helper", "Shape.nested", 28),
+    ]
+    assert functions[0] == {
+        "name": "top",
+        "qualname": "top",
+        "enclosing": None,
+        "lineno": 1,
+        "parameters": ["a", "b", "args", "c", "d", "kw"],
+        "defaults": ["b", "d"],
+        "is_method": False,
+        "decorators": [],
+        "is_async": False,
+    }
+    assert (functions[1]["parameters"], functions[1]["defaults"]) == (["x", "y"], ["y"])
+    methods = [f["name"] for f in functions if f["is_method"]]
+    assert methods == ["method", "__init__", "area", "area", "square", "draw"]
+    assert [f["decorators"] for f in functions[4:9]] == [
+        ["property"],
+        ["area.setter"],
+        ["staticmethod"],
+        ["classmethod", "functools.cache"],
+        ["register"],
+    ]
+    assert [f["name"] for f in functions if f["is_async"]] == ["draw"]
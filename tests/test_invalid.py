@@ -1,3 +1,4 @@
+import ast
 import itertools
 
 import pytest
@@ -38,6 +39,65 @@ def test_syntax_error_del_inp(python_parse_str, exp):
         python_parse_str(f"del {exp}", mode="exec")
 
 
+@pytest.mark.parametrize(
+    "source", ["del (a, b)", "del a, b", "del (a), (b)", "del [a, (b, c)]", "del ()", "del []", "() = [] = x"]
+)
+def test_del_targets(check_ast_attributes, source):
+    # a parenthesized list is a single Tuple target, like a bracketed one is a List
+    check_ast_attributes(source)
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "del *a",
+        "del a, *b",
+        "del (a, *b)",
+        "del [*a]",
+        "del a + b",
+        "del a < b < c",
+        "del f(x)",
+        "del (a, f())",
+        "del (yield)",
+    ],
+)
+def test_del_errors(source):
+    from peg_parser import parse
+
+    with pytest.raises(SyntaxError) as expected:
+        compile(source, "<unknown>", "exec")
+    with pytest.raises(SyntaxError) as error:
+        parse(source)
+    assert (error.value.msg, error.value.offset, error.value.end_offset) == (
+        expected.value.msg,
+        expected.value.offset,
+        expected.value.end_offset,
+    )
+
+
+@pytest.mark.parametrize(
+    "source", ['f"x" r"y"', '"a"f"b"', 'u"a" "b" f"{c}"', 'b"a"rb"b"', 'rb"a" Br"b"', 'f"{a}" "b" f"{c}"']
+)
+def test_string_concatenation(source):
+    from peg_parser import parse
+
+    assert ast.dump(parse(source)) == ast.dump(ast.parse(source))
+
+
+@pytest.mark.parametrize(
+    "source", ['f"x" b"y"', 'b"x" "y"', 'b"x" f"{y}"', 'b"a" rb"b" "c" + 1', 'x = (\n  "a"\n  b"c"\n)']
+)
+def test_string_concatenation_errors(source):
+    from peg_parser import parse
+
+    with pytest.raises(SyntaxError) as expected:
+        compile(source, "<unknown>", "exec")
+    with pytest.raises(SyntaxError) as error:
+        parse(source)
+    assert error.value.msg == expected.value.msg == "cannot mix bytes and nonbytes literals"
+    assert (error.value.lineno, error.value.offset) == (expected.value.lineno, expected.value.offset)
+
+
 def test_syntax_error_assign_literal(python_parse_str):
     with pytest.raises(SyntaxError):
         python_parse_str("7 = x", mode="exec")
@@ -172,5 +232,5 @@ def test_syntax_error_lambda_nondefault_follows_default(python_parse_str):
     ),
 )
 def test_syntax_error_literal_concat_different(first_prefix, second_prefix, python_parse_str):
-    with pytest.raises((SyntaxError, TypeError)):
+    with pytest.raises(SyntaxError, match="cannot mix bytes and nonbytes literals"):
         python_parse_str(f"{first_prefix}'hello' {second_prefix}'world'", mode="exec")
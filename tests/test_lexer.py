@@ -1,12 +1,28 @@
 """Tests the xonsh lexer."""
 
 import difflib
+import errno
+import io
+import itertools
+import sys
+import tokenize
 from collections.abc import Sequence
+from pathlib import Path
 
 import pytest
 
 from peg_parser.tokenize import Token as t  # noqa: N813
-from peg_parser.tokenize import TokenInfo
+from peg_parser.tokenize import (
+    EXACT_TOKEN_TYPES,
+    OPS,
+    SourceReadError,
+    TokenInfo,
+    Trivia,
+    dump_tokens,
+    generate_tokens,
+    main,
+    source_end,
+)
 
 
 def ensure_tuple(seq) -> str:
@@ -44,6 +60,20 @@ def check_tokens(inp: str, *exp):
     return assert_tokens_equal(exp, obs)
 
 
+def cpython_conformance(inp: str) -> list[str]:
+    """Mismatches between our token stream and CPython's tokenize (WS tokens are xonsh specific)"""
+    exp = [
+        repr((tokenize.tok_name[tok.type], tok.string, tok.start, tok.end))
+        for tok in tokenize.generate_tokens(io.StringIO(inp).readline)
+    ]
+    obs = [
+        repr((tok.type.name, tok.string, tok.start, tok.end))
+        for tok in generate_tokens(inp)
+        if tok.type != t.WS
+    ]
+    return [line for line in difflib.unified_diff(exp, obs, "cpython", "obtained", lineterm="")]
+
+
 def check_tokens_subproc(inp, exp, stop=-1):
     obs = lex_input(f"$[{inp}]")[1:stop]
     return assert_tokens_equal(exp, obs)
@@ -471,3 +501,675 @@ non-important content
         ("FSTRING_MIDDLE", "\nnon-important content\n", 11),
         (t.FSTRING_END, "'''", 0),
     )
+
+
+@pytest.mark.parametrize(
+    "inp, exp",
+    [
+        (
+            'f"{$(ls -l)} x"',
+            [
+                ("FSTRING_START", 'f"', 0),
+                ("OP", "{", 2),
+                ("OP", "$(", 3),
+                ("NAME", "ls", 5),
+                ("OP", "-", 8),
+                ("NAME", "l", 9),
+                ("OP", ")", 10),
+                ("OP", "}", 11),
+                ("FSTRING_MIDDLE", " x", 12),
+                ("FSTRING_END", '"', 14),
+            ],
+        ),
+        (
+            'f"{![ls]!r:>8}"',
+            [
+                ("FSTRING_START", 'f"', 0),
+                ("OP", "{", 2),
+                ("OP", "![", 3),
+                ("NAME", "ls", 5),
+                ("OP", "]", 7),
+                ("OP", "!", 8),
+                ("NAME", "r", 9),
+                ("OP", ":", 10),
+                ("FSTRING_MIDDLE", ">8", 11),
+                ("OP", "}", 13),
+                ("FSTRING_END", '"', 14),
+            ],
+        ),
+        (
+            'f"{a[b[0]]:{w}} {c}"',
+            [
+                ("FSTRING_START", 'f"', 0),
+                ("OP", "{", 2),
+                ("NAME", "a", 3),
+                ("OP", "[", 4),
+                ("NAME", "b", 5),
+                ("OP", "[", 6),
+                ("NUMBER", "0", 7),
+                ("OP", "]", 8),
+                ("OP", "]", 9),
+                ("OP", ":", 10),
+                ("OP", "{", 11),
+                ("NAME", "w", 12),
+                ("OP", "}", 13),
+                ("FSTRING_MIDDLE", "", 14),
+                ("OP", "}", 14),
+                ("FSTRING_MIDDLE", " ", 15),
+                ("OP", "{", 16),
+                ("NAME", "c", 17),
+                ("OP", "}", 18),
+                ("FSTRING_END", '"', 19),
+            ],
+        ),
+        (
+            "f\"{ {'k': $(ls)}['k']:>{w}x}\"",
+            [
+                ("FSTRING_START", 'f"', 0),
+                ("OP", "{", 2),
+                ("OP", "{", 4),
+                ("STRING", "'k'", 5),
+                ("OP", ":", 8),
+                ("OP", "$(", 10),
+                ("NAME", "ls", 12),
+                ("OP", ")", 14),
+                ("OP", "}", 15),
+                ("OP", "[", 16),
+                ("STRING", "'k'", 17),
+                ("OP", "]", 20),
+                ("OP", ":", 21),
+                ("FSTRING_MIDDLE", ">", 22),
+                ("OP", "{", 23),
+                ("NAME", "w", 24),
+                ("OP", "}", 25),
+                ("FSTRING_MIDDLE", "x", 26),
+                ("OP", "}", 27),
+                ("FSTRING_END", '"', 28),
+            ],
+        ),
+        (
+            'f"{a:{b:{$(c)}}}"',
+            [
+                ("FSTRING_START", 'f"', 0),
+                ("OP", "{", 2),
+                ("NAME", "a", 3),
+                ("OP", ":", 4),
+                ("OP", "{", 5),
+                ("NAME", "b", 6),
+                ("OP", ":", 7),
+                ("OP", "{", 8),
+                ("OP", "$(", 9),
+                ("NAME", "c", 11),
+                ("OP", ")", 12),
+                ("OP", "}", 13),
+                ("FSTRING_MIDDLE", "", 14),
+                ("OP", "}", 14),
+                ("FSTRING_MIDDLE", "", 15),
+                ("OP", "}", 15),
+                ("FSTRING_END", '"', 16),
+            ],
+        ),
+    ],
+)
+def test_fstring_replacement_field_depth(inp, exp):
+    assert check_tokens(inp, *exp)
+
+
+requires_c_tokenize = pytest.mark.skipif(
+    sys.version_info < (3, 12), reason="tokenize module uses the C tokenizer only from Python 3.12"
+)
+
+
+@requires_c_tokenize
+@pytest.mark.parametrize(
+    "inp",
+    [
+        "x = 1  # comment\n",
+        "x = 1  # comment",
+        "x = (1,  # comment\n     2)\n",
+        "x = [\n    # comment\n    1,\n]\n",
+        "if x:\n    y = 1\n    # same level\n  # shallower\n# column zero\n    z = 2\n",
+        "if x:\n    y = 1\n    # last line",
+        "x = 1\n# last line",
+        "x = 1\n    # indented last line",
+        "x\r\n# crlf\r\n",
+        "x = 1\t# after tab\n",
+    ],
+)
+def test_comment_tokens_match_cpython(inp):
+    assert not cpython_conformance(inp)
+
+
+@requires_c_tokenize
+def test_comment_fixture_matches_cpython():
+    source = (Path(__file__).parent / "data" / "comments.py").read_text()
+    assert not cpython_conformance(source)
+
+
+@requires_c_tokenize
+@pytest.mark.parametrize(
+    "inp",
+    [
+        "if a:\n    if b:\n        if c:\n            x\ny\n",
+        "if a:\n    if b:\n        x\n    y\nz\n",
+        "if a:\n\tx\n\tif b:\n\t\ty\n",
+        "if a:\n    x\n\n   \n    y\n",
+        "if a:\n    x\n        \n",
+        "if a:\n    x = (1,\n  2)\n    y\n",
+        "if a:\n    x = 1 + \\\n  2\n    y\n",
+        "if a:\n  \f  x\n",
+        "if a:\n    x\n    # comment\n\n",
+        "def f():\n    '''doc\n  string'''\n    return 1\n",
+    ],
+)
+def test_indentation_matches_cpython(inp):
+    assert not cpython_conformance(inp)
+
+
+@pytest.mark.parametrize(
+    "inp, exp",
+    [
+        (
+            '"a"f"b"',
+            [("STRING", '"a"'), ("FSTRING_START", 'f"'), ("FSTRING_MIDDLE", "b"), ("FSTRING_END", '"')],
+        ),
+        ('"a"b"c"', [("STRING", '"a"'), ("STRING", 'b"c"')]),
+        ('b"a"rb"c"', [("STRING", 'b"a"'), ("STRING", 'rb"c"')]),
+        ('"a"p"b"', [("STRING", '"a"'), ("STRING", 'p"b"')]),
+        ('"a"ur"b"', [("STRING", '"a"'), ("NAME", "ur"), ("STRING", '"b"')]),
+    ],
+)
+def test_adjacent_string_prefixes(inp, exp):
+    # the letters right after a string start the prefix of the next one, if they are a valid prefix
+    tokens = [(tok.type.name, tok.string) for tok in generate_tokens(inp) if tok.string.strip()]
+    assert tokens == exp
+
+
+def span_mismatches(source: str) -> list[TokenInfo]:
+    """Tokens whose (start, end) span does not cover exactly their text within `line`"""
+    mismatches = []
+    for tok in generate_tokens(source):
+        if not tok.string:  # DEDENT, ENDMARKER and the implicit NEWLINE
+            continue
+        (srow, scol), (erow, ecol) = tok.start, tok.end
+        lines = tok.line.splitlines(keepends=True)
+        if len(lines) != erow - srow + 1:
+            mismatches.append(tok)
+        elif "".join(lines)[scol : len("".join(lines)) - len(lines[-1]) + ecol] != tok.string:
+            mismatches.append(tok)
+    return mismatches
+
+
+@pytest.mark.parametrize("newline", ["\n", "\r\n"])
+@pytest.mark.parametrize("path", sorted((Path(__file__).parent / "data").glob("*.py")), ids=lambda p: p.name)
+def test_token_spans_match_lines(path, newline):
+    source = path.read_text().replace("\n", newline)
+    assert span_mismatches(source) == []
+
+
+@pytest.mark.parametrize(
+    "inp, exp",
+    [
+        ("x **=\r\n", [("OP", "**=", (1, 2), (1, 5)), ("NEWLINE", "\r\n", (1, 5), (1, 7))]),
+        ("f(x)\r\n", [("OP", ")", (1, 3), (1, 4)), ("NEWLINE", "\r\n", (1, 4), (1, 6))]),
+        ("x = y ->", [("OP", "->", (1, 6), (1, 8)), ("NEWLINE", "", (1, 8), (1, 9))]),
+        ('s = "abc"\r\n', [("STRING", '"abc"', (1, 4), (1, 9)), ("NEWLINE", "\r\n", (1, 9), (1, 11))]),
+        ("x = (1,\r\n 2)\r\n", [("OP", ")", (2, 2), (2, 3)), ("NEWLINE", "\r\n", (2, 3), (2, 5))]),
+        ("x = 1 \x01\n", [("ERRORTOKEN", "\x01", (1, 6), (1, 7)), ("NEWLINE", "\n", (1, 7), (1, 8))]),
+        ("x = 1\r", [("NUMBER", "1", (1, 4), (1, 5)), ("ERRORTOKEN", "\r", (1, 5), (1, 6))]),
+    ],
+)
+def test_line_end_columns(inp, exp):
+    tokens = [(tok.type.name, tok.string, tok.start, tok.end) for tok in generate_tokens(inp)]
+    assert tokens[-len(exp) - 1 : -1] == exp
+
+
+@pytest.mark.parametrize(
+    "inp, line",
+    [
+        ('x = """a\nb"""\n', 'x = """a\nb"""\n'),
+        ('x = "a\\\nb"\n', 'x = "a\\\nb"\n'),
+        ('f"""{a} b\nc"""\n', 'f"""{a} b\nc"""\n'),
+    ],
+)
+def test_multi_line_token_line(inp, line):
+    tok = next(tok for tok in generate_tokens(inp) if tok.start[0] != tok.end[0])
+    assert tok.line == line
+
+
+@pytest.mark.parametrize(
+    "inp, exp",
+    [
+        (
+            '"""a\nb\nc\nd\ne"""\n',
+            [("STRING", '"""a\nb\nc\nd\ne"""', (1, 0), (5, 4)), ("NEWLINE", "\n", (5, 4), (5, 5))],
+        ),
+        ("'''a\nb'''\n", [("STRING", "'''a\nb'''", (1, 0), (2, 4)), ("NEWLINE", "\n", (2, 4), (2, 5))]),
+        (
+            "x = '''it's \"q\"\n'' '''\n",
+            [("STRING", "'''it's \"q\"\n'' '''", (1, 4), (2, 6)), ("NEWLINE", "\n", (2, 6), (2, 7))],
+        ),
+        (
+            '"""a ""\nb \\"""\n"""\n',
+            [("STRING", '"""a ""\nb \\"""\n"""', (1, 0), (3, 3)), ("NEWLINE", "\n", (3, 3), (3, 4))],
+        ),
+        (
+            "s = '''a\\\nb\\\n'''\n",
+            [("STRING", "'''a\\\nb\\\n'''", (1, 4), (3, 3)), ("NEWLINE", "\n", (3, 3), (3, 4))],
+        ),
+    ],
+)
+def test_multi_line_string_spans(inp, exp):
+    tokens = [(tok.type.name, tok.string, tok.start, tok.end) for tok in generate_tokens(inp)]
+    assert tokens[-len(exp) - 1 : -1] == exp
+
+
+@pytest.mark.parametrize(
+    "inp, exp",
+    [
+        (
+            "x = 'abc\ny = 2'\n",
+            [
+                ("NAME", "x", (1, 0), (1, 1)),
+                ("OP", "=", (1, 2), (1, 3)),
+                ("ERRORTOKEN", "'abc", (1, 4), (1, 8)),
+                ("NEWLINE", "\n", (1, 8), (1, 9)),
+                ("NAME", "y", (2, 0), (2, 1)),
+                ("OP", "=", (2, 2), (2, 3)),
+                ("NUMBER", "2", (2, 4), (2, 5)),
+                ("ERRORTOKEN", "'", (2, 5), (2, 6)),
+                ("NEWLINE", "\n", (2, 6), (2, 7)),
+            ],
+        ),
+        (
+            'f"a{b}c\n',
+            [
+                ("FSTRING_START", 'f"', (1, 0), (1, 2)),
+                ("FSTRING_MIDDLE", "a", (1, 2), (1, 3)),
+                ("OP", "{", (1, 3), (1, 4)),
+                ("NAME", "b", (1, 4), (1, 5)),
+                ("OP", "}", (1, 5), (1, 6)),
+                ("ERRORTOKEN", "c", (1, 6), (1, 7)),
+                ("NEWLINE", "\n", (1, 7), (1, 8)),
+            ],
+        ),
+        ('"', [("ERRORTOKEN", '"', (1, 0), (1, 1)), ("NEWLINE", "", (1, 1), (1, 2))]),
+    ],
+)
+def test_unterminated_string_tokens(inp, exp):
+    tokens = [(tok.type.name, tok.string, tok.start, tok.end) for tok in generate_tokens(inp)]
+    tokens = [tok for tok in tokens if tok[0] != "WS"]
+    assert tokens[: len(exp)] == exp
+
+
+@pytest.mark.parametrize(
+    "inp, string",
+    [
+        ("x = 'a\\\nb'\n", "'a\\\nb'"),
+        ("x = 'a\\\r\nb'\r\n", "'a\\\r\nb'"),
+        ("x = '''a\nb'''\n", "'''a\nb'''"),
+    ],
+)
+def test_line_ending_inside_string(inp, string):
+    assert [tok.string for tok in generate_tokens(inp) if tok.type == t.STRING] == [string]
+
+
+@pytest.mark.parametrize("inp", ['f"a\\\nb{x}"\n', "f'''a\n{x}\nb'''\n"])
+def test_line_ending_inside_fstring(inp):
+    assert t.ERRORTOKEN not in [tok.type for tok in generate_tokens(inp)]
+
+
+EOF_SOURCES = [
+    "x = 1\n",
+    "x = 1",
+    "x = 1\n\n\n",
+    "x = 1\n   ",
+    "x = 1\n# last line",
+    "if x:\n    y = 1\n",
+    "if x:\n    y = 1",
+    "if x:\n    y = 1\n\n    \n",
+    "if x:\n    y = 1\n    ",
+    "x = 1\\\n  ",
+    "",
+    "\n",
+]
+
+
+@requires_c_tokenize
+@pytest.mark.parametrize("inp", EOF_SOURCES)
+def test_eof_tokens_match_cpython(inp):
+    assert not cpython_conformance(inp)
+
+
+@pytest.mark.parametrize("inp", [src for src in EOF_SOURCES if src.endswith("\n") or not src])
+def test_source_end_is_endmarker_start(inp):
+    *_, endmarker = generate_tokens(inp)
+    assert endmarker.type == t.ENDMARKER
+    assert source_end(inp) == endmarker.start
+
+
+@pytest.mark.parametrize(
+    "inp, end",
+    [("x = 1", (1, 5)), ("x = 1\n   ", (2, 3)), ("x\r\ny", (2, 1)), ("x\r\n", (2, 0))],
+)
+def test_source_end(inp, end):
+    assert source_end(inp) == end
+
+
+TRIVIA = (t.WS, t.COMMENT, t.NL)
+
+
+@pytest.mark.parametrize("path", sorted((Path(__file__).parent / "data").glob("*.*")), ids=lambda p: p.name)
+def test_skip_trivia(path):
+    source = path.read_text()
+    expected = [tok for tok in generate_tokens(source) if tok.type not in TRIVIA]
+    assert list(generate_tokens(source, skip_trivia=True)) == expected
+
+
+def test_skip_trivia_callable():
+    # trivia kept while the callable says so, as the parser does in macro arguments
+    keep = [False]
+    tokens = generate_tokens("a  # note\n\nb c\n", skip_trivia=lambda: not keep[0], trivia=Trivia.COALESCED)
+    assert [tok.string for tok in itertools.islice(tokens, 2)] == ["a", "\n"]
+    keep[0] = True
+    assert [tok.type for tok in itertools.islice(tokens, 3)] == [t.NL, t.NAME, t.WS]
+
+
+def test_initial_position():
+    tokens = list(generate_tokens("x = '''a\nb'''\ny\n", initial_lineno=5, initial_col_offset=2))
+    assert [(tok.string, tok.start, tok.end) for tok in tokens if tok.string.strip()] == [
+        ("x", (5, 2), (5, 3)),
+        ("=", (5, 4), (5, 5)),
+        ("'''a\nb'''", (5, 6), (6, 4)),
+        ("y", (7, 0), (7, 1)),
+    ]
+
+
+@pytest.mark.parametrize("op", sorted(OPS - set("()[]{}")))
+def test_operators_longest_match(op):
+    # an operator is a single token even where a shorter one is its prefix, like ":=" and ":"
+    closer = {"(": ")", "[": "]", "{": "}"}.get(op[-1], "")
+    tokens = [tok.string for tok in generate_tokens(f"a {op} b {closer}") if tok.type == t.OP]
+    assert tokens == [op, closer] if closer else [op]
+
+
+TOKEN_DUMPS = sorted((Path(__file__).parent / "data" / "tokens").glob("*.txt"))
+
+
+@pytest.mark.parametrize("dump", TOKEN_DUMPS, ids=lambda p: p.stem)
+def test_dump_tokens_matches_cpython(dump):
+    # the dumps are the output of ``python -m tokenize`` for the files of the same name
+    source = (dump.parent.parent / f"{dump.stem}.py").read_text()
+    assert dump_tokens(source) == dump.read_text()
+
+
+def test_dump_xonsh_tokens():
+    assert dump_tokens("x = `.*`\n").splitlines()[3:5] == [
+        "1,4-1,8:            SEARCH_PATH    '`.*`'         ",
+        "1,8-1,9:            NEWLINE        '\\n'           ",
+    ]
+
+
+def test_tokenize_cli(capsys):
+    main([str(TOKEN_DUMPS[0].parent.parent / f"{TOKEN_DUMPS[0].stem}.py")])
+    assert capsys.readouterr().out == TOKEN_DUMPS[0].read_text()
+
+
+
+@pytest.mark.parametrize(
+    "source, first",
+    [
+        ("x = (a +\n     b)\n", ("NAME", "b", 5)),
+        ("x = (a\n     + b)\n", ("OP", "+", 5)),
+        ("x = (a\n+ b)\n", ("OP", "+", 0)),
+        ("x = [a **\n\t  b]\n", ("NAME", "b", 3)),
+        ("f(a,\n  \f  b)\n", ("NAME", "b", 5)),
+        ("x = {a:\n    //b}\n", ("OP", "//", 4)),
+        ("x = $(ls\n   -l)\n", ("OP", "-", 3)),
+    ],
+)
+def test_continuation_line_columns(source, first):
+    """The first token of a line within brackets starts after the whitespace before it."""
+    line = [tok for tok in generate_tokens(source, trivia=Trivia.COALESCED) if tok.start[0] == 2]
+    tok = next(tok for tok in line if tok.type != t.WS)
+    assert (tok.type.name, tok.string, tok.start[1]) == first
+    assert tok.end == (2, first[2] + len(first[1]))
+    if first[2]:
+        assert (line[0].start, line[0].end) == ((2, 0), tok.start)
+
+
+FORMATTED = "def f(a,  b):\n\treturn {a: b}  \t # swap\n"
+
+
+@pytest.mark.parametrize(
+    "trivia, spaces",
+    [
+        (Trivia.NONE, []),
+        (Trivia.COALESCED, [(1, 3, 4), (1, 8, 10), (2, 7, 8), (2, 11, 12), (2, 14, 18)]),
+        (
+            Trivia.EXACT,
+            [(1, 3, 4), (1, 8, 10), (2, 7, 8), (2, 11, 12), (2, 14, 16), (2, 16, 17), (2, 17, 18)],
+        ),
+    ],
+)
+def test_trivia_modes(trivia, spaces):
+    tokens = list(generate_tokens(FORMATTED, trivia=trivia))
+    ws = [tok for tok in tokens if tok.type == t.WS]
+    assert [(tok.start[0], tok.start[1], tok.end[1]) for tok in ws] == spaces
+    assert all(tok.string == tok.line[tok.start[1] : tok.end[1]] for tok in ws)
+    others = [tok for tok in tokens if tok.type != t.WS]
+    assert others == [tok for tok in generate_tokens(FORMATTED) if tok.type != t.WS]
+    assert len(others) == 20
+
+
+def test_type_comment_tokens():
+    source = "def f(a,  # type: int\n      ):\n    #type:() -> None\n    x = 1  # type: ignore[x]\n"
+    tokens = list(generate_tokens(source, skip_trivia=True, type_comments=True))
+    found = [(tok.type, tok.string, tok.start, tok.end) for tok in tokens if tok.type.name.startswith("TYPE")]
+    assert found == [
+        (t.TYPE_COMMENT, "int", (1, 18), (1, 21)),
+        (t.TYPE_COMMENT, "() -> None", (3, 10), (3, 20)),
+        (t.TYPE_IGNORE, "[x]", (4, 25), (4, 28)),
+    ]
+    # a type comment alone on its line ends a statement
+    assert [tok.type for tok in tokens[8:11]] == [t.NEWLINE, t.TYPE_COMMENT, t.NEWLINE]
+    assert t.TYPE_COMMENT not in {tok.type for tok in generate_tokens(source)}
+
+
+@requires_c_tokenize
+def test_no_trivia_matches_cpython():
+    source = (Path(__file__).parent / "data" / "expressions.py").read_text()
+    readline = io.StringIO(source).readline
+    expected = [(tokenize.tok_name[tok.type], *tok[1:4]) for tok in tokenize.generate_tokens(readline)]
+    # by default, as with Trivia.NONE
+    assert [(tok.type.name, *tok[1:4]) for tok in generate_tokens(source)] == expected
+
+
+def test_exact_token_types():
+    import token
+
+    assert set(EXACT_TOKEN_TYPES) == OPS
+    assert {op: EXACT_TOKEN_TYPES[op] for op in token.EXACT_TOKEN_TYPES} == {
+        op: token.tok_name[typ] for op, typ in token.EXACT_TOKEN_TYPES.items()
+    }
+    exact_types = [tok.exact_type for tok in generate_tokens("f($(ls))")]
+    assert exact_types[:4] == ["NAME", "LPAR", "DOLLAR_LPAR", "NAME"]
+
+
+@requires_c_tokenize
+def test_dump_exact_tokens_matches_cpython():
+    source = (Path(__file__).parent / "data" / "expressions.py").read_text()
+    expected = [
+        ("%d,%d-%d,%d:" % (tok.start + tok.end), tokenize.tok_name[tok.exact_type], tok.string)
+        for tok in tokenize.generate_tokens(io.StringIO(source).readline)
+    ]
+    lines = dump_tokens(source, exact=True).splitlines()[1:]
+    assert lines == [f"{pos:<20}{name:<15}{string!r:<15}" for pos, name, string in expected]
+
+
+def test_dump_tokens_api():
+    import peg_parser
+
+    assert peg_parser.dump_tokens("x\n") == dump_tokens("x\n")
+
+
+READLINE_SOURCE = "x = $(ls -l)\nif x:\n    y = 'é'\n"
+
+
+def lines_of(source: str):
+    yield from source.splitlines(keepends=True)
+
+
+@pytest.mark.parametrize(
+    "readline",
+    [
+        io.StringIO(READLINE_SOURCE).readline,
+        io.StringIO(READLINE_SOURCE),
+        lines_of(READLINE_SOURCE).__next__,
+        io.BytesIO(READLINE_SOURCE.encode()).readline,
+        io.BytesIO(b"\xef\xbb\xbf" + READLINE_SOURCE.encode()).readline,
+        io.BytesIO(("# -*- coding: latin-1 -*-\n" + READLINE_SOURCE).encode("latin-1")).readline,
+    ],
+)
+def test_readline_kinds(readline):
+    # without the line of the coding cookie
+    tokens = [tok.string for tok in generate_tokens(readline) if "coding" not in tok.line]
+    assert tokens == [tok.string for tok in generate_tokens(READLINE_SOURCE)]
+
+
+def test_readline_of_file(tmp_path):
+    path = tmp_path / "source.xsh"
+    path.write_text(READLINE_SOURCE)
+    with path.open() as file:
+        assert list(generate_tokens(file)) == list(generate_tokens(READLINE_SOURCE))
+
+
+def test_readline_error_propagates():
+    lines = iter(["x = 1\n", "y = 2\n"])
+
+    def readline():
+        line = next(lines, None)
+        if line is None:
+            raise OSError("connection lost")
+        return line
+
+    tokens = generate_tokens(readline)
+    assert [tok.string for tok in itertools.islice(tokens, 4)] == ["x", "=", "1", "\n"]
+    with pytest.raises(OSError, match="connection lost"):
+        list(tokens)
+
+
+class FlakyFile(io.StringIO):
+    """A file failing to be read after *size* characters, like one on a network drive."""
+
+    name = "flaky.xsh"
+
+    def __init__(self, source: str, size: int):
+        super().__init__(source)
+        self.size = size
+
+    def readline(self, limit: int = -1) -> str:
+        if self.tell() >= self.size:
+            raise OSError(errno.EIO, "Input/output error")
+        return super().readline(limit)
+
+
+def test_file_read_error():
+    tokens = []
+    with pytest.raises(SourceReadError) as e:
+        tokens.extend(generate_tokens(FlakyFile("x = 'é'\ny = 2\nz = 3\n", 14)))
+    assert isinstance(e.value, OSError)
+    assert (e.value.errno, e.value.filename) == (errno.EIO, "flaky.xsh")
+    assert (e.value.lineno, e.value.offset) == (3, 15)
+    assert "Input/output error" in str(e.value)
+    assert tokens[-1].type == t.NEWLINE
+    assert tokens[-1].start[0] == 2
+
+
+def test_parse_file_read_error(python_parse_file, tmp_path, monkeypatch):
+    from peg_parser import subheader
+
+    path = tmp_path / "flaky.xsh"
+    path.write_text("x = 1\ny = 2\n")
+    monkeypatch.setattr(subheader, "open", lambda _: FlakyFile(path.read_text(), 6), raising=False)
+    with pytest.raises(SourceReadError, match=r"reading line 2, after 6 bytes"):
+        python_parse_file(path)
+
+
+def skip_from_first_opener(source):
+    from peg_parser.tokenizer import skip_balanced
+
+    tokens = generate_tokens(source, skip_trivia=True)
+    opener = next(tok for tok in tokens if tok.type == t.OP and tok.string[-1] in "([{")
+    closer, skipped = skip_balanced(tokens, opener)
+    return opener.string, "".join(tok.string for tok in skipped), closer.string, closer.start
+
+
+@pytest.mark.parametrize(
+    "source, expected",
+    [
+        ("f(a)", ("(", "a", ")", (1, 3))),
+        ("x = [a, (b, {c: [d]})] + e", ("[", "a,(b,{c:[d]})", "]", (1, 21))),
+        ("{a: (1,\n  2)}", ("{", "a:(1,2)", "}", (2, 4))),
+        ("$(ls @(x) ![echo] $[a] ${b} !(c)) + 1", ("$(", "ls@(x)![echo]$[a]${b}!(c)", ")", (1, 32))),
+        ("@$(which ls)", ("@$(", "whichls", ")", (1, 11))),
+        ("f(')', \"]\", '''(\n''')", ("(", "')',\"]\",'''(\n'''", ")", (2, 3))),
+        ("f(f'{x:{w}}' f'{(y)}}}(' )", ("(", "f'{x:{w}}'f'{(y)}}}('", ")", (1, 25))),
+        ('f(f"{f"{a[0]}"}")', ("(", 'f"{f"{a[0]}"}"', ")", (1, 16))),
+    ],
+)
+def test_skip_balanced(source, expected):
+    if 'f"{f"' in source and sys.version_info < (3, 12):
+        pytest.skip("nested f-strings reusing quotes need python3.12")
+    assert skip_from_first_opener(source) == expected
+
+
+@pytest.mark.parametrize(
+    "source, message, start, end",
+    [
+        ("f(a, [b)", "closing parenthesis ')' does not match opening parenthesis '['", (1, 8), (1, 8)),
+        (
+            "f(a, [b,\n )",
+            "closing parenthesis ')' does not match opening parenthesis '[' on line 1",
+            (2, 2),
+            (2, 2),
+        ),
+        ("$(ls }", "closing parenthesis '}' does not match opening parenthesis '('", (1, 6), (1, 6)),
+        ("f(a, (b", "'(' was never closed", (1, 6), (1, 8)),
+        ("f(a, ![ls\n", "'![' was never closed", (1, 6), (1, 10)),
+        ("f(a", "'(' was never closed", (1, 2), (1, 4)),
+    ],
+)
+def test_skip_balanced_errors(source, message, start, end):
+    with pytest.raises(SyntaxError) as e:
+        skip_from_first_opener(source)
+    assert e.value.msg == message
+    assert ((e.value.lineno, e.value.offset), (e.value.end_lineno, e.value.end_offset)) == (start, end)
+
+
+def test_token_repr_and_equality():
+    source = "x = '" + "a" * 100 + "' + y\n"
+    tokens = list(generate_tokens(source, skip_trivia=True))
+    assert repr(tokens[0]) == "<NAME>('x') at 1:0-1:1"
+    assert repr(tokens[2]) == "<STRING>(\"'" + "a" * 36 + "...\") at 1:4-1:106"
+    assert repr(tokens[-1]) == "<ENDMARKER>('') at 2:0-2:0"
+
+    again = list(generate_tokens(source, skip_trivia=True))
+    assert tokens == again
+    assert len({*tokens, *again}) == len(tokens)
+    assert tokens[0].line is tokens[-2].line
+    other = list(generate_tokens("x = '" + "b" * 100 + "' + y\n", skip_trivia=True))
+    assert tokens[0] != other[0]
+    assert tokens[0][:4] == other[0][:4]
+
+
+def test_bracket_depth():
+    from peg_parser.tokenizer import Tokenizer
+
+    tokenizer = Tokenizer(generate_tokens("f(a[1], {b: (c)})\n", skip_trivia=True))
+    while tokenizer.getnext().type != t.ENDMARKER:
+        pass
+    depths = [tokenizer.bracket_depth(index) for index in range(len(tokenizer._tokens) + 1)]
+    assert depths == [0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 3, 3, 2, 1, 0, 0, 0]
+    assert tokenizer.bracket_depth(-3) == 1  # before the last closing parenthesis
+    assert tokenizer.bracket_depth(-100) == tokenizer.bracket_depth(100) == 0
@@ -0,0 +1,141 @@
+"""Tests the opt-in limits for untrusted input."""
+
+import ast
+import io
+import itertools
+
+import pytest
+
+from peg_parser import Parser, cache_clear, cache_info, parse, parse_cached, parse_events
+from peg_parser.tokenize import LimitExceeded, generate_tokens
+from peg_parser.tokenizer import Tokenizer
+
+
+def test_source_bytes_limit_on_single_long_line():
+    source = "x = '" + "a" * 1_000_000 + "'\n"
+    with pytest.raises(LimitExceeded) as e:
+        list(generate_tokens(source, max_source_bytes=1024))
+    assert e.value.limit == "max_source_bytes"
+    assert e.value.value == 1024
+    assert e.value.position == (1, 1024)
+
+
+def test_source_bytes_limit_cuts_long_line_reads():
+    file = io.StringIO("x = '" + "é" * 1_000_000 + "'\n")
+    reads = []
+    readline = file.readline
+    file.readline = lambda *args: reads.append(readline(*args)) or reads[-1]
+    with pytest.raises(LimitExceeded) as e:
+        list(generate_tokens(file, max_source_bytes=1024))
+    assert e.value.position == (1, 514)
+    # the line is not read in full before the limit trips
+    assert sum(map(len, reads)) == 1025
+
+
+def test_source_bytes_limit_reached_on_later_line():
+    source = "x = 1\n" * 100
+    tokens = generate_tokens(source, max_source_bytes=64)
+    with pytest.raises(LimitExceeded) as e:
+        list(tokens)
+    assert e.value.position == (11, 4)
+
+
+def test_source_bytes_limit_counts_utf8_bytes():
+    source = "s = 'ééééé'\n"
+    assert len(source) < 16 < len(source.encode())
+    with pytest.raises(LimitExceeded):
+        list(generate_tokens(source, max_source_bytes=16))
+
+
+def test_tokens_limit_fails_while_streaming():
+    source = "x = 1\n" * 1_000_000
+    tokens = generate_tokens(source, max_tokens=100)
+    consumed = []
+    with pytest.raises(LimitExceeded) as e:
+        for tok in tokens:
+            consumed.append(tok)
+    assert e.value.limit == "max_tokens"
+    assert len(consumed) == 100
+    next_token = next(itertools.islice(generate_tokens(source), 100, None))
+    assert e.value.position == next_token.start
+
+
+def test_tokenizer_buffer_stays_under_the_limit():
+    source = "a + " * 100_000 + "a\n"
+    tokenizer = Tokenizer(generate_tokens(source, max_tokens=500))
+    with pytest.raises(LimitExceeded):
+        while True:
+            tokenizer.getnext()
+    assert len(tokenizer._tokens) <= 500
+
+
+def test_limits_within_bounds_are_transparent():
+    source = "x = 1\ny = 2\n"
+    assert list(generate_tokens(source, max_source_bytes=len(source), max_tokens=100)) == list(
+        generate_tokens(source)
+    )
+
+
+@pytest.mark.parametrize(
+    "kwargs",
+    [
+        {"max_source_bytes": 100},
+        {"max_tokens": 100},
+    ],
+)
+def test_parse_string_limits(python_parse_str, kwargs):
+    with pytest.raises(LimitExceeded):
+        python_parse_str("x = [" + "1, " * 1000 + "]", mode="exec", **kwargs)
+    assert python_parse_str("x = 1", mode="exec", **kwargs)
+
+
+@pytest.mark.parametrize(
+    "kwargs",
+    [
+        {"max_source_bytes": 100},
+        {"max_tokens": 100},
+    ],
+)
+def test_public_parse_limits(kwargs):
+    source = "x = [" + "1, " * 1000 + "]\n"
+    with pytest.raises(LimitExceeded):
+        parse(source, **kwargs)
+    with pytest.raises(LimitExceeded):
+        Parser(**kwargs).parse(source)
+    with pytest.raises(LimitExceeded):
+        parse_events(source, lambda *args: None, **kwargs)
+    assert ast.dump(parse("x = 1\n", **kwargs)) == ast.dump(ast.parse("x = 1\n"))
+
+
+def test_parse_cached_limits():
+    source = "x = [" + "1, " * 1000 + "]\n"
+    cache_clear()
+    assert parse_cached(source)
+    # the tree cached without limits is not returned for a call with them
+    with pytest.raises(LimitExceeded):
+        parse_cached(source, max_tokens=100)
+    assert parse_cached(source, max_tokens=10_000)
+    assert cache_info().currsize == 2
+    cache_clear()
+
+
+def test_parse_file_limits(python_parse_file, tmp_path):
+    path = tmp_path / "big.py"
+    path.write_text("x = 1\n" * 1000)
+    with pytest.raises(LimitExceeded):
+        python_parse_file(path, max_source_bytes=100)
+    with pytest.raises(LimitExceeded):
+        python_parse_file(path, max_tokens=100)
+    assert python_parse_file(path, max_source_bytes=6000, max_tokens=10_000)
+
+
+def test_source_bytes_limit_same_for_ascii_and_non_ascii_lines():
+    source = "x = 1\nif x:\n    y = 'a' + f'{x}'\n"
+    mixed = source + "# é\n"
+    size = len(mixed.encode())
+    tokens = list(generate_tokens(mixed, max_source_bytes=size))
+    # everything up to the DEDENT and ENDMARKER before the added comment
+    assert tokens[:-4] == list(generate_tokens(source, max_source_bytes=len(source)))[:-2]
+    with pytest.raises(LimitExceeded) as e:
+        list(generate_tokens(mixed, max_source_bytes=size - 1))
+    assert e.value.position == (4, 3)
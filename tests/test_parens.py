@@ -0,0 +1,74 @@
+"""Tests the spans of the parentheses only grouping expressions, targets and patterns."""
+
+import pytest
+
+from peg_parser import paren_map
+
+SOURCE = """\
+@(decorator)
+def f(x):
+    with (cm):
+        pass
+    with (cm) as c, (other):
+        pass
+    with (a as b):
+        pass
+    with (cm,):
+        pass
+    (y) = ((x))
+    del (y)
+    (z): int = 1
+    print((i for i in x), (1, 2), f())
+    match x:
+        case (1 | 2):
+            pass
+    return [i for i in x if (n := i)]
+"""
+
+
+def grouped(source: str) -> list[str]:
+    lines = source.splitlines()
+    spans = []
+    for span in paren_map(source):
+        assert span["lineno"] == span["end_lineno"]
+        spans.append(lines[span["lineno"] - 1][span["col_offset"] : span["end_col_offset"]])
+    return spans
+
+
+def test_paren_map():
+    assert grouped(SOURCE) == [
+        "(decorator)",
+        "(cm)",
+        "(cm)",
+        "(other)",
+        "(y)",
+        "((x))",
+        "(x)",
+        "(y)",
+        "(z)",
+        "(1 | 2)",
+        "(n := i)",
+    ]
+
+
+@pytest.mark.parametrize(
+    "source, expected",
+    [
+        ("return (x)", ["(x)"]),
+        ("return ( x )", ["( x )"]),
+        ("return (yield)", ["(yield)"]),
+        ("return (x),", ["(x)"]),
+        ("return (x,)", []),
+        ("x = ()", []),
+        ("f(a)(b)", []),
+        ("x = $(ls @(y)) + $[echo]", []),
+    ],
+)
+def test_grouping_parentheses(source, expected):
+    assert grouped(source) == expected
+
+
+def test_multiline_group():
+    assert paren_map("x = (\n    a\n    + b\n)\n") == [
+        {"lineno": 1, "col_offset": 4, "end_lineno": 4, "end_col_offset": 1}
+    ]
@@ -1,10 +1,20 @@
 """Tests the xonsh parser."""
 
+import ast
+import functools
+import gc
+import re
 import sys
+import warnings
+import weakref
 from pathlib import Path
 
 import pytest
 
+DATA = Path(__file__).parent / "data"
+requires_py311 = pytest.mark.skipif(sys.version_info < (3, 11), reason="requires python3.11")
+requires_py312 = pytest.mark.skipif(sys.version_info < (3, 12), reason="requires python3.12")
+
 
 @pytest.mark.parametrize(
     "inp",
@@ -13,6 +23,16 @@ import pytest
         'RB"hell\\o"',
         'Br"hell\\o"',
         'rB"hell\\o"',
+        'u"x"',
+        'U"x"',
+        'b"x"',
+        'u"a" "b"',
+        '"a" u"b"',
+        'u"a" U"b"',
+        'b"a" B"b"',
+        # Python 3.11 marks every piece of an f-string concatenated with a u-string
+        pytest.param('u"a" f"{x}b"', marks=requires_py312),
+        pytest.param('f"{x}" u"b"', marks=requires_py312),
         "+1",
         "-1",
         "~1",
@@ -99,6 +119,236 @@ def arr_container():
     return Arr()
 
 
+def test_unary_operators(eval_code, check_xonsh_ast):
+    assert eval_code("not x or y", x=1, y=2) == 2
+    assert eval_code("-x ** 2 + ~x", x=3) == -13
+    assert eval_code("not -x", x=0) is True
+    results = []
+    check_xonsh_ast(
+        "async def f():\n    import asyncio\n\n    return not await asyncio.sleep(0, 0)\n"
+        "import asyncio\nresults.append(asyncio.run(f()))",
+        mode="exec",
+        results=results,
+    )
+    assert results == [True]
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "def f(cb=lambda x: x + 1, n=2): pass",
+        "def f(a, /, cb=lambda x, y=1: x + y, *, n=lambda: 2, **k): pass",
+        "f(key=lambda v: v.x)",
+        "f(lambda x: x, key=lambda *a, **k: (a, k))",
+        "f(*args, key=lambda: 1, **kw)",
+        "d = {'a': lambda x: x, 'b': lambda: 1}",
+        "x[lambda a: a, 1:2]",
+        "lambda a=lambda b=1: b: a()",
+    ],
+)
+def test_lambda_as_value(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "x = (a +\n     b)\n",
+        "x = (a\n     + b)\n",
+        "x = [a **\n\t  b]\n",
+        "x = (a <<\n  \f  b\n  >> c)\n",
+        "f(a\n  // b,\n  c @\n  d)\n",
+    ],
+)
+def test_multi_line_binop_locations(check_ast_attributes, source):
+    tree = check_ast_attributes(source)
+    binop = next(node for node in ast.walk(tree) if isinstance(node, ast.BinOp))
+    assert binop.end_lineno > binop.lineno
+
+
+def test_lambda_as_value_runs(check_xonsh_ast):
+    results = []
+    check_xonsh_ast(
+        "def f(cb=lambda x: x + 1, n=2, *, key=lambda v: -v):\n"
+        "    return cb(n), key(n)\n"
+        "results.append(f())\n"
+        "results.append(f(lambda x: x * 10, 3))\n"
+        "results.append(sorted([3, 1, 2], key=lambda v: -v))\n"
+        "results.append({'a': lambda x: x + 1}['a'](1))\n"
+        "results.append(type('Getter', (), {'__getitem__': lambda self, key: key()})()[lambda: 2])\n"
+        "results.append((lambda a=lambda b=1: b: a())())",
+        mode="exec",
+        results=results,
+    )
+    assert results == [(3, -2), (30, -3), [3, 2, 1], 2, 2, 1]
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "def f():\n    return a if p else b, c",
+        "def f():\n    return a, b if p else c",
+        "def f():\n    return *a, b if p else c",
+        "def f():\n    return (a if p else b), *c",
+        "def f():\n    yield *a, *b",
+        "def f():\n    yield a if p else b, *c",
+        "def f():\n    x = yield a, b if p else c if q else d",
+        "x = *a, b if p else c, d",
+    ],
+)
+def test_conditional_and_starred_tuple_items(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "x = [" + ", ".join(f"[{i}]" for i in range(1000)) + "]\n",
+        "d = {" + ", ".join(f"'k{i}': {{'v': ({i},)}}" for i in range(1000)) + "}\n",
+    ],
+    ids=["list", "dict"],
+)
+def test_wide_literals(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "if x: a = 1; b = 2\nelse: c = 3",
+        "if x: a = 1; b = 2;\nelif y: c = 3; d = 4\nelse: e = 5;",
+        "while x: break; continue\nelse: c; d",
+        "for i in x: a; b\nelse: c",
+        "try: a; b\nexcept E: c; d\nelse: e; f\nfinally: g; h",
+        "def f(): return 1; x",
+        "with a: b; c",
+        "class C: x = 1; y = 2",
+        "if x:\n    if y: a; b\n    else: c\nelse: d",
+    ],
+)
+def test_one_line_compound_bodies(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "10000000000000000000000000",
+        "-10000000000000000000000000",
+        "1e400",
+        "1e400j",
+        "1.5e-400",
+        "1_000_000",
+        "1_000.0_1e1_0",
+        "0x_ff_FF",
+        "0o7_7",
+        "0b1_0",
+        "00",
+        "0_0",
+        "0123.5",
+        "0777j",
+        "07e1",
+        "1.5J",
+        "1if x else y",
+        "[0x1for x in y]",
+        "1or 2",
+        "1 + 2j",
+    ],
+)
+def test_number_literals(check_ast_attributes, source):
+    check_ast_attributes(source, mode="eval")
+
+
+@pytest.mark.parametrize(
+    "source, args",
+    [
+        ("$[head -n 0123]", "'head', '-n', '0123'"),
+        ("$(echo 1_ 0b2 1abc)", "'echo', '1_', '0b2', '1abc'"),
+    ],
+)
+def test_malformed_numbers_in_commands(source, args, parse_str):
+    assert f"({args})" in ast.unparse(parse_str(source))
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "f(*args)",
+        "f(* a)",
+        "f(*a, *b, c, *d)",
+        "f(a, *b, k=1, **c)",
+        "f(**a, **b)",
+        "f(*a or b, **c or d)",
+        "def g():\n    return *a, *b",
+        "def g():\n    return *a,",
+        "def g():\n    yield *a,",
+        "def g():\n    yield *a, b",
+    ],
+)
+def test_unpacking_in_calls_and_returns(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+def test_unpacking_in_calls_and_returns_run():
+    from peg_parser import parse
+
+    source = """
+def f(*args, **kwargs):
+    return args, kwargs
+
+def g(a, b):
+    return *a, *b
+
+def h(a):
+    yield *a, 0
+
+calls = f(*[1, 2], 3, *(4,), k=5, **{"x": 6}, **{"y": 7, "a": 8})
+returns = g([1, 2], (3,)), next(h(range(2)))
+try:
+    f(**{"x": 1}, **{"x": 2})
+except TypeError as e:
+    duplicate = str(e)
+"""
+    namespace: dict = {}
+    exec(compile(parse(source), "<test>", "exec"), namespace)
+    assert namespace["calls"] == ((1, 2, 3, 4), {"k": 5, "x": 6, "y": 7, "a": 8})
+    assert list(namespace["calls"][1]) == ["k", "x", "y", "a"]
+    assert namespace["returns"] == ((1, 2, 3), (0, 1, 0))
+    assert "multiple values for keyword argument 'x'" in namespace["duplicate"]
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "x[a:=b]",
+        "x[a:=b, c]",
+        "x:int=1",
+        "f(a:=1)",
+        "print(x:=1, y=2)",
+        "[y:=1, 2]",
+        "{a:=1}",
+        "x:=1",
+        "x = y:=1",
+        "{x: =1}",
+        "x.y := 1",
+        "def f(a:=1): pass",
+        "{a:=1: 2}",
+    ],
+)
+def test_walrus_tokens(source):
+    from peg_parser import parse
+
+    try:
+        expected = ast.dump(ast.parse(source), include_attributes=True)
+    except SyntaxError as e:
+        expected = (e.msg, e.lineno, e.offset, e.end_lineno, e.end_offset)
+    try:
+        result = ast.dump(parse(source), include_attributes=True)
+    except SyntaxError as e:
+        result = (e.msg, e.lineno, e.offset, e.end_lineno, e.end_offset)
+    assert result == expected
+
+
 def test_subscription_special_syntaxes(eval_code):
     assert eval_code("arr[1, 2, 3]", arr=arr_container()) == (1, 2, 3)
     # dataframe
@@ -148,6 +398,34 @@ def test_exprs(inp, exp, unparse_diff):
     unparse_diff(inp, exp)
 
 
+def test_path_literals_make_paths(check_xonsh_ast, xsh):
+    xsh.path_literal = Path
+    paths = []
+    check_xonsh_ast(
+        'paths.extend([p"/tmp/x", pf"/tmp/{name}", pr"/tmp/\\d", Fp"/tmp/{name}/d"])',
+        mode="exec",
+        paths=paths,
+        name="y",
+    )
+    assert paths == [Path("/tmp/x"), Path("/tmp/y"), Path("/tmp/\\d"), Path("/tmp/y/d")]
+
+
+@pytest.mark.parametrize("inp", ['x = p"/tmp"', 'x = pf"/{t}mp"'])
+def test_path_literal_location(inp):
+    from peg_parser import parse
+
+    call = parse(inp).body[0].value
+    assert (call.lineno, call.col_offset, call.end_lineno, call.end_col_offset) == (1, 4, 1, len(inp))
+    assert call.args[0].col_offset == 4
+
+
+@pytest.mark.parametrize("inp, start", [('pb"/tmp"', 0), ('x = bP"/tmp"', 4), ('p"/a" + rpb"/b"', 8)])
+def test_bytes_path_literal(python_parse_str, inp, start):
+    with pytest.raises(SyntaxError, match="path literals cannot be bytes") as e:
+        python_parse_str(inp, mode="exec")
+    assert (e.value.offset, e.value.end_offset) == (start + 1, len(inp) + 1)
+
+
 @pytest.mark.parametrize("inp, exp", glob_data_param("fstring_py312.py"))
 @pytest.mark.skipif(sys.version_info < (3, 12), reason="requires python3.12")
 def test_py312_fstring(inp, exp, unparse_diff):
@@ -275,3 +553,1125 @@ def test_parsing(parse_str, subtests):
     for name, inp in get_cases(file):
         with subtests.test(name=name):
             parse_str(inp, mode="exec")
+
+
+def test_parse_expression_str(python_parser_cls):
+    node = python_parser_cls.parse_expression_str("x + $HOME")
+    assert isinstance(node, ast.BinOp)
+    assert (node.lineno, node.col_offset, node.end_lineno, node.end_col_offset) == (1, 0, 1, 9)
+
+
+@pytest.mark.parametrize("inp", ["1 + 2 extra", "x = 1", "1\n2"])
+def test_parse_expression_str_requires_full_input(python_parser_cls, inp):
+    with pytest.raises(SyntaxError):
+        python_parser_cls.parse_expression_str(inp)
+
+
+def test_parse_suite_at_indent(python_parser_cls):
+    body = python_parser_cls.parse_suite("    x = 1\n    if x:\n        y = 2\n    z = 3\n")
+    assert [type(stmt) for stmt in body] == [ast.Assign, ast.If, ast.Assign]
+    assert [stmt.col_offset for stmt in body] == [4, 4, 4]
+    assert body[1].body[0].col_offset == 8
+
+
+def test_parse_suite_dedented_below_first_line(python_parser_cls):
+    with pytest.raises(IndentationError):
+        python_parser_cls.parse_suite("    x = 1\ny = 2\n")
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "        x = (\n1,\n)\n        y\n",
+        "        if a:\n            b = [\n    1,\n]\n        c\n",
+        "        def f(\n):\n            return (\n  1\n)\n        g = f()\n",
+    ],
+)
+def test_parse_suite_brackets_closed_below_indent(python_parser_cls, source):
+    body = python_parser_cls.parse_suite(source)
+    assert [stmt.col_offset for stmt in body] == [8, 8]
+
+
+@pytest.mark.parametrize(
+    "source, lineno, offset",
+    [
+        ("        x = 1\n    y = 2\n", 2, 10),
+        ("        if a:\n            b\n          c\n", 3, 12),
+        ("        x = (\n1)\ny\n", 3, 2),
+    ],
+)
+def test_parse_suite_unindent_mismatch(python_parser_cls, source, lineno, offset):
+    with pytest.raises(IndentationError, match="unindent does not match any outer indentation level") as e:
+        python_parser_cls.parse_suite(source)
+    assert (e.value.lineno, e.value.offset, e.value.end_offset) == (lineno, offset, -1)
+
+
+def test_parse_suite_without_indent_ok(python_parser_cls):
+    assert len(python_parser_cls.parse_suite("x = 1\ny = 2\n", indent_ok=False)) == 2
+    with pytest.raises(SyntaxError):
+        python_parser_cls.parse_suite("    x = 1\n", indent_ok=False)
+
+
+def test_parse_signature_matches_ast():
+    import inspect
+
+    from peg_parser import parse
+
+    expected = inspect.signature(ast.parse).parameters
+    obtained = inspect.signature(parse).parameters
+    for name, param in expected.items():
+        assert (obtained[name].kind, obtained[name].default) == (param.kind, param.default)
+
+
+@pytest.mark.parametrize(
+    "source, mode",
+    [
+        ("x = 1\n", "exec"),
+        ("x + 1\n", "eval"),
+        ("x = 1; y\n", "single"),
+        ("(int, *str) -> list[int]", "func_type"),
+        ("() -> None", "func_type"),
+        (b"x = 1\n", "exec"),
+    ],
+)
+def test_parse_modes(check_ast_attributes, source, mode):
+    check_ast_attributes(source, mode=mode)
+
+
+@pytest.mark.parametrize(
+    "source, is_expression_last, span",
+    [
+        ("x + 1\n", True, (1, 0, 1, 5)),
+        ("$(ls)", True, (1, 0, 1, 5)),
+        ("x = 1\nif x:\n    pass\nf(x,\n  y)\n", True, (4, 0, 5, 4)),
+        ("x = 1; x\ny = 2\n", False, None),
+        ("def f():\n    x\n", False, None),
+        ("", False, None),
+        ("# comment\n\n", False, None),
+    ],
+)
+def test_parse_single_with_echo(source, is_expression_last, span):
+    from peg_parser import parse, parse_single_with_echo
+
+    tree, is_last, last_span = parse_single_with_echo(source)
+    assert isinstance(tree, ast.Interactive)
+    assert ast.dump(tree) == ast.dump(ast.Interactive(body=parse(source).body))
+    assert is_last is is_expression_last
+    attrs = ("lineno", "col_offset", "end_lineno", "end_col_offset")
+    assert last_span == (None if span is None else dict(zip(attrs, span)))
+    compile(tree, "<stdin>", "single")
+
+
+@pytest.mark.parametrize(
+    "source, expected",
+    [
+        ("a && b || c", "a and b or c"),
+        ("a || b && c", "a or b and c"),
+        ("not a && b", "not a and b"),
+        ("not (a || b)", "not (a or b)"),
+        ("a < b && b == c || d is not e", "a < b and b == c or d is not e"),
+        ("a && b and c || d or e", "a and b and c or d or e"),
+    ],
+)
+def test_xonsh_boolean_operators(python_parse_str, source, expected):
+    assert ast.dump(python_parse_str(source)) == ast.dump(ast.parse(expected, mode="eval"))
+
+
+@pytest.mark.parametrize(
+    "source, message, offset",
+    [
+        ("a && b", "'&&' is only valid in xonsh, use 'and'", 3),
+        ("x = not a || b", "'||' is only valid in xonsh, use 'or'", 11),
+    ],
+)
+def test_xonsh_boolean_operators_off(python_parse_str, source, message, offset):
+    from peg_parser import parse
+
+    with pytest.raises(SyntaxError) as e:
+        python_parse_str(source, mode="exec", xonsh_operators=False)
+    assert (e.value.msg, e.value.offset) == (message, offset)
+    with pytest.raises(SyntaxError, match=message):
+        parse(source, xonsh_operators=False)
+    python_parse_str(source.replace("&&", "and").replace("||", "or"), mode="exec", xonsh_operators=False)
+
+
+@pytest.mark.parametrize("source", ["a ?? b", "x = a.b ?? c"])
+def test_null_coalescing_is_not_supported(python_parse_str, source):
+    with pytest.raises(SyntaxError, match=r"null-coalescing operator '\?\?' is not supported yet"):
+        python_parse_str(source, mode="exec")
+
+
+@pytest.mark.parametrize("source", ["a.b.c.d", "a . b", "(a\n  .b\n  .c)", "a.b().c[0].d", "a.b\\\n.c"])
+def test_attribute_chain_locations(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+@pytest.mark.parametrize("source, start", [("$(echo @(1 if c))", 10), ("x = $(ls) if c", 5)])
+def test_missing_else_in_xonsh_expression(python_parse_str, source, start):
+    with pytest.raises(SyntaxError, match="expected 'else' after 'if' expression") as e:
+        python_parse_str(source, mode="exec")
+    assert (e.value.offset, e.value.end_offset) == (start, source.index(" if ") + len(" if c") + 1)
+
+
+@pytest.mark.parametrize(
+    "kwargs, exc",
+    [
+        ({"mode": "bad"}, ValueError),
+        ({"feature_version": (3, 10)}, SyntaxError),
+        ({"feature_version": 10}, SyntaxError),
+    ],
+)
+def test_parse_errors_match_ast(kwargs, exc):
+    from peg_parser import parse
+
+    source = "try:\n    pass\nexcept* E:\n    pass\n"
+    with pytest.raises(exc):
+        ast.parse(source, **kwargs)
+    with pytest.raises(exc):
+        parse(source, **kwargs)
+
+
+@pytest.mark.parametrize(
+    "source, feature_version, expected",
+    [
+        (
+            "async = 1; await = 2\n",
+            (3, 6),
+            (
+                "Module(body=[Assign(targets=[Name(id='async', ctx=Store())], "
+                "value=Constant(value=1)), Assign(targets=[Name(id='await', ctx=Store())], "
+                "value=Constant(value=2))], type_ignores=[])"
+            ),
+        ),
+        (
+            "x = async.y + await(1)\ndef async(await): pass\nf(async=1)\n",
+            (3, 6),
+            (
+                "Module(body=[Assign(targets=[Name(id='x', ctx=Store())], "
+                "value=BinOp(left=Attribute(value=Name(id='async', ctx=Load()), attr='y', ctx=Load()), "
+                "op=Add(), right=Call(func=Name(id='await', ctx=Load()), args=[Constant(value=1)], "
+                "keywords=[]))), FunctionDef(name='async', args=arguments(posonlyargs=[], "
+                "args=[arg(arg='await')], kwonlyargs=[], kw_defaults=[], defaults=[]), body=[Pass()], "
+                "decorator_list=[]), Expr(value=Call(func=Name(id='f', ctx=Load()), args=[], "
+                "keywords=[keyword(arg='async', value=Constant(value=1))]))], type_ignores=[])"
+            ),
+        ),
+        (
+            "def f():\n    await x\n",
+            (3, 6),
+            ("invalid syntax", 2, 11),
+        ),
+        (
+            "async def f():\n    await x\n    async for a in b: pass\nawait = 1\n",
+            (3, 6),
+            (
+                "Module(body=[AsyncFunctionDef(name='f', args=arguments(posonlyargs=[], args=[], "
+                "kwonlyargs=[], kw_defaults=[], defaults=[]), "
+                "body=[Expr(value=Await(value=Name(id='x', ctx=Load()))), AsyncFor(target=Name(id='a', "
+                "ctx=Store()), iter=Name(id='b', ctx=Load()), body=[Pass()], orelse=[])], "
+                "decorator_list=[]), Assign(targets=[Name(id='await', ctx=Store())], "
+                "value=Constant(value=1))], type_ignores=[])"
+            ),
+        ),
+        (
+            "async def f():\n    async = 1\n",
+            (3, 6),
+            ("invalid syntax", 2, 11),
+        ),
+        (
+            "class C:\n    async def f(self):\n        def g(): await x\n    await = 3\n",
+            (3, 6),
+            (
+                "Module(body=[ClassDef(name='C', bases=[], keywords=[], "
+                "body=[AsyncFunctionDef(name='f', args=arguments(posonlyargs=[], "
+                "args=[arg(arg='self')], kwonlyargs=[], kw_defaults=[], defaults=[]), "
+                "body=[FunctionDef(name='g', args=arguments(posonlyargs=[], args=[], kwonlyargs=[], "
+                "kw_defaults=[], defaults=[]), body=[Expr(value=Await(value=Name(id='x', "
+                "ctx=Load())))], decorator_list=[])], decorator_list=[]), "
+                "Assign(targets=[Name(id='await', ctx=Store())], value=Constant(value=3))], "
+                "decorator_list=[])], type_ignores=[])"
+            ),
+        ),
+        (
+            "@d\nasync def f(): await x\nasync(1)\n",
+            (3, 6),
+            (
+                "Module(body=[AsyncFunctionDef(name='f', args=arguments(posonlyargs=[], args=[], "
+                "kwonlyargs=[], kw_defaults=[], defaults=[]), "
+                "body=[Expr(value=Await(value=Name(id='x', ctx=Load())))], "
+                "decorator_list=[Name(id='d', ctx=Load())]), Expr(value=Call(func=Name(id='async', "
+                "ctx=Load()), args=[Constant(value=1)], keywords=[]))], type_ignores=[])"
+            ),
+        ),
+        (
+            "async with a: pass\n",
+            (3, 6),
+            ("invalid syntax", 1, 7),
+        ),
+        (
+            "async = 1; await = 2\n",
+            (3, 8),
+            ("invalid syntax", 1, 7),
+        ),
+        (
+            "x = async.y + await(1)\ndef async(await): pass\nf(async=1)\n",
+            (3, 8),
+            ("invalid syntax", 1, 5),
+        ),
+        (
+            "def f():\n    await x\n",
+            (3, 8),
+            (
+                "Module(body=[FunctionDef(name='f', args=arguments(posonlyargs=[], args=[], "
+                "kwonlyargs=[], kw_defaults=[], defaults=[]), "
+                "body=[Expr(value=Await(value=Name(id='x', ctx=Load())))], decorator_list=[])], "
+                "type_ignores=[])"
+            ),
+        ),
+        (
+            "async def f():\n    await x\n    async for a in b: pass\nawait = 1\n",
+            (3, 8),
+            ("invalid syntax", 4, 7),
+        ),
+        (
+            "async def f():\n    async = 1\n",
+            (3, 8),
+            ("invalid syntax", 2, 11),
+        ),
+        (
+            "class C:\n    async def f(self):\n        def g(): await x\n    await = 3\n",
+            (3, 8),
+            ("invalid syntax", 4, 11),
+        ),
+        (
+            "@d\nasync def f(): await x\nasync(1)\n",
+            (3, 8),
+            ("invalid syntax", 3, 6),
+        ),
+        (
+            "async with a: pass\n",
+            (3, 8),
+            (
+                "Module(body=[AsyncWith(items=[withitem(context_expr=Name(id='a', ctx=Load()))], "
+                "body=[Pass()])], type_ignores=[])"
+            ),
+        ),
+    ],
+)
+def test_async_names_before_37(source, feature_version, expected):
+    # CPython 3.13 no longer parses 3.6 sources, so the 3.12 results are pinned here
+    from peg_parser import parse
+
+    dump = functools.partial(ast.dump, show_empty=True) if sys.version_info >= (3, 13) else ast.dump
+    try:
+        result = dump(parse(source, feature_version=feature_version)).replace(", type_params=[]", "")
+    except SyntaxError as e:
+        result = (e.msg, e.lineno, e.offset)
+    assert result == expected
+
+
+@pytest.mark.skipif(sys.version_info < (3, 11), reason="except* needs python3.11")
+def test_except_star_handlers(check_xonsh_ast):
+    results = []
+    check_xonsh_ast(
+        "def f(results, *errors):\n"
+        "    try:\n"
+        "        raise ExceptionGroup('group', list(errors))\n"
+        "    except* (KeyError, IndexError) as e:\n"
+        "        results.append(sorted(type(x).__name__ for x in e.exceptions))\n"
+        "    except* ValueError:\n"
+        "        results.append('value')\n"
+        "    else:\n"
+        "        results.append('else')\n"
+        "    finally:\n"
+        "        results.append('finally')\n"
+        "f(results, KeyError(), ValueError(), IndexError())\n"
+        "f(results, ValueError())",
+        mode="exec",
+        results=results,
+    )
+    assert results == [["IndexError", "KeyError"], "value", "finally", "value", "finally"]
+
+
+def test_parse_reports_filename():
+    from peg_parser import parse
+
+    with pytest.raises(SyntaxError) as e:
+        parse("x = = 1\n", "script.py")
+    assert e.value.filename == "script.py"
+
+
+def test_initial_position():
+    from peg_parser import parse
+
+    source = 'x = (a +\n  b)\nif x:\n    y = """a\nb"""; z = 1\n'
+    expected = ast.parse(source)
+    ast.increment_lineno(expected, 99)
+    for node in ast.walk(expected):
+        if getattr(node, "lineno", None) == 100:
+            node.col_offset += 8
+        if getattr(node, "end_lineno", None) == 100:
+            node.end_col_offset += 8
+    tree = parse(source, initial_lineno=100, initial_col_offset=8)
+    assert ast.dump(tree, include_attributes=True) == ast.dump(expected, include_attributes=True)
+
+
+@pytest.mark.parametrize(
+    "source, position",
+    [
+        ("x = = 1", (10, 9, 10, 10)),
+        ("x = (1,\n 2 +)", (11, 5, 11, 6)),
+        ("s = 'abc", (10, 9, 10, 9)),
+        ("x = 1\n  y", (11, 2, 11, -1)),
+    ],
+)
+def test_initial_position_of_errors(source, position):
+    from peg_parser import parse
+
+    with pytest.raises(SyntaxError) as e:
+        parse(source, initial_lineno=10, initial_col_offset=4)
+    assert (e.value.lineno, e.value.offset, e.value.end_lineno, e.value.end_offset) == position
+
+
+def test_parser_reused_for_many_sources():
+    from peg_parser import Parser
+
+    parser = Parser("eval", filename="repl.xsh", xonsh_operators=False)
+    for source in ["1 + 2", "f(x)[0]", "[i for i in y]", b"x.y"]:
+        assert ast.dump(parser.parse(source)) == ast.dump(ast.parse(source, mode="eval"))
+    with pytest.raises(SyntaxError, match="'&&' is only valid in xonsh") as e:
+        parser.parse("a && b")
+    assert e.value.filename == "repl.xsh"
+    with pytest.raises(SyntaxError) as e:
+        parser.parse("a +", "line 3")
+    assert e.value.filename == "line 3"
+
+
+@pytest.mark.parametrize(
+    "kwargs, exc",
+    [
+        ({"mode": "bad"}, ValueError),
+        ({"feature_version": (2, 7)}, ValueError),
+    ],
+)
+def test_parser_checks_options_once(kwargs, exc):
+    from peg_parser import Parser
+
+    with pytest.raises(exc):
+        Parser(**kwargs)
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "x = y = 1  # type: int\n",
+        "x = 1  #type:int  \n",
+        "def f(a,  # type: int\n      *args,  # type: str\n      b=1  # type: int\n      ):\n"
+        "    # type: (...) -> None\n    pass\n",
+        "async def f(a, b):  # type: (int, str) -> None\n"
+        "    async for x in y:  # type: int\n        pass\n",
+        "with a as b:  # type: int\n    pass\n",
+        "import a  # type: ignore[attr]  # more\n# type: ignore\nx = 1  # type: ignore_\n",
+        "x = (1  # type: ignore\n)\n",
+        "x = 1\n# type: int\n",
+        "x + 1  # type: int\n",
+        "import a  # type: ignored\n",
+        "def f():  # type: () -> None\n    # type: () -> int\n    pass\n",
+        "def f(*, # type: int\n a): pass\n",
+    ],
+)
+def test_type_comments(source):
+    from peg_parser import parse
+
+    def parsed(parse):
+        try:
+            return ast.dump(parse(source, type_comments=True), include_attributes=True)
+        except SyntaxError as e:
+            return (e.msg, e.lineno, e.offset, e.end_lineno, e.end_offset)
+
+    assert parsed(parse) == parsed(ast.parse)
+    assert ast.dump(parse("x = 1  # type: int\n")) == ast.dump(ast.parse("x = 1  # type: int\n"))
+
+
+def test_parser_shared_between_threads():
+    from concurrent.futures import ThreadPoolExecutor
+
+    from peg_parser import Parser
+
+    parser = Parser()
+    sources = [f"def f{i}(a, b={i}):\n    return [a * x for x in range(b)]\n" for i in range(50)]
+    with ThreadPoolExecutor(max_workers=4) as pool:
+        trees = list(pool.map(parser.parse, sources))
+    assert [ast.dump(tree) for tree in trees] == [ast.dump(ast.parse(source)) for source in sources]
+
+
+def test_parser_threads_do_not_share_memo():
+    import sys
+    import threading
+
+    from peg_parser import Parser
+
+    # the same rules are tried at the same token positions, with different results and errors
+    sources = ["f(a)\n", "f[a]\n", "f.a\n", "f(a\n", "f = a\n", "f + a\n", "f(*)\n", "f = $(a)\n"]
+
+    def result(parse, source):
+        try:
+            return ast.dump(parse(source), include_attributes=True)
+        except SyntaxError as e:
+            return e.msg, e.lineno, e.offset
+
+    expected = [result(Parser().parse, source) for source in sources]
+    parser = Parser()
+    barrier = threading.Barrier(8)
+    failures = []
+
+    def run(index):
+        barrier.wait()
+        for repeat in range(25):
+            position = (index + repeat) % len(sources)
+            found = result(parser.parse, sources[position])
+            if found != expected[position]:
+                failures.append((sources[position], found))
+
+    interval = sys.getswitchinterval()
+    sys.setswitchinterval(1e-6)  # switch threads within the parses
+    try:
+        threads = [threading.Thread(target=run, args=(index,)) for index in range(8)]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+    finally:
+        sys.setswitchinterval(interval)
+    assert failures == []
+
+
+def test_parse_as_ast_parse_replacement(monkeypatch):
+    import inspect
+    import textwrap
+
+    from peg_parser import parse
+
+    source = Path(textwrap.__file__).read_text()
+    expected = ast.dump(ast.parse(source), include_attributes=True)
+    signature = inspect.signature(divmod)
+    monkeypatch.setattr(ast, "parse", parse)
+
+    assert ast.dump(ast.parse(source), include_attributes=True) == expected
+    assert ast.literal_eval("{'a': [1, 2.5, -3j], 'b': (None, True)}") == {
+        "a": [1, 2.5, -3j],
+        "b": (None, True),
+    }
+    assert inspect.signature(divmod) == signature
+
+
+def test_identifiers_are_interned(python_parse_str):
+    source = "def f(some_arg):\n    global some_name\n    some_arg.some_name = some_name(some_arg=1)\n"
+    tree = python_parse_str(source, mode="exec")
+    func = tree.body[0]
+    assign = func.body[1]
+    call = assign.value
+    assert func.args.args[0].arg is assign.targets[0].value.id is call.keywords[0].arg
+    assert func.body[0].names[0] is assign.targets[0].attr is call.func.id
+
+
+PY_DATA_FILES = [
+    *(
+        path
+        for path in sorted(DATA.glob("*.py"))
+        if path.name not in ("try_except_group.py", "type_params.py", "fstring_py312.py")
+    ),
+    pytest.param(DATA / "try_except_group.py", marks=requires_py311),
+    pytest.param(DATA / "type_params.py", marks=requires_py312),
+    pytest.param(DATA / "fstring_py312.py", marks=requires_py312),
+]
+
+
+@pytest.mark.parametrize("path", [*PY_DATA_FILES, DATA / "statements.xsh"], ids=lambda path: path.name)
+def test_nodes_compile_without_warnings(python_parse_str, path):
+    """Nodes are built with keyword arguments only, so they stay valid across the CI matrix (3.10 - 3.13)."""
+    for part in path.read_text().split("\n\n\n"):
+        with warnings.catch_warnings():
+            warnings.simplefilter("error")
+            tree = python_parse_str(part, "exec")
+        for node in ast.walk(tree):
+            assert all(hasattr(node, field) for field in node._fields), ast.dump(node)
+        if path.suffix == ".py":
+            try:
+                compile(part, str(path), "exec", dont_inherit=True)
+            except SyntaxError:  # fixtures may hold code that only parses
+                continue
+        with warnings.catch_warnings():
+            warnings.simplefilter("error")
+            compile(tree, str(path), "exec", dont_inherit=True)
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "x = f(a,\n      b) + 1\n",
+        "if x:\n\tfoo(\n\t\t1)\n",
+        "s = 'abc'; t = [\n  1,\n  2]\n",
+    ],
+)
+def test_source_segment_matches_ast(source):
+    from peg_parser import parse, source_segment
+
+    for exp, obs in zip(ast.walk(ast.parse(source)), ast.walk(parse(source)), strict=True):
+        for padded in (False, True):
+            expected = ast.get_source_segment(source, exp, padded=padded)
+            assert source_segment(source, obs, padded=padded) == expected
+
+
+def test_source_segment_counts_characters():
+    from peg_parser import parse, source_segment
+
+    source = "é = 'ü' + (ñ,\n  ø)\n"
+    value = parse(source).body[0].value
+    assert source_segment(source, value.left) == "'ü'"
+    assert source_segment(source, value.right) == "(ñ,\n  ø)"
+    assert source_segment(source, value.right, padded=True) == "          (ñ,\n  ø)"
+    assert source_segment(source, ast.Load()) is None
+
+
+def test_columns_count_characters():
+    from peg_parser import parse
+
+    source = "x = 'é'; y\nf('ü', b=\"€\") + ñ\n"
+    expected = ast.parse(source)
+    lines = source.encode().splitlines()
+    # the UTF-8 byte columns of ast.parse as characters
+    for node in ast.walk(expected):
+        if getattr(node, "end_col_offset", None) is not None:
+            node.col_offset = len(lines[node.lineno - 1][: node.col_offset].decode())
+            node.end_col_offset = len(lines[node.end_lineno - 1][: node.end_col_offset].decode())
+    tree = parse(source)
+    assert ast.dump(tree, include_attributes=True) == ast.dump(expected, include_attributes=True)
+    assert tree.body[1].value.col_offset == 9
+
+
+def dump_loaded(node):
+    return re.sub(r"Store\(\)|Del\(\)", "Load()", ast.dump(node))
+
+
+@pytest.mark.parametrize("path", PY_DATA_FILES, ids=lambda path: path.name)
+def test_source_segment_round_trips(path):
+    """The text of each expression parses back to the same node, which checks the node locations."""
+    from peg_parser import parse, source_segment
+
+    source = path.read_text()
+    tree = parse(source)
+    # parts of f-strings, slices and starred items are not expressions on their own
+    skipped = set()
+    for node in ast.walk(tree):
+        if isinstance(node, ast.JoinedStr | ast.Slice | ast.Starred | ast.MatchStar):
+            skipped.update(id(child) for child in ast.walk(node))
+    for node in ast.walk(tree):
+        if isinstance(node, ast.expr) and id(node) not in skipped:
+            segment = source_segment(source, node)
+            again = parse(f"({segment})", mode="eval").body
+            assert dump_loaded(again) == dump_loaded(node), (segment, node.lineno)
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        '"""a\nb\nc\nd\ne"""\n',
+        "x = 1\n'''a\n  b'''\n",
+        "def f():\n    \"\"\"doc \"q\"\n    ''' \\\"\"\"\n    \"\"\"\n    return\n",
+        "'''a\\\nb\\\n''' ; y\n",
+        "\"\"\"a\"\"\" '''b\nc'''\n",
+    ],
+)
+def test_multi_line_string_locations(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+SURROGATE = "\ud800"
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        f'x = "a{SURROGATE}b"\n',
+        f"x = f'{SURROGATE}{{y}}' r'{SURROGATE}'  # {SURROGATE}\n",
+        f'x = $(ls "{SURROGATE}.txt")\n',
+    ],
+)
+def test_lone_surrogates_in_literals(source):
+    from peg_parser import parse, parse_cached, parse_tokens_buffer, tokenize_for_parse
+
+    tree = parse(source)
+    assert repr(SURROGATE)[1:-1] in ast.dump(tree)
+    assert ast.dump(parse_cached(source)) == ast.dump(tree)
+    assert ast.dump(parse_tokens_buffer(tokenize_for_parse(source))) == ast.dump(tree)
+
+
+@pytest.mark.parametrize("source", [f"x = 1 {SURROGATE}\n", f"x{SURROGATE} = 1\n", f"ls {SURROGATE}\n"])
+def test_stray_lone_surrogates(source):
+    from peg_parser import parse
+    from peg_parser.tokenize import Token, generate_tokens
+
+    errors = [tok for tok in generate_tokens(source) if tok.type == Token.ERRORTOKEN]
+    assert [tok.string for tok in errors] == [SURROGATE]
+    with pytest.raises(SyntaxError, match="invalid syntax") as e:
+        parse(source)
+    assert e.value.offset == source.index(SURROGATE) + 1
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "obj.attr[key].other = value",
+        "d[k1][k2] += 1",
+        "f(x).y = 3",
+        "f(x)[0], a.b(c)(d).e = 1, 2",
+        "for f(x).y in z: pass",
+        "with a as f(x)[k]: pass",
+        "del f(x).y, g()[0]",
+    ],
+)
+def test_call_trailers_in_targets(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+@pytest.mark.parametrize("source", ["f(x) = 3", "a.b(c) = 3", "f(x) += 1", "del f(x)", "for f(x) in y: pass"])
+def test_call_as_last_trailer_of_target(source):
+    from peg_parser import parse
+
+    with pytest.raises(SyntaxError) as expected:
+        ast.parse(source)
+    with pytest.raises(SyntaxError) as e:
+        parse(source)
+    assert (e.value.msg, e.value.offset, e.value.end_offset) == (
+        expected.value.msg,
+        expected.value.offset,
+        expected.value.end_offset,
+    )
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "f(match=1, case=2, type=3, _=4)",
+        "f(a, *b, match=c, **d)",
+        "obj.match.case.type._",
+        "obj.type(x).case = y",
+        "match(x); case(y); type(z); _(w)",
+        "match(match=case)",
+        "case.type(type=match.case)",
+        "print(*args, sep=type)",
+    ],
+)
+def test_soft_keywords_in_calls(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+UNPACKING_AFTER_KWARGS = "iterable argument unpacking follows keyword argument unpacking"
+
+
+@pytest.mark.parametrize(
+    "source, expected",
+    [
+        ("print(*, sep=',')", ("invalid syntax", 1, 8, 9)),
+        ("f(*)", ("invalid syntax", 1, 4, 5)),
+        ("f(**)", ("invalid syntax", 1, 5, 6)),
+        ("f(*, )", ("invalid syntax", 1, 4, 5)),
+        ("f(**, a)", ("invalid syntax", 1, 5, 6)),
+        ("f(a, *)", ("invalid syntax", 1, 7, 8)),
+        ("f(x, **)", ("invalid syntax", 1, 8, 9)),
+        ("f(*, **)", ("invalid syntax", 1, 4, 5)),
+        ("f(a=1, *)", (UNPACKING_AFTER_KWARGS, 1, 8, 9)),
+        ("f(**kw, *)", (UNPACKING_AFTER_KWARGS, 1, 9, 10)),
+        ("f(**kw, *a)", (UNPACKING_AFTER_KWARGS, 1, 9, 10)),
+        ("match(a, *, b)", ("invalid syntax", 1, 11, 12)),
+    ],
+)
+def test_bare_star_in_call(source, expected):
+    """Errors match CPython 3.11/3.12; 3.10 and 3.13 word and place some of them differently."""
+    from peg_parser import parse
+
+    with pytest.raises(SyntaxError) as e:
+        parse(source)
+    assert (e.value.msg, e.value.lineno, e.value.offset, e.value.end_offset) == expected
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "class A:\n    @property\n    # a comment\n    def f(self): pass\n",
+        "class A:\n    class B:\n        @d  # a comment\n\n        # another\n\t\n"
+        "        @e\n        async def f(self): pass\n",
+        "class A:\n  \t@d\n  \t# a comment\n  \tclass B: pass\n",
+        "if x:\n    @d\n        # a comment indented further\n    def f(): pass\n",
+        "@d\n# a comment\n\n@e(\n  # within the call\n)\nclass C:\n    @x\n    # y\n    def g(): ...\n",
+    ],
+)
+def test_decorated_definition_locations(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+PEP646_SOURCES = [
+    # adapted from the examples of PEP 646
+    "def f(*args: *Ts) -> Tuple[*Ts]: ...",
+    "x: Array[int, *Shape]",
+    "class Array(Generic[DType, *Shape]): ...",
+    "def g(x: Array[Batch, *Shape]) -> Tuple[*Shape, Batch]: ...",
+    "x: Tuple[int, *Tuple[str, ...]]",
+    "y = a[*b]",
+    "y = a[1:2, *b, c]",
+    "a[*b] = c",
+]
+
+
+@requires_py311
+@pytest.mark.parametrize("source", PEP646_SOURCES)
+def test_variadic_generics(check_ast_attributes, source):
+    check_ast_attributes(source, feature_version=(3, 11))
+
+
+@pytest.mark.parametrize("source", PEP646_SOURCES)
+def test_variadic_generics_need_311(source):
+    from peg_parser import parse
+
+    feature = "Starred annotations are" if source.startswith("def f(*args") else "Unpacking in subscripts is"
+    with pytest.raises(SyntaxError) as e:
+        parse(source, feature_version=(3, 10))
+    assert e.value.msg == f"{feature} only supported in Python 3.11 and greater"
+
+
+@pytest.mark.parametrize("source", ["x: Tuple[int, str]", "y = a[b, c:d]", "def f(*args: int): ..."])
+def test_older_annotations_and_subscripts(source):
+    from peg_parser import parse
+
+    expected = ast.dump(ast.parse(source, feature_version=(3, 10)))
+    assert ast.dump(parse(source, feature_version=(3, 10))) == expected
+
+
+# as edited by older xonsh users, a tab indenting lines of a block otherwise indented with 8 spaces
+MIXED_TABS = "def f(x):\n        if x:\n\t    y = (1,\n\t\t2)\n\t    return y\n\treturn x\t# done\n"
+
+
+@pytest.mark.parametrize(
+    "source, accepted",
+    [
+        (MIXED_TABS, {"EXPAND"}),
+        ("def f(x):\n\tif x:\n\t\treturn (1,\n\t2)\n", {"STRICT", "EXPAND"}),
+        (
+            "def f(x):\n    if x:\n        return (1,\n\t2)\t# tabs within statements\n",
+            {"STRICT", "EXPAND", "FORBID"},
+        ),
+    ],
+)
+def test_tab_policies(source, accepted):
+    from peg_parser import parse
+    from peg_parser.tokenize import TabPolicy
+
+    for policy in TabPolicy:
+        if policy.name in accepted:
+            expected = ast.dump(ast.parse(source.expandtabs(8)))
+            assert ast.dump(parse(source, tab_policy=policy)) == expected
+        else:
+            with pytest.raises(TabError):
+                parse(source, tab_policy=policy)
+
+
+def test_strict_tabs_by_default():
+    from peg_parser import parse
+
+    with pytest.raises(TabError) as expected:
+        ast.parse(MIXED_TABS)
+    with pytest.raises(TabError) as e:
+        parse(MIXED_TABS)
+    assert (e.value.msg, e.value.lineno, e.value.offset) == (
+        expected.value.msg,
+        expected.value.lineno,
+        expected.value.offset,
+    )
+
+
+def test_tab_policy_of_entry_points(python_parser_cls, tmp_path):
+    from peg_parser.tokenize import TabPolicy
+
+    path = tmp_path / "mixed.xsh"
+    path.write_text(MIXED_TABS)
+    expected = ast.dump(ast.parse(MIXED_TABS.expandtabs(8)))
+    with pytest.raises(TabError):
+        python_parser_cls.parse_string(MIXED_TABS, "exec")
+    tree = python_parser_cls.parse_string(MIXED_TABS, "exec", tab_policy=TabPolicy.EXPAND)
+    assert ast.dump(tree) == expected
+    assert ast.dump(python_parser_cls.parse_file(path, tab_policy=TabPolicy.EXPAND)) == expected
+    body = python_parser_cls.parse_suite(MIXED_TABS, tab_policy=TabPolicy.EXPAND)
+    assert ast.dump(ast.Module(body=body, type_ignores=[])) == expected
+
+
+def test_node_construction_errors_propagate(monkeypatch):
+    from peg_parser import parse
+
+    error = TypeError("BinOp.__init__() got an unexpected keyword argument 'kind'")
+
+    def broken_binop(*args, **kwargs):
+        raise error
+
+    monkeypatch.setattr(ast, "BinOp", broken_binop)
+    assert ast.dump(parse("x = -1\n"))
+    with pytest.raises(TypeError) as e:
+        parse("x = (1 +\n     2)\n")
+    assert e.value is error
+
+
+@pytest.mark.parametrize("reused", [False, True], ids=["parse", "Parser"])
+def test_stub_files_parse_as_modules(check_ast_attributes, reused):
+    from peg_parser import Parser, parse
+
+    source = (Path(__file__).parent / "data" / "stub_file.pyi").read_text()
+    stub_parse = Parser(is_stub=True).parse if reused else functools.partial(parse, is_stub=True)
+    check_ast_attributes(source, parser=stub_parse)
+    with pytest.raises(IndentationError):
+        stub_parse("class C():\n")
+
+
+@requires_py311
+def test_stub_files_ignore_feature_version():
+    from peg_parser import parse
+
+    source = "def f(*args: *Ts) -> Tuple[*Ts]: ...\n"
+    with pytest.raises(SyntaxError):
+        parse(source, feature_version=(3, 10))
+    expected = ast.dump(ast.parse(source))
+    assert ast.dump(parse(source, feature_version=(3, 10), is_stub=True)) == expected
+
+
+@pytest.mark.parametrize(
+    "source, docstring",
+    [
+        ('"part1 " "part2"\n', "part1 part2"),
+        ('"""part1\n""" \\\n"implicitly continued"\n', "part1\nimplicitly continued"),
+        ('("part1\\n"\n "part2")\nx = 1\n', "part1\npart2"),
+        ('"first"\n"second"\n', "first"),
+        ('x = 1\n"not a" \\\n"docstring"\n', None),
+        ('def f():\n    "part1 " \\\n        "part2"\n    pass\n', "part1 part2"),
+        ('class C:\n    (\n        "part1 "\n        "part2"\n    )\n', "part1 part2"),
+    ],
+)
+def test_docstrings_of_continued_strings(check_ast_attributes, source, docstring):
+    tree = check_ast_attributes(source)
+    node = tree.body[0] if isinstance(tree.body[0], ast.FunctionDef | ast.ClassDef) else tree
+    assert ast.get_docstring(node, clean=False) == docstring
+
+
+@pytest.mark.parametrize(
+    "source, node, feature, version",
+    [
+        ("try:\n    a\nexcept* E:\n    b\n", "TryStar", "Exception groups are", "3.11"),
+        ("type X = int", "TypeAlias", "Type statement is", "3.12"),
+        ("def f[T](x: T): ...", "TypeVar", "Type parameter lists are", "3.12"),
+        ("class C[*Ts]: ...", "TypeVarTuple", "Type parameter lists are", "3.12"),
+        ("def f[**P](): ...", "ParamSpec", "Type parameter lists are", "3.12"),
+    ],
+)
+def test_nodes_missing_from_ast_module(monkeypatch, source, node, feature, version):
+    from peg_parser import parse
+
+    if not hasattr(ast, node):
+        pytest.skip(f"ast.{node} needs Python {version}")
+    parse(source)
+    monkeypatch.delattr(ast, node)
+    with pytest.raises(SyntaxError) as e:
+        parse(source)
+    message = f"{feature} only supported in Python {version} and greater"
+    assert e.value.msg == f"{message}, this Python has no ast.{node}"
+    with pytest.raises(SyntaxError) as e:
+        parse(source, feature_version=(3, 10))
+    assert e.value.msg == message
+
+
+def test_repeated_literals(check_ast_attributes):
+    from peg_parser import parse
+
+    source = "x = [0, 00, 7, 1_000, 0x1f, 0o17, 1e3, 2.5, 2.5, 3j, 'a\\tb', 'a\\tb', b'a\\tb', u'c', 'c', 7]"
+    tree = check_ast_attributes(source)
+    values = [elt.value for elt in tree.body[0].value.elts]
+    assert values[7] is values[8]
+    assert values[10] is values[11]
+    assert values[12] == b"a\tb"
+    with pytest.raises(SyntaxError, match="leading zeros"):
+        parse("x = 7\ny = 07\n")
+
+
+@pytest.mark.parametrize(
+    "target",
+    [
+        "a{}",
+        "a{}.b[f(x{}, (y, z))]",
+        "(a{}, [b{}, *c])",
+    ],
+)
+def test_chained_assignment_parses_linearly(monkeypatch, target):
+    from peg_parser import parse
+    from peg_parser.tokenizer import Tokenizer
+
+    peeks = 0
+    peek = Tokenizer.peek
+
+    def counting_peek(self):
+        nonlocal peeks
+        peeks += 1
+        return peek(self)
+
+    monkeypatch.setattr(Tokenizer, "peek", counting_peek)
+
+    def work(count):
+        nonlocal peeks
+        peeks = 0
+        source = " = ".join(target.format(i, i) for i in range(count)) + " = value\n"
+        tree = parse(source)
+        assert len(tree.body[0].targets) == count
+        return peeks
+
+    # the targets are memoized, so the one turning out to be the value is not parsed again
+    assert work(400) < 2.1 * work(200)
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "for x, in pairs: pass",
+        "for (x,) in pairs: pass",
+        "for [x] in pairs: pass",
+        "for k, v in d.items(): pass",
+        "for k, v, in d.items(): pass",
+        "[y for x, in pairs]",
+        "[v for k, v in d.items()]",
+        "{k: v for k, v, in d.items()}",
+        "[x for x, in a for y, in b if x in y]",
+        "async def f():\n    [x async for x, in pairs]",
+    ],
+)
+def test_for_targets_stop_at_in(check_ast_attributes, source):
+    check_ast_attributes(source)
+
+
+def test_for_targets_stop_at_in_run():
+    from peg_parser import parse
+
+    source = """
+pairs = [(1,), (2,)]
+d = {"a": 1, "b": 2}
+for x, in pairs:
+    loop = x
+for (y,) in pairs:
+    parenthesized = y
+items = [(k, v) for k, v in d.items()]
+firsts = [x for x, in pairs]
+swapped = {v: k for k, v, in d.items()}
+"""
+    namespace: dict = {}
+    exec(compile(parse(source), "<test>", "exec"), namespace)
+    expected: dict = {}
+    exec(compile(source, "<test>", "exec"), expected)
+    for name in ("loop", "parenthesized", "items", "firsts", "swapped"):
+        assert namespace[name] == expected[name]
+    assert (namespace["loop"], namespace["firsts"]) == (2, [1, 2])
+
+
+def test_parse_touches_only_its_nodes(monkeypatch):
+    from peg_parser import parse, parser, subheader
+
+    touched: dict[str, set[str]] = {"parser": set(), "subheader": set()}
+
+    class CountingAst:
+        def __init__(self, module):
+            self.module = module
+
+        def __getattr__(self, name):
+            touched[self.module].add(name)
+            return getattr(ast, name)
+
+    monkeypatch.setattr(parser, "ast", CountingAst("parser"))
+    monkeypatch.setattr(subheader, "ast", CountingAst("subheader"))
+    rc_file = Path(__file__).parent / "data" / "xonshrc.xsh"
+    tree = parse(rc_file.read_text())
+    # the generated rules only build the nodes of the source, and newer nodes are not probed for
+    assert touched["parser"] <= {type(node).__name__ for node in ast.walk(tree)}
+    assert not touched["subheader"] & set(subheader.VERSIONED_NODES)
+    if sys.version_info >= (3, 12):
+        parse("type X = int")
+        assert touched["subheader"] & set(subheader.VERSIONED_NODES) == {"TypeAlias"}
+
+
+class Source(str):
+    """A source that can be watched with a weak reference, unlike a str."""
+
+
+@pytest.mark.parametrize(
+    "call",
+    [
+        "parse",
+        "parse_with_warnings",
+        "parse_events",
+        "paren_map",
+        "detect_features",
+        "scan_module_interface",
+        "scan_functions",
+        "split_statements",
+        "parse_cached",
+        "parse_tokens_buffer",
+        "source_segment",
+    ],
+)
+def test_source_is_not_retained(call):
+    import peg_parser
+
+    calls = {
+        "parse_with_warnings": lambda source: peg_parser.Parser(warnings=True).parse(source),
+        "parse_events": lambda source: peg_parser.parse_events(source, lambda *args: None),
+        "parse_tokens_buffer": lambda source: peg_parser.parse_tokens_buffer(
+            peg_parser.tokenize_for_parse(source)
+        ),
+        "source_segment": lambda source: peg_parser.source_segment(source, peg_parser.parse(source).body[-1]),
+    }
+    text = (Path(__file__).parent / "data" / "xonshrc.xsh").read_text() + "__all__ = ['a']\nx = f'{a!r}'\n"
+    source = Source(text)
+    collected = []
+    ref = weakref.ref(source, collected.append)
+    result = (calls[call] if call in calls else getattr(peg_parser, call))(source)
+    del source
+    gc.collect()
+    assert collected == [ref]
+    if isinstance(result, ast.AST):
+        assert ast.dump(result) == ast.dump(peg_parser.parse(text))
+
+
+def test_retained_sources():
+    import peg_parser
+
+    source = Source("x = (\n    1\n)\n")
+    ref = weakref.ref(source)
+    buffer = peg_parser.tokenize_for_parse(source)
+    segment = peg_parser.source_segment(source, peg_parser.parse(source).body[0])
+    del source
+    gc.collect()
+    assert ref() is buffer.source
+    del buffer
+    gc.collect()
+    assert ref() is None
+    assert segment == "x = (\n    1\n)"
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "match v:\n    case x if a is not None:\n        pass",
+        "match v:\n    case x if a not in s:\n        pass",
+        "match v:\n    case [x, *_] if x is not y not in z:\n        pass",
+        "match v:\n    case x if (n := len(x)) > 2:\n        pass",
+        "match v:\n    case x if n := len(x):\n        pass",
+        "match v:\n    case x if a if b else c:\n        pass",
+        "f = lambda k=x not in s: k",
+        "f = lambda k=x is not None, j=(n := 1): k",
+        "f = lambda k=a if b else c, *, j=a not in b not in c: k",
+    ],
+)
+def test_two_token_operators_in_guards_and_defaults(check_ast_attributes, source):
+    check_ast_attributes(source)
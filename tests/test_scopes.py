@@ -0,0 +1,233 @@
+"""Test the checks that need scope information against CPython's compiler."""
+
+import sys
+
+import pytest
+
+requires_py311 = pytest.mark.skipif(
+    sys.version_info < (3, 11), reason="CPython 3.10 reports these differently"
+)
+
+
+def compile_result(parse, source):
+    try:
+        parse(source)
+    except SyntaxError as e:
+        return e.msg, e.lineno, e.offset, e.end_lineno, e.end_offset
+    return None
+
+
+@requires_py311
+@pytest.mark.parametrize(
+    "source",
+    [
+        # adapted from CPython's test_syntax and test_coroutines
+        "await x",
+        "def f():\n    await x",
+        "async def f():\n    await x",
+        "def f():\n    x = await y",
+        "class A:\n    x = await y",
+        "async def f():\n    class A:\n        await x",
+        "async def f():\n    lambda: await x",
+        "def f():\n    async def g():\n        await x",
+        "async def f():\n    def g():\n        await x",
+        # defaults and decorators belong to the enclosing scope
+        "async def f(x=await g()): pass",
+        "async def f():\n    def g(x=await y): pass",
+        "def f():\n    async def g(x=await y): pass",
+        "async def f():\n    lambda x=await y: x",
+        "@await d\ndef g(): pass",
+        "async def f():\n    @await d\n    def g(): pass",
+        "async def f():\n    class A(await b): pass",
+        # annotations
+        "def g(x: await y): pass",
+        "async def f() -> await x: pass",
+        "async def f():\n    def g(x: await y): pass",
+        "x: await y",
+        "class A:\n    x: await y",
+        "def f():\n    x: await y",
+        "def f():\n    x: int = await y",
+        "async def f():\n    class A:\n        x: await y",
+        "from __future__ import annotations\ndef g(x: await y): pass",
+        "from __future__ import annotations\nasync def f():\n    def g(x: await y) -> await z: pass",
+        '"""doc"""\nfrom __future__ import annotations\ndef f():\n    x: await y',
+        # comprehensions
+        "[await x for x in y]",
+        "(await x for x in y)",
+        "[x async for x in y]",
+        "(x async for x in y)",
+        "def f():\n    [await x for x in y]",
+        "def f():\n    (await x for x in y)",
+        "async def f():\n    [await x for x in y]",
+        "def f():\n    [x for x in await y]",
+        "async def f():\n    [x for x in await y]",
+        "def f():\n    [x for y in z for x in await y]",
+        "def f():\n    [x for x in y if await z]",
+        "def f():\n    {await k: v for k, v in y}",
+        "def f():\n    [[await x for x in z] for y in w]",
+        "def f():\n    [[x for x in await y] for z in w]",
+        "def f():\n    [(await x for x in y) for z in w]",
+        "def f():\n    [lambda: await x for x in y]",
+        "def f():\n    [(lambda: await x, await y) for x in y]",
+        "def f():\n    (x for y in z if [await a for a in b])",
+        "async def f():\n    def g():\n        [[await x for x in z] for y in w]",
+        "async def f():\n    class A:\n        [await x for x in y]",
+        "async def f():\n    class A:\n        (await x for x in y)",
+        "async def f():\n    def g(x=[await z for z in w]): pass",
+    ],
+)
+def test_await_placement_matches_cpython(python_parse_str, source):
+    expected = compile_result(lambda src: compile(src, "<unknown>", "exec"), source)
+    assert compile_result(lambda src: python_parse_str(src, "exec", check_scopes=True), source) == expected
+
+
+def test_scopes_are_not_checked_by_default(python_parse_str):
+    assert python_parse_str("await x", "exec")
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        # adapted from CPython's test_syntax
+        "x = 1\nglobal x",
+        "print(x)\nglobal x",
+        "x: int\nglobal x",
+        "def f(x):\n    global x",
+        "def f(*, x):\n    global x",
+        "lambda: [x for x in y]\nglobal x",
+        "def f():\n    global x\n    x: int = 3",
+        "class A:\n    global x\n    x: int",
+        "def f(x):\n    nonlocal x",
+        "def f():\n    x = 1\n    def g():\n        x = 2\n        nonlocal x",
+        "def f():\n    x = 1\n    def g():\n        x\n        nonlocal x",
+        "def f():\n    x: int\n    nonlocal x",
+        "def f():\n    x = 1\n    def g():\n        nonlocal x\n        x: int",
+        "def f():\n    global x\n    nonlocal x",
+        "def f():\n    nonlocal x\n    global x",
+        "nonlocal x",
+        # redeclarations and names bound after the declaration are fine
+        "global x\nglobal x\nx = 1",
+        "def f():\n    global x, x",
+        "def f():\n    global x\n    x += 1",
+        "global x\nx: int",
+        "def f():\n    global x\n    (x): int",
+        "import x\nglobal x",
+        # the first binding reported is a parameter, then a use, then an annotation
+        "def f(x):\n    global x\n    x = 1",
+        "print(x)\nx = 1\nglobal x",
+        "x: int\nx = 1\nglobal x",
+        "x = 1\ny: int\nglobal x, y",
+        # the statements binding a name
+        "for x in y: pass\nglobal x",
+        "del x\nglobal x",
+        "x += 1\nglobal x",
+        "(x := 1)\nglobal x",
+        "def x(): pass\nglobal x",
+        "try: pass\nexcept E as x: pass\nglobal x",
+        "with a as x: pass\nglobal x",
+        "match a:\n    case {**x}: pass\nglobal x",
+        "match a:\n    case [*x]: pass\nglobal x",
+        "x = 1\nif y:\n    global x",
+        # the names used
+        "x.a = 1\nglobal x",
+        "f'{x}'\nglobal x",
+        "[x for y in x]\nglobal x",
+        "[y for y in z if x]\nglobal x",
+        "lambda: x\nglobal x",
+        "def f(x=y):\n    pass\nglobal y",
+        "@y\ndef f(): pass\nglobal y",
+        "class A(y): pass\nglobal y",
+        "def f(a: y):\n    pass\nglobal y",
+        "def f():\n    x: y\n    global y",
+        "from __future__ import annotations\ndef f(a: y):\n    pass\nglobal y",
+        # scopes
+        "def f():\n    [x for x in y]\n    global x",
+        "def f():\n    [(x := 1) for y in z]\n    global x",
+        "def f():\n    def g():\n        global x\n    x = 1\n    global x",
+        # reported before the errors of the compiler
+        "await x\nx = 1\nglobal x",
+    ],
+)
+def test_declarations_match_cpython(python_parse_str, source):
+    expected = compile_result(lambda src: compile(src, "<unknown>", "exec"), source)
+    assert compile_result(lambda src: python_parse_str(src, "exec", check_scopes=True), source) == expected
+
+
+@requires_py311
+@pytest.mark.parametrize(
+    "source",
+    [
+        "*x",
+        "x = *y",
+        "a = b = *c",
+        "x += *y",
+        "x: int = *y",
+        "def f():\n    return *x",
+        "def f():\n    yield *x",
+        "for i in *x: pass",
+        "with *x as y: pass",
+        "f(x=*y)",
+        "[*x for y in z]",
+        "x = f(*a)[*b, *c]",
+        "print(f'{*x, y}')",
+        # unpacking where it is valid
+        "*x, = y",
+        "x = *y,",
+        "x = [*a, *b], (*c,), {*d}",
+        "def f():\n    return *x, 1",
+        "def f():\n    yield *x,",
+        "for i in *x, y: pass",
+        "f(*a, *b, c=1, **d)",
+        "class C(*bases, metaclass=M): pass",
+        "def f(*args: *Ts): pass",
+        "x = a[*b]",
+        # reported after the awaits and declarations found before
+        "await x\nx = *y",
+        "x = *y\nawait x",
+    ],
+)
+def test_starred_values_match_cpython(python_parse_str, source):
+    expected = compile_result(lambda src: compile(src, "<unknown>", "exec"), source)
+    assert compile_result(lambda src: python_parse_str(src, "exec", check_scopes=True), source) == expected
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        # adapted from CPython's test_scope and test_syntax
+        "def f():\n    nonlocal x",
+        "x = 1\ndef f():\n    nonlocal x",
+        "def f():\n    x = 1\n    def g():\n        nonlocal x",
+        "def f():\n    def g():\n        nonlocal x\n    x = 1",
+        "def f(x):\n    def g():\n        def h():\n            nonlocal x",
+        "def f(x):\n    def g():\n        nonlocal x\n        def h():\n            nonlocal x",
+        "def f():\n    nonlocal a, b\n    nonlocal c",
+        "def f():\n    def g():\n        nonlocal y\n    nonlocal x",
+        "def f():\n    import x.y\n    from z import w as v\n    def g():\n        nonlocal x, v",
+        "def f():\n    x: int\n    def g():\n        nonlocal x",
+        "def f():\n    for x in y: pass\n    def g():\n        nonlocal x",
+        "def f():\n    del x\n    def g():\n        nonlocal x",
+        # classes are skipped, but for __class__
+        "def f():\n    x = 1\n    class C:\n        def g(self):\n            nonlocal x",
+        "def f():\n    class C:\n        x = 1\n        def g(self):\n            nonlocal x",
+        "class C:\n    def g(self):\n        nonlocal __class__",
+        "def f():\n    x = 1\n    class C:\n        global x\n        def g(self):\n            nonlocal x",
+        # the names declared global in a function are not bound for nested ones
+        "def f():\n    global x\n    def g():\n        nonlocal x",
+        "def f():\n    global x\n    x = 1\n    def g():\n        nonlocal x",
+        "def f():\n    global x\n    [x for x in y]\n    [lambda: x for z in w]",
+        # comprehensions and lambdas are function scopes
+        "def f():\n    [x for x in y]\n    def g():\n        nonlocal x",
+        "def f():\n    (y := 1)\n    [(x := 1) for z in w]\n    def g():\n        nonlocal x, y",
+        "def f(x):\n    lambda: [x for y in z]\n    def g():\n        nonlocal x",
+        "def f():\n    lambda x: x\n    def g():\n        nonlocal x",
+        "def f():\n    [lambda x=y: x for y in z]\n    def g():\n        nonlocal y",
+        # reported after the errors found while reading the tree
+        "def f():\n    nonlocal x\nnonlocal y",
+        "def f():\n    nonlocal x\n    x = 1\n    global x",
+        "def f():\n    nonlocal x\n    await y",
+    ],
+)
+def test_nonlocal_bindings_match_cpython(python_parse_str, source):
+    expected = compile_result(lambda src: compile(src, "<unknown>", "exec"), source)
+    assert compile_result(lambda src: python_parse_str(src, "exec", check_scopes=True), source) == expected
@@ -0,0 +1,61 @@
+"""Snapshots of the tokens and trees of xonsh syntax, which CPython cannot be compared with.
+
+Each ``tests/snapshots/<name>.xsh`` fixture has its tokens pinned in ``<name>.tokens`` and its tree in
+``<name>.ast``. A changed snapshot fails its test and is written next to the approved one as
+``<name>.tokens.new`` or ``<name>.ast.new``, to be accepted or rejected with
+``python tasks/review_snapshots.py``. Missing snapshots are written the same way, and running the
+tests with ``PEG_PARSER_UPDATE_SNAPSHOTS=1`` approves all of them at once.
+"""
+
+import ast
+import os
+from pathlib import Path
+
+import pytest
+
+from peg_parser import dump_tokens, parse
+
+SNAPSHOTS = Path(__file__).parent / "snapshots"
+FIXTURES = sorted(SNAPSHOTS.glob("*.xsh"))
+
+
+def dump_tree(node: object, level: int = 0) -> str:
+    """An indented dump of a tree, leaving out empty fields so that it is the same for all Pythons."""
+    if isinstance(node, list):
+        items = [f"\n{'  ' * (level + 1)}{dump_tree(item, level + 1)}" for item in node]
+        return f"[{','.join(items)}]"
+    if not isinstance(node, ast.AST):
+        return repr(node)
+    fields = [
+        f"\n{'  ' * (level + 1)}{name}={dump_tree(value, level + 1)}"
+        for name, value in ast.iter_fields(node)
+        if value is not None and value != []
+    ]
+    if isinstance(node, ast.expr_context) or not fields:
+        return f"{type(node).__name__}()"
+    return f"{type(node).__name__}({','.join(fields)})"
+
+
+def assert_snapshot(path: Path, actual: str) -> None:
+    new = path.with_name(f"{path.name}.new")
+    if os.environ.get("PEG_PARSER_UPDATE_SNAPSHOTS"):
+        path.write_text(actual)
+    elif not path.exists() or path.read_text() != actual:
+        new.write_text(actual)
+        pytest.fail(f"{path.name} is not approved, see {new.name} and tasks/review_snapshots.py")
+    new.unlink(missing_ok=True)
+
+
+def test_fixtures():
+    assert len(FIXTURES) >= 15
+
+
+@pytest.mark.parametrize("fixture", FIXTURES, ids=lambda p: p.stem)
+def test_token_snapshots(fixture):
+    assert_snapshot(fixture.with_suffix(".tokens"), dump_tokens(fixture.read_text()))
+
+
+@pytest.mark.parametrize("fixture", FIXTURES, ids=lambda p: p.stem)
+def test_tree_snapshots(fixture):
+    tree = parse(fixture.read_text(), filename=fixture.name)
+    assert_snapshot(fixture.with_suffix(".ast"), dump_tree(tree) + "\n")
@@ -0,0 +1,122 @@
+"""Tests splitting sources into top level statements without parsing them."""
+
+import pytest
+
+from peg_parser import block_complete, split_statements
+
+PASTE = """\
+x = 1
+
+# a comment between statements
+def f(a,
+      b):
+    return a  # the first
+
+    # within the body
+    pass
+
+for i in x:
+    if i:
+        y
+    else:
+        z
+else:
+    w
+@d
+@e(1)
+class C: pass
+ls -l | grep py
+"""
+
+
+def statements(source: str) -> list[tuple[str, bool]]:
+    return [(source[start:end], complete) for start, end, complete in split_statements(source)]
+
+
+def test_split_paste():
+    assert statements(PASTE) == [
+        ("x = 1\n", True),
+        ("def f(a,\n      b):\n    return a  # the first\n\n    # within the body\n    pass\n", True),
+        ("for i in x:\n    if i:\n        y\n    else:\n        z\nelse:\n    w\n", True),
+        ("@d\n@e(1)\nclass C: pass\n", True),
+        ("ls -l | grep py\n", True),
+    ]
+
+
+@pytest.mark.parametrize(
+    "source, expected",
+    [
+        (
+            "try:\n  a\nexcept E:\n  b\nfinally:\n  c\nx",
+            [("try:\n  a\nexcept E:\n  b\nfinally:\n  c\n", True), ("x", True)],
+        ),
+        ("x = 1; y = 2\n\n\nz = $(ls)\n", [("x = 1; y = 2\n", True), ("z = $(ls)\n", True)]),
+        ("", []),
+        ("# only a comment\n\n", []),
+    ],
+)
+def test_split_statements(source, expected):
+    assert statements(source) == expected
+
+
+@pytest.mark.parametrize(
+    "source, incomplete",
+    [
+        ("x = 1\nfor i in x:\n", "for i in x:\n"),
+        ("x = 1\nf(a,\n  b\n", "f(a,\n  b\n"),
+        ("x = 1\ns = '''abc\n", "s = '''abc\n"),
+        ("x = 1\nif x:\n    y = [\n", "if x:\n    y = [\n"),
+        ("x = 1\n@d\n", "@d\n"),
+        ("x = 1\ny = 2 + \\\n", "y = 2 + \\\n"),
+        ("x = 1\ns = 'abc\n", "s = 'abc\n"),
+        ("x = 1\ns = f'{a} abc\nz = 2\n", "s = f'{a} abc\nz = 2\n"),
+    ],
+)
+def test_incomplete_last_statement(source, incomplete):
+    assert statements(source) == [("x = 1\n", True), (incomplete, False)]
+
+
+def test_offsets_count_characters():
+    source = 'x = "é"\ny = 1\n'
+    assert split_statements(source) == [(0, 8, True), (8, 14, True)]
+    assert statements(source) == [('x = "é"\n', True), ("y = 1\n", True)]
+
+
+@pytest.mark.parametrize(
+    "source, expected",
+    [
+        ("", True),
+        ("x = 1\n", True),
+        ("if x:", False),
+        ("if x:  # a comment\n\n", False),
+        ("if x: y", True),
+        ("for i in x:\n    y\n", False),
+        ("for i in x:\n    y\n\n", True),
+        ("for i in x:\n    y\nz\n", True),
+        ("if x:\n\n", False),
+        ("if x:\n  pass\n\n", True),
+        ("def f():\n    if x:\n", False),
+        ("def f():\n    if x:\n\n", False),
+        ("def f():\n    if x:\n        y\n    z\n", False),
+        ("def f():\n    if x:\n        y\n\n", True),
+        ("def f():\n    if x:\n        y\n    z\n\n", True),
+        ("def f():\n    if x:\n        y\n    else:\n\n", False),
+        ("def f():\n    if x:\n        y\nz", True),
+        ("class C:\n    def f(self): pass\n\nx = C()\n", True),
+        ("try:\n    a\nexcept E:\n", False),
+        ("@d\n", False),
+        ("@d\nclass C: pass\n", True),
+        ("f(a,\n  b", False),
+        ("x = {'a':", False),
+        ("s = '''abc\n", False),
+        ("s = f'{a", False),
+        ("y = 2 + \\\n", False),
+        ("x = lambda: 1\nd[1:]\n", True),
+        ("ls -l | grep py\n", True),
+        ("x = 'abc", None),
+        ("if x:\n    a\n  b\n", None),
+        ("if x:\n\ta\n        b\n", None),
+    ],
+)
+def test_block_complete(source, expected):
+    assert block_complete(source) is expected
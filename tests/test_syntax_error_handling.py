@@ -1,6 +1,8 @@
 """Test syntax errors for cases where the parser can generate helpful messages."""
 
+import ast
 import sys
+import traceback
 
 import pytest
 
@@ -15,7 +17,11 @@ def parse_invalid_syntax(
     start,
     end,
     min_python_version=(3, 10),
+    python_error=None,
 ) -> None:
+    # CPython's (message, start, end) when it differs from ours on this version
+    py_message, py_start, py_end = python_error or (message, start, end)
+
     # Check we obtain the expected error from Python
     try:
         exec(source, {}, {})
@@ -46,7 +52,7 @@ def parse_invalid_syntax(
     if sys.version_info >= min_python_version:
         # This fails for Python < 3.10.5 but keeping the fix for a patch version is not
         # worth it
-        assert message in py_exc.args[0]
+        assert py_message in py_exc.args[0]
 
     print(str(e.exconly()))
     assert message in str(e.exconly())
@@ -55,26 +61,26 @@ def parse_invalid_syntax(
         return
 
     # Check start/end line/column on Python 3.10
-    for parser, exc in ([("Python", py_exc)] if sys.version_info >= min_python_version else []) + [
-        ("pegen", e.value)
-    ]:
+    for parser, exc, exp_start, exp_end in (
+        [("Python", py_exc, py_start, py_end)] if sys.version_info >= min_python_version else []
+    ) + [("pegen", e.value, start, end)]:
         if (
-            exc.lineno != start[0]
-            or exc.offset != start[1]
+            exc.lineno != exp_start[0]
+            or exc.offset != exp_start[1]
             # Do not check end for indentation errors
             or (
                 sys.version_info >= (3, 10)
                 and not isinstance(e, IndentationError)
-                and exc.end_lineno != end[0]
+                and exc.end_lineno != exp_end[0]
             )
             or (
                 sys.version_info >= (3, 10)
                 and not isinstance(e, IndentationError)
-                and (end[1] is not None and exc.end_offset != end[1])
+                and (exp_end[1] is not None and exc.end_offset != exp_end[1])
             )
         ):
             raise ValueError(
-                f"Expected locations of {start} and {end}, but got "
+                f"Expected locations of {exp_start} and {exp_end}, but got "
                 f"{(exc.lineno, exc.offset)} and {(exc.end_lineno, exc.end_offset)} "
                 f"from {parser}"
             )
@@ -92,6 +98,25 @@ def parse_invalid_syntax(
         ("exec 1", "Missing parentheses in call to 'exec'", (1, 1), (1, 7)),
         ("a if b", "expected 'else' after 'if' expression", (1, 1), (1, 7)),
         ("c = a if b:", "invalid syntax", (1, 11), (1, 12)),
+        ("x = 1 if cond", "expected 'else' after 'if' expression", (1, 5), (1, 14)),
+        ("def f():\n    return 1 if c", "expected 'else' after 'if' expression", (2, 12), (2, 18)),
+        ("f(a, 1 if c, b)", "expected 'else' after 'if' expression", (1, 6), (1, 12)),
+        ("f(a, key=1 if c)", "expected 'else' after 'if' expression", (1, 10), (1, 16)),
+        ("a.1", "invalid syntax", (1, 2), (1, 4)),
+        ("obj.123", "invalid syntax", (1, 4), (1, 8)),
+        ("x = a.b.1", "invalid syntax", (1, 8), (1, 10)),
+        ("[a.1]", "invalid syntax. Perhaps you forgot a comma?", (1, 2), (1, 5)),
+        # the end of the NEWLINE token at the end of the source differs before Python 3.12
+        ("a.", "invalid syntax", (1, 3), (1, None)),
+        ("a.b.\n", "invalid syntax", (1, 5), (1, None)),
+        ("f(a.)", "invalid syntax", (1, 5), (1, 6)),
+        pytest.param(
+            "f'{1 if c}'",
+            "expected 'else' after 'if' expression",
+            (1, 4),
+            (1, 10),
+            marks=pytest.mark.skipif(sys.version_info < (3, 12), reason="Requires Python 3.12+"),
+        ),
     ],
 )
 def test_invalid_expression(python_parse_file, python_parse_str, tmp_path, source, message, start, end):
@@ -100,6 +125,48 @@ def test_invalid_expression(python_parse_file, python_parse_str, tmp_path, sourc
     )
 
 
+@pytest.mark.parametrize(
+    "source, start, end",
+    [
+        ("x = import os", (1, 5), (1, 11)),
+        ("y = (pass)", (1, 6), (1, 10)),
+        ("z = 1 + def f(): ...", (1, 9), (1, 12)),
+        ("f(class)", (1, 3), (1, 8)),
+        ("x = [1, return]", (1, 9), (1, 15)),
+        ("a = b if while else c", (1, 10), (1, 15)),
+        ("x = lambda: pass", (1, 13), (1, 17)),
+        ("x = {global: 1}", (1, 6), (1, 12)),
+    ],
+)
+def test_misplaced_keyword(python_parse_file, python_parse_str, tmp_path, source, start, end):
+    parse_invalid_syntax(
+        python_parse_file, python_parse_str, tmp_path, source, SyntaxError, "invalid syntax", start, end
+    )
+
+
+@pytest.mark.parametrize(
+    "source, operator, start",
+    [
+        ("x = not", "not", (1, 8)),
+        ("x = -", "-", (1, 6)),
+        ("x = +\n", "+", (1, 6)),
+        ("x = ~ ", "~", (1, 7)),
+        ("f(not)", "not", (1, 6)),
+        ("x = 1 + - * 2", "-", (1, 11)),
+        ("x = not not", "not", (1, 12)),
+        ("if not:\n    pass", "not", (1, 7)),
+    ],
+)
+def test_missing_unary_operand(python_parse_str, source, operator, start):
+    # CPython reports "invalid syntax" at the same token
+    with pytest.raises(SyntaxError) as py_e:
+        compile(source, "<unknown>", "exec")
+    with pytest.raises(SyntaxError) as e:
+        python_parse_str(source, "exec")
+    assert e.value.msg == f"expected expression after '{operator}'"
+    assert (e.value.lineno, e.value.offset) == (py_e.value.lineno, py_e.value.offset) == start
+
+
 # Those tests are mostly there to get coverage on exiting rules without matching
 @pytest.mark.parametrize(
     "source, message, start, end",
@@ -122,17 +189,38 @@ def test_invalid_statements(python_parse_file, python_parse_str, tmp_path, sourc
     )
 
 
+# CPython 3.13 reports bare stars as invalid star expressions and starts the
+# unpacking error at the comma.
+PY313_CALL_ERRORS = {
+    "f(**a, *b)": ("iterable argument unpacking follows keyword argument unpacking", (1, 6), (1, 10)),
+    "f(x=1, *)": ("Invalid star expression", (1, 9), (1, 10)),
+    "f(**k, x=1, *a)": ("iterable argument unpacking follows keyword argument unpacking", (1, 11), (1, 15)),
+    "f(a, **k, *b)": ("iterable argument unpacking follows keyword argument unpacking", (1, 9), (1, 13)),
+    "f(*)": ("Invalid star expression", (1, 4), (1, 5)),
+    "f(a, *)": ("Invalid star expression", (1, 7), (1, 8)),
+    "f(* *a)": ("Invalid star expression", (1, 5), (1, 6)),
+}
+
+
 @pytest.mark.parametrize(
     "source, message, start, end",
     [
         # Invalid arguments rules
-        pytest.param(
-            "f(**a, *b)",
+        ("f(**a, *b)", "iterable argument unpacking follows keyword argument unpacking", (1, 8), (1, 9)),
+        ("f(x=1, *)", "iterable argument unpacking follows keyword argument unpacking", (1, 8), (1, 9)),
+        (
+            "f(**k, x=1, *a)",
             "iterable argument unpacking follows keyword argument unpacking",
-            (1, 3),
-            (1, 6),
-            marks=pytest.mark.xfail,
+            (1, 13),
+            (1, 14),
         ),
+        ("f(a, **k, *b)", "iterable argument unpacking follows keyword argument unpacking", (1, 11), (1, 12)),
+        # a bare star is reported at the token after it
+        ("f(*)", "invalid syntax", (1, 4), (1, 5)),
+        ("f(**)", "invalid syntax", (1, 5), (1, 6)),
+        ("f(a, *)", "invalid syntax", (1, 7), (1, 8)),
+        ("f(* *a)", "invalid syntax", (1, 5), (1, 6)),
+        ("f(** *a)", "invalid syntax", (1, 6), (1, 7)),
         # NOTE CPython bug, should report 15 as expected (we use None to omit the check)
         ("f(a for a in b, c)", "Generator expression must be parenthesized", (1, 3), (1, None)),
         # NOTE CPython bug, should report 20 as expected (we use None to omit the check)
@@ -192,7 +280,9 @@ def test_invalid_call_arguments(python_parse_file, python_parse_str, tmp_path, s
         message,
         start,
         end,
-        (3, 11) if "cannot assign" in message else (3, 10),
+        # CPython 3.10 reports the misplaced stars from the opening parenthesis
+        (3, 11) if "cannot assign" in message or source in PY313_CALL_ERRORS else (3, 10),
+        PY313_CALL_ERRORS.get(source) if sys.version_info >= (3, 13) else None,
     )
 
 
@@ -643,6 +733,7 @@ def test_invalid_parameters(
         ("def f(a, *,):\n\tpass", (1, 10), (1, 11)),
         ("def f(a, *, **):\n\tpass", (1, 10), (1, 11)),
         ("lambda a, *: a", (1, 12), (1, 13)),
+        ("lambda *: x", (1, 9), (1, 10)),
         ("lambda a, *, **:a", (1, 14), (1, 16)),
     ],
 )
@@ -659,6 +750,35 @@ def test_invalid_star_etc(python_parse_file, python_parse_str, tmp_path, source,
     )
 
 
+@pytest.mark.parametrize(
+    "source, start, end",
+    [
+        ("def f(/, a):\n\tpass", (1, 7), (1, 8)),
+        ("lambda /, a: x", (1, 8), (1, 9)),
+    ],
+)
+def test_invalid_leading_slash(python_parse_file, python_parse_str, tmp_path, source, start, end):
+    parse_invalid_syntax(
+        python_parse_file,
+        python_parse_str,
+        tmp_path,
+        source,
+        SyntaxError,
+        "at least one argument must precede /",
+        start,
+        end,
+        (3, 11),
+    )
+
+
+@pytest.mark.parametrize(
+    "source",
+    ["def f(a, *, b):\n\tpass", "def f(a, /):\n\tpass", "lambda a, *, b: b", "lambda a, /: a"],
+)
+def test_star_and_slash_ok(python_parse_str, source):
+    assert ast.dump(python_parse_str(source, mode="exec")) == ast.dump(ast.parse(source))
+
+
 @pytest.mark.parametrize(
     "source, message, start, end",
     [
@@ -916,6 +1036,22 @@ def test_invalid_try_stmt(
             (3, 9),
             marks=pytest.mark.skipif(sys.version_info < (3, 11), reason="Syntax unsupported before 3.11+"),
         ),
+        pytest.param(
+            "try:\n\tpass\nexcept* ValueError:\n\tpass\nexcept:\n\tpass",
+            SyntaxError,
+            "cannot have both 'except' and 'except*' on the same 'try'",
+            (5, 1),
+            (5, 7),
+            marks=pytest.mark.skipif(sys.version_info < (3, 11), reason="Syntax unsupported before 3.11+"),
+        ),
+        pytest.param(
+            "try:\n\tpass\nexcept ValueError as e:\n\tpass\nexcept* TypeError:\n\tpass",
+            SyntaxError,
+            "cannot have both 'except' and 'except*' on the same 'try'",
+            (5, 1),
+            (5, 8),
+            marks=pytest.mark.skipif(sys.version_info < (3, 11), reason="Syntax unsupported before 3.11+"),
+        ),
     ],
 )
 def test_invalid_except_stmt(
@@ -926,6 +1062,51 @@ def test_invalid_except_stmt(
     )
 
 
+@pytest.mark.parametrize(
+    "source, start",
+    [
+        ("x = 1\n  y = 2", (2, 2)),
+        ("if x: pass\n  else: pass", (2, 2)),
+        ("if x: a; b\n    c\n", (2, 4)),
+        ("def f(): return 1; x\n  y\n", (2, 2)),
+        ("if x:\n\ta\n\t\tb\n", (3, 2)),
+    ],
+)
+def test_unexpected_indent(python_parse_file, python_parse_str, tmp_path, source, start):
+    parse_invalid_syntax(
+        python_parse_file,
+        python_parse_str,
+        tmp_path,
+        source,
+        IndentationError,
+        "unexpected indent",
+        start,
+        (start[0], -1),
+    )
+
+
+@pytest.mark.parametrize(
+    "source, start",
+    [
+        ("if x:\n    a\n  b\n", (3, 4)),
+        ("if x:\n    if y:\n        a\n      b\n", (4, 8)),
+        ("if x:\n    a = (\n1,\n)\n  b  # comment\n", (5, 15)),
+        ("if x:\n    a\n  b", (3, 4)),
+    ],
+)
+def test_unindent_mismatch(python_parse_file, python_parse_str, tmp_path, source, start):
+    parse_invalid_syntax(
+        python_parse_file,
+        python_parse_str,
+        tmp_path,
+        source,
+        IndentationError,
+        "unindent does not match any outer indentation level",
+        start,
+        (start[0], -1),
+    )
+
+
 @pytest.mark.parametrize(
     "source, exception, message, start, stop",
     [
@@ -999,6 +1180,18 @@ def test_invalid_match_stmt(
             (3, 2),
             (3, 6),
         ),
+        # a malformed guard is reported within it, not as a missing colon
+        ("match a:\n\tcase x if a is not:\n\t\tpass", SyntaxError, "invalid syntax", (2, 20), (2, 21)),
+        ("match a:\n\tcase x if a not:\n\t\tpass", SyntaxError, "invalid syntax", (2, 17), (2, 18)),
+        ("match a:\n\tcase x if:\n\t\tpass", SyntaxError, "invalid syntax", (2, 11), (2, 12)),
+        ("match a:\n\tcase x if a b:\n\t\tpass", SyntaxError, "invalid syntax", (2, 14), (2, 15)),
+        (
+            "match a:\n\tcase x if a is not None\n\t\tpass",
+            SyntaxError,
+            "expected ':'",
+            (2, 25),
+            (2, 26) if sys.version_info >= (3, 12) else (2, 25),
+        ),
     ],
 )
 def test_invalid_case_stmt(
@@ -1326,6 +1519,20 @@ def test_invalid_def_stmt(
             (2, 1),
             (2, 5),
         ),
+        (
+            "class C():\npass",
+            IndentationError,
+            "expected an indented block after class definition on line 1",
+            (2, 1),
+            (2, 5),
+        ),
+        (
+            "class C():",
+            IndentationError,
+            "expected an indented block after class definition on line 1",
+            (1, 11),
+            (1, -1),
+        ),
     ],
 )
 def test_invalid_class_stmt(
@@ -1428,3 +1635,376 @@ def test_syntax_error_in_type_params(
     parse_invalid_syntax(
         python_parse_file, python_parse_str, tmp_path, source, exception, message, start, end
     )
+
+
+@pytest.mark.parametrize(
+    "source, start",
+    [
+        ("def f():\n    a = = 1\n", (2, 9)),
+        ("@d\nasync def f():\n    pass\n    a = = 1\n", (4, 9)),
+        ("class A:\n    def f(self):\n        a = = 1\n", (3, 13)),
+        ("try:\n    a = = 1\nexcept E:\n    pass\n", (2, 9)),
+        ("try:\n    pass\nexcept E as e:\n    a = = 1\n", (4, 9)),
+        ("try:\n    pass\nfinally:\n    a = = 1\n", (4, 9)),
+        ("with a as b:\n    for x in y:\n        a = = 1\n", (3, 13)),
+        ("for x in y:\n    pass\nelse:\n    a = = 1\n", (4, 9)),
+        ("if x:\n    pass\nelif y:\n    while z:\n        a = = 1\n", (5, 13)),
+        ("match x:\n    case 1:\n        a = = 1\n", (3, 13)),
+        (
+            "while x:\n    with y:\n        try:\n            pass\n        except E:\n"
+            "            class A:\n                def f(self):\n                    a = = 1\n",
+            (8, 25),
+        ),
+    ],
+)
+def test_error_inside_compound_stmt_body(python_parse_file, python_parse_str, tmp_path, source, start):
+    parse_invalid_syntax(
+        python_parse_file,
+        python_parse_str,
+        tmp_path,
+        source,
+        SyntaxError,
+        "invalid syntax",
+        start,
+        (start[0], start[1] + 1),
+    )
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        pytest.param("x = 1\ny = = 1", id="no-trailing-newline"),
+        pytest.param("x = 1\r\ny = = 1\r\nz = 2\r\n", id="crlf"),
+    ],
+)
+def test_error_text_line_endings(python_parse_file, python_parse_str, tmp_path, source):
+    test_file = tmp_path / "test.py"
+    test_file.write_bytes(source.encode())
+    for parse in (lambda: python_parse_str(source, "exec"), lambda: python_parse_file(test_file)):
+        with pytest.raises(SyntaxError) as e:
+            parse()
+        assert (e.value.text, e.value.lineno, e.value.offset) == ("y = = 1\n", 2, 5)
+
+
+def test_error_text_long_line_is_truncated(python_parse_str):
+    line = "x = " + "a + " * 224 + "= 1 " + "#" * 96
+    assert len(line) == 1000 and line[900] == "="
+
+    with pytest.raises(SyntaxError) as e:
+        python_parse_str(line + "\n", "exec")
+    text, offset = e.value.text, e.value.offset
+    assert text == "..." + line[488:] + "\n"
+    assert (e.value.lineno, e.value.end_lineno) == (1, 1)
+    assert text[offset - 1] == "="
+    assert e.value.end_offset == offset + 1
+
+
+@pytest.mark.parametrize(
+    "source, message, start, end",
+    [
+        # examples from PEP 572
+        (
+            "[i := 0 for i, j in stuff]",
+            "assignment expression cannot rebind comprehension iteration variable 'i'",
+            (1, 2),
+            (1, 3),
+        ),
+        (
+            "[i for i, j in stuff if True or (j := 1)]",
+            "assignment expression cannot rebind comprehension iteration variable 'j'",
+            (1, 34),
+            (1, 35),
+        ),
+        (
+            "[[(j := j) for i in range(5)] for j in range(5)]",
+            "assignment expression cannot rebind comprehension iteration variable 'j'",
+            (1, 4),
+            (1, 5),
+        ),
+        (
+            "[i for i in range(5) if (j := 0) for k[j] in range(5)]",
+            "comprehension inner loop cannot rebind assignment expression target 'j'",
+            (1, 40),
+            (1, 41),
+        ),
+        (
+            "[i+1 for i in (i := stuff)]",
+            "assignment expression cannot be used in a comprehension iterable expression",
+            (1, 16),
+            (1, 26),
+        ),
+        (
+            "[i+1 for i in range(2) for j in (k := stuff)]",
+            "assignment expression cannot be used in a comprehension iterable expression",
+            (1, 34),
+            (1, 44),
+        ),
+        (
+            "[i+1 for i in [j for j in (k := stuff)]]",
+            "assignment expression cannot be used in a comprehension iterable expression",
+            (1, 28),
+            (1, 38),
+        ),
+        (
+            "[i+1 for i in (lambda: (j := stuff))()]",
+            "assignment expression cannot be used in a comprehension iterable expression",
+            (1, 25),
+            (1, 35),
+        ),
+        # other comprehension kinds
+        (
+            "{(k := 1): v for k, v in y}",
+            "assignment expression cannot rebind comprehension iteration variable 'k'",
+            (1, 3),
+            (1, 4),
+        ),
+        (
+            "f((y := 1) for y in z)",
+            "assignment expression cannot rebind comprehension iteration variable 'y'",
+            (1, 4),
+            (1, 5),
+        ),
+        (
+            "(x for x in y if [(x := 1) for z in w])",
+            "assignment expression cannot rebind comprehension iteration variable 'x'",
+            (1, 20),
+            (1, 21),
+        ),
+        ("[x for *a in y]", "starred assignment target must be in a list or tuple", (1, 8), (1, 10)),
+    ],
+)
+def test_comprehension_assignment_expression(
+    python_parse_file, python_parse_str, tmp_path, source, message, start, end
+):
+    parse_invalid_syntax(
+        python_parse_file, python_parse_str, tmp_path, source, SyntaxError, message, start, end
+    )
+
+
+@pytest.mark.parametrize(
+    "source",
+    [
+        "[(total := total + v) for v in values]",
+        "[lambda: (x := 1) for x in y]",
+        "[(y := 1) for x in (y for y in z)]",
+        "[1 for a in y if [(z := 1) for q in w] for z in v]",
+        "[(j := 0) for k.j in y]",
+        "[x for (*a, b) in y]",
+    ],
+)
+def test_comprehension_assignment_expression_ok(python_parse_str, source):
+    assert ast.dump(python_parse_str(source, "exec")) == ast.dump(ast.parse(source))
+
+
+@pytest.mark.parametrize(
+    "source, message, start, end",
+    [
+        (
+            "if x = 1:\n    pass\n",
+            "invalid syntax. Maybe you meant '==' or ':=' instead of '='?",
+            (1, 4),
+            (1, 9),
+        ),
+        (
+            "while x = next(it):\n    pass\n",
+            "invalid syntax. Maybe you meant '==' or ':=' instead of '='?",
+            (1, 7),
+            (1, 19),
+        ),
+        (
+            "if a:\n    pass\nelif x = 1:\n    pass\n",
+            "invalid syntax. Maybe you meant '==' or ':=' instead of '='?",
+            (3, 6),
+            (3, 11),
+        ),
+        (
+            "if x.y = 1:\n    pass\n",
+            "cannot assign to attribute here. Maybe you meant '==' instead of '='?",
+            (1, 4),
+            (1, 7),
+        ),
+        ("[x for x in y if x = 1]", "invalid syntax", (1, 20), (1, 21)),
+        ("f(x = = 1)", "invalid syntax", (1, 7), (1, 8)),
+        ("f(a.b = 1)", 'expression cannot contain assignment, perhaps you meant "=="?', (1, 3), (1, 8)),
+        # the suggestion is also given when the first attempt was an assignment target
+        ("x = (y = 1)", "invalid syntax. Maybe you meant '==' or ':=' instead of '='?", (1, 6), (1, 11)),
+        ("print((y = 1))", "invalid syntax. Maybe you meant '==' or ':=' instead of '='?", (1, 8), (1, 13)),
+        (
+            "z = (y.a = 1)",
+            "cannot assign to attribute here. Maybe you meant '==' instead of '='?",
+            (1, 6),
+            (1, 9),
+        ),
+    ],
+)
+def test_assignment_in_expression(
+    python_parse_file, python_parse_str, tmp_path, source, message, start, end
+):
+    parse_invalid_syntax(
+        python_parse_file, python_parse_str, tmp_path, source, SyntaxError, message, start, end
+    )
+
+
+@pytest.mark.parametrize(
+    "source, message, start, end",
+    [
+        ("*a = b", "starred assignment target must be in a list or tuple", (1, 1), (1, 3)),
+        ("a, *b, *c = x", "multiple starred expressions in assignment", (1, 1), (1, 10)),
+        ("**a, b = c", "invalid syntax", (1, 1), (1, 3)),
+        ("x = *a, *b = y", "multiple starred expressions in assignment", (1, 5), (1, 11)),
+        ("a, *[b, *c, *d] = x", "multiple starred expressions in assignment", (1, 5), (1, 16)),
+        ("for *a in x: pass", "starred assignment target must be in a list or tuple", (1, 5), (1, 7)),
+        ("for a, *b, *c in x: pass", "multiple starred expressions in assignment", (1, 5), (1, 14)),
+        ("for **a, b in x: pass", "invalid syntax", (1, 5), (1, 7)),
+        ("with x as *a: pass", "starred assignment target must be in a list or tuple", (1, 11), (1, 13)),
+        ("[x for a, (*b, *c) in y]", "multiple starred expressions in assignment", (1, 11), (1, 19)),
+        ("[*a, *b] = (y := x)", "multiple starred expressions in assignment", (1, 1), (1, 9)),
+        ("(x := *a)", "invalid syntax", (1, 7), (1, 8)),
+    ],
+)
+def test_starred_targets(python_parse_file, python_parse_str, tmp_path, source, message, start, end):
+    parse_invalid_syntax(
+        python_parse_file, python_parse_str, tmp_path, source, SyntaxError, message, start, end
+    )
+
+
+@pytest.mark.parametrize(
+    "source", ["*a, = x", "for *a, (b, *c) in x: pass", "[x for *a, b in y]", "f(*a, *b)"]
+)
+def test_starred_targets_ok(python_parse_str, source):
+    tree = python_parse_str(source, mode="exec")
+    assert ast.dump(tree) == ast.dump(ast.parse(source))
+    for starred in (node for node in ast.walk(tree) if isinstance(node, ast.Starred)):
+        assert type(starred.ctx) is type(starred.value.ctx)
+
+
+@pytest.mark.parametrize(
+    "source, start, end",
+    [
+        ('bf"a{x}"', (1, 3), (1, 9)),
+        ('x = fb"a"', (1, 7), (1, 10)),
+    ],
+)
+def test_bytes_fstring(python_parse_file, python_parse_str, tmp_path, source, start, end):
+    parse_invalid_syntax(
+        python_parse_file, python_parse_str, tmp_path, source, SyntaxError, "invalid syntax", start, end
+    )
+
+
+FSTRING_KIND = "f-string" if sys.version_info >= (3, 12) else "string"
+
+
+@pytest.mark.parametrize(
+    "source, message, start",
+    [
+        ("x = 'abc\ny = 2'\n", "unterminated string literal (detected at line 1)", (1, 5)),
+        ("x = 'abc", "unterminated string literal (detected at line 1)", (1, 5)),
+        ("x = 1\ny = 'abc\n", "unterminated string literal (detected at line 2)", (2, 5)),
+        ('x = "a\\\nb\nc"\n', "unterminated string literal (detected at line 2)", (1, 5)),
+        ('x = "a" "b\n', "unterminated string literal (detected at line 1)", (1, 9)),
+        ("x = f'{x}'  'ab", "unterminated string literal (detected at line 1)", (1, 13)),
+        ('x = f"abc\ny"\n', f"unterminated {FSTRING_KIND} literal (detected at line 1)", (1, 5)),
+        ('x = f"a{b}c\n"\n', f"unterminated {FSTRING_KIND} literal (detected at line 1)", (1, 5)),
+    ],
+)
+def test_unterminated_string(python_parse_file, python_parse_str, tmp_path, source, message, start):
+    parse_invalid_syntax(
+        python_parse_file, python_parse_str, tmp_path, source, SyntaxError, message, start, start
+    )
+
+
+def test_unterminated_string_filename():
+    from peg_parser import parse
+
+    with pytest.raises(SyntaxError, match="unterminated string literal") as e:
+        parse("x = 'abc\n", "script.py")
+    assert e.value.filename == "script.py"
+
+
+LEADING_ZEROS = (
+    "leading zeros in decimal integer literals are not permitted; use an 0o prefix for octal integers"
+)
+
+
+@pytest.mark.parametrize(
+    "source, message, start, end",
+    [
+        ("x = 0123", LEADING_ZEROS, (1, 5), (1, 6)),
+        ("x = 0_7", LEADING_ZEROS, (1, 5), (1, 7)),
+        ("x = 1__0", "invalid decimal literal", (1, 6), (1, 6)),
+        ("x = 1_", "invalid decimal literal", (1, 6), (1, 6)),
+        ("x = 1.5_", "invalid decimal literal", (1, 8), (1, 8)),
+        ("x = 1abc", "invalid decimal literal", (1, 5), (1, 5)),
+        ("x = 1e", "invalid decimal literal", (1, 5), (1, 5)),
+        ("x = 1.5e+", "invalid decimal literal", (1, 9), (1, 9)),
+        ("x = 1jk", "invalid imaginary literal", (1, 6), (1, 6)),
+        ("x = 0x", "invalid hexadecimal literal", (1, 6), (1, 6)),
+        ("x = 0xff_", "invalid hexadecimal literal", (1, 9), (1, 9)),
+        ("x = 0o78", "invalid digit '8' in octal literal", (1, 8), (1, 8)),
+        ("x = 0b102", "invalid digit '2' in binary literal", (1, 9), (1, 9)),
+        ("x = 0b1__0", "invalid binary literal", (1, 8), (1, 8)),
+        ("match x:\n    case -0123:\n        pass", LEADING_ZEROS, (2, 11), (2, 12)),
+    ],
+)
+def test_invalid_number_literals(python_parse_file, python_parse_str, tmp_path, source, message, start, end):
+    parse_invalid_syntax(
+        python_parse_file, python_parse_str, tmp_path, source, SyntaxError, message, start, end
+    )
+
+
+@pytest.mark.parametrize(
+    "source, exc_cls, message, start, end",
+    [
+        ("improt os\n", SyntaxError, "invalid syntax", (1, 8), (1, 10)),
+        ("f(x iff y)\n", SyntaxError, "invalid syntax. Perhaps you forgot a comma?", (1, 3), (1, 8)),
+        ("x = (1,\n2\n", SyntaxError, "'(' was never closed", (1, 5), (2, 2)),
+        ("x = [1,\n (2, 3\n", SyntaxError, "'(' was never closed", (2, 2), (2, 7)),
+        ("x = $(ls -l\n", SyntaxError, "'$(' was never closed", (1, 5), (1, 12)),
+        ("x = 1 \\\n", SyntaxError, "unexpected EOF while parsing", (1, 8), (1, -1)),
+        ("x = 1 === 2\n", SyntaxError, "invalid syntax", (1, 9), (1, 10)),
+        ("if x:\n  y\n   z\n", IndentationError, "unexpected indent", (3, 3), (3, -1)),
+        (
+            "def f() -> int:\n",
+            IndentationError,
+            "expected an indented block after function definition on line 1",
+            (1, 16),
+            (1, -1),
+        ),
+        (
+            "if x:\n    if y:  # c\n\n",
+            IndentationError,
+            "expected an indented block after 'if' statement on line 2",
+            (3, 1),
+            (3, -1),
+        ),
+    ],
+)
+def test_error_end_positions(source, exc_cls, message, start, end):
+    from peg_parser import parse
+
+    with pytest.raises(exc_cls) as e:
+        parse(source, "script.py")
+    assert type(e.value) is exc_cls
+    assert (e.value.msg, e.value.filename) == (message, "script.py")
+    assert (e.value.lineno, e.value.offset, e.value.end_lineno, e.value.end_offset) == (*start, *end)
+
+
+@pytest.mark.parametrize(
+    "source, underline",
+    [
+        ("if x:\n    x = 1 +* 2\n", "           ^"),
+        ("improt os\n", "           ^^"),
+        ("f(x iff y)\n", "      ^^^^^"),
+        ("x = (1,\n2\n", "        ^"),
+        ("x = 1\n  y\n", "    ^"),
+    ],
+)
+def test_format_error(source, underline):
+    from peg_parser import format_error, parse
+
+    with pytest.raises(SyntaxError) as e:
+        parse(source)
+    lines = format_error(e.value).splitlines()
+    assert lines[2] == underline
+    expected = "".join(traceback.format_exception_only(e.value)).splitlines()
+    assert lines[:2] + lines[3:] == expected[:2] + expected[-1:]
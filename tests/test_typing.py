@@ -0,0 +1,27 @@
+"""Tests that the package is ready for type checkers, which read the annotations of its modules."""
+
+import inspect
+from pathlib import Path
+
+import pytest
+
+import peg_parser
+from peg_parser import tokenize
+
+
+def test_typed_marker():
+    assert (Path(peg_parser.__file__).parent / "py.typed").is_file()
+
+
+def public_functions():
+    for module in (peg_parser, tokenize):
+        for name, value in vars(module).items():
+            if inspect.isfunction(value) and not name.startswith("_") and value.__module__ == module.__name__:
+                yield pytest.param(value, id=f"{module.__name__}.{name}")
+
+
+@pytest.mark.parametrize("function", public_functions())
+def test_public_functions_are_annotated(function):
+    signature = inspect.signature(function)
+    assert signature.return_annotation is not inspect.Signature.empty
+    assert [name for name, param in signature.parameters.items() if param.annotation is param.empty] == []
@@ -0,0 +1,99 @@
+"""Tests the warnings reported for legal but questionable code."""
+
+import sys
+import warnings
+
+import pytest
+
+from peg_parser import Parser, parse
+
+PY312 = sys.version_info >= (3, 12)
+ESCAPE = SyntaxWarning if PY312 else DeprecationWarning
+
+SOURCE = '''\
+x = "a\\d" + b'\\N' + r"\\d" + p"\\e"
+y = f"{x}\\q{x:\\w}" + """
+  \\777"""
+if x is 1 or x is not -1.5 or (1, None) is x or x is None or x is ... or x is [] or x is f"a":
+    assert (x, "message")
+    assert ()
+x == None != True
+'''
+
+
+def is_literal(op, kind):
+    suggestion = "==" if op == "is" else "!="
+    literal = f"'{kind}' literal" if PY312 else "a literal"
+    return f'"{op}" with {literal}. Did you mean "{suggestion}"?'
+
+
+def test_collected_warnings():
+    tree, found = parse(SOURCE, warnings=True)
+    assert tree.body
+    assert [tuple(entry) for entry in found] == [
+        (ESCAPE, "invalid escape sequence '\\d'", 1, 6),
+        (ESCAPE, "invalid escape sequence '\\N'", 1, 14),
+        (ESCAPE, "invalid escape sequence '\\e'", 1, 30),
+        (ESCAPE, "invalid escape sequence '\\q'", 2, 9),
+        (ESCAPE, "invalid escape sequence '\\w'", 2, 14),
+        (ESCAPE, "invalid octal escape sequence '\\777'", 3, 2),
+        (SyntaxWarning, is_literal("is", "int"), 4, 3),
+        (SyntaxWarning, is_literal("is not", "float"), 4, 13),
+        (SyntaxWarning, is_literal("is", "tuple"), 4, 30),
+        (SyntaxWarning, "assertion is always true, perhaps remove parentheses?", 5, 4),
+    ]
+
+
+@pytest.mark.skipif(
+    sys.version_info < (3, 11), reason="python3.10 does not warn about octal escapes over 0o377"
+)
+def test_same_warnings_as_compile():
+    source = SOURCE.replace(' + p"\\e"', "")
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        compile(source, "<unknown>", "exec")
+    expected = [(w.category, str(w.message), w.lineno) for w in caught]
+    # the position of an escape is reported rather than the start of its string
+    _, found = parse(source, warnings=True)
+    found = [(entry.category, entry.message, 2 if entry.lineno == 3 else entry.lineno) for entry in found]
+    assert sorted(found, key=str) == sorted(expected, key=str)
+
+
+def test_escapes_keep_backslash():
+    tree, _ = parse('"a\\d" + f"\\w{x}"', mode="eval", warnings=True)
+    assert (tree.body.left.value, tree.body.right.values[0].value) == ("a\\d", "\\w")
+
+
+def test_pedantic_comparisons():
+    _, found = parse(SOURCE, warnings=True, pedantic=True)
+    assert [(entry.message, entry.lineno) for entry in found if entry.lineno == 7] == [
+        ('"==" with None. Did you mean "is"?', 7),
+        ('"!=" with None. Did you mean "is not"?', 7),
+    ]
+    assert not [entry for entry in parse("x == y != 1", warnings=True, pedantic=True)[1]]
+
+
+def test_no_warnings_by_default():
+    with warnings.catch_warnings():
+        warnings.simplefilter("error")
+        tree = parse(SOURCE)
+    assert tree.body
+
+
+def test_emit_warnings():
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        tree = Parser(filename="script.xsh", emit_warnings=True).parse('x = "\\d"\nx is 1\n')
+    assert tree.body
+    assert [(w.category, w.filename, w.lineno) for w in caught] == [
+        (ESCAPE, "script.xsh", 1),
+        (SyntaxWarning, "script.xsh", 2),
+    ]
+
+
+def test_warnings_as_errors():
+    with warnings.catch_warnings():
+        warnings.simplefilter("error")
+        with pytest.raises(SyntaxError, match="with a literal|with 'int' literal") as e:
+            parse("x = 2\nx is 1\n", "script.xsh", emit_warnings=True)
+    assert (e.value.filename, e.value.lineno, e.value.offset, e.value.text) == ("script.xsh", 2, 1, "x is 1")